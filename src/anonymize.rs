@@ -0,0 +1,202 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::OnceLock,
+};
+
+use regex::{Captures, Regex};
+
+use crate::{Machine, PciAddr, PciDevice};
+
+impl Machine {
+    /// Replaces the Device Serial Numbers and VPD serial numbers of the
+    /// machine's devices by pseudonyms, for sharing graphs publicly.  The
+    /// pseudonyms are handed out in address order, and devices that share
+    /// a serial number share a pseudonym, so that PCIe switches are still
+    /// recognized as such.
+    pub fn anonymize_serial_numbers(&mut self) {
+        static DEVICE_SERIAL_NUMBER_RE: OnceLock<Regex> = OnceLock::new();
+        static VPD_SERIAL_NUMBER_RE: OnceLock<Regex> = OnceLock::new();
+
+        let mut pseudonyms = BTreeMap::new();
+
+        for dev in self.pci_devices.values() {
+            if let Some(serial_number) = dev.serial_number() {
+                let next = pseudonyms.len() as u64 + 1;
+
+                pseudonyms.entry(serial_number).or_insert(next);
+            }
+        }
+
+        let device_serial_number_re = DEVICE_SERIAL_NUMBER_RE.get_or_init(|| {
+            Regex::new(r"\] Device Serial Number ((?:[0-9a-f]{2}-){7}[0-9a-f]{2})\n").unwrap()
+        });
+
+        let vpd_serial_number_re = VPD_SERIAL_NUMBER_RE
+            .get_or_init(|| Regex::new(r"\[SN\] Serial number: [^\n]*").unwrap());
+
+        self.rewrite_devices(|dev| {
+            let desc = match dev.serial_number() {
+                Some(serial_number) => {
+                    let pseudonym = pseudonyms[&serial_number].to_be_bytes();
+
+                    device_serial_number_re
+                        .replace(dev.desc(), |_: &Captures| {
+                            format!(
+                                "] Device Serial Number {}\n",
+                                pseudonym.map(|byte| format!("{:02x}", byte)).join("-")
+                            )
+                        })
+                        .into_owned()
+                }
+                None => dev.desc().to_string(),
+            };
+
+            vpd_serial_number_re
+                .replace_all(&desc, "[SN] Serial number: anonymized")
+                .into_owned()
+        });
+    }
+
+    /// Renumbers the machine's PCI domains and buses, for sharing graphs
+    /// without giving away the exact topology.  Domains and buses are
+    /// numbered from zero up in their original order, which keeps the bus
+    /// ranges behind bridges intact, and device and function numbers are
+    /// left as they are.
+    pub fn anonymize_addresses(&mut self) {
+        static HEADER_ADDR_RE: OnceLock<Regex> = OnceLock::new();
+        static BUS_RE: OnceLock<Regex> = OnceLock::new();
+
+        let mut buses = BTreeSet::new();
+
+        for dev in self.pci_devices.values() {
+            let domain = dev.addr().domain();
+
+            buses.insert((domain, dev.addr().bus()));
+            buses.extend(dev.secondary_bus().map(|bus| (domain, bus)));
+            buses.extend(dev.subordinate_bus().map(|bus| (domain, bus)));
+        }
+
+        buses.extend(
+            self.dmi_slots
+                .keys()
+                .map(|addr| (addr.domain(), addr.bus())),
+        );
+
+        buses.extend(
+            self.bridge_parents
+                .values()
+                .map(|addr| (addr.domain(), addr.bus())),
+        );
+
+        //
+        // Bus 0 stays bus 0, as root ports on it are taken to be on the PCH.
+        //
+        let domains = buses
+            .iter()
+            .map(|(domain, _)| *domain)
+            .collect::<BTreeSet<_>>();
+
+        buses.extend(domains.iter().map(|domain| (*domain, 0)));
+
+        let domains = domains
+            .into_iter()
+            .enumerate()
+            .map(|(index, domain)| (domain, index as u32))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut bus_map = BTreeMap::new();
+        let mut next_bus = BTreeMap::new();
+
+        for (domain, bus) in &buses {
+            let next = next_bus.entry(*domain).or_insert(0usize);
+
+            bus_map.insert((*domain, *bus), *next as u8);
+
+            *next += 1;
+        }
+
+        let map_addr = |addr: PciAddr| {
+            PciAddr::new(
+                domains[&addr.domain()],
+                bus_map[&(addr.domain(), addr.bus())],
+                addr.device(),
+                addr.function(),
+            )
+        };
+
+        let header_addr_re = HEADER_ADDR_RE.get_or_init(|| {
+            Regex::new(concat!(
+                r"^(?:[0-9a-f]{4,8}:)?",
+                r"(?:[0-9a-f]{2}:[0-9a-f]{2}\.[0-7]/)*",
+                r"[0-9a-f]{2}:[0-9a-f]{2}\.[0-7] "
+            ))
+            .unwrap()
+        });
+
+        let bus_re = BUS_RE.get_or_init(|| {
+            Regex::new(concat!(
+                r"Bus: primary=([0-9a-f]{2}), ",
+                r"secondary=([0-9a-f]{2}), ",
+                r"subordinate=([0-9a-f]{2}),"
+            ))
+            .unwrap()
+        });
+
+        self.rewrite_devices(|dev| {
+            let domain = dev.addr().domain();
+
+            let desc =
+                header_addr_re.replace(dev.desc(), format!("{} ", map_addr(dev.addr())).as_str());
+
+            bus_re
+                .replace_all(&desc, |caps: &Captures| {
+                    let bus = |index: usize| {
+                        let bus = u8::from_str_radix(&caps[index], 16).unwrap();
+
+                        bus_map.get(&(domain, bus)).copied().unwrap_or(bus)
+                    };
+
+                    format!(
+                        "Bus: primary={:02x}, secondary={:02x}, subordinate={:02x},",
+                        bus(1),
+                        bus(2),
+                        bus(3)
+                    )
+                })
+                .into_owned()
+        });
+
+        self.dmi_slots = std::mem::take(&mut self.dmi_slots)
+            .into_iter()
+            .map(|(addr, name)| (map_addr(addr), name))
+            .collect();
+
+        //
+        // The rewritten headers leave out the bridge paths, so the bridges
+        // that the paths named are carried over here instead.
+        //
+        self.bridge_parents = std::mem::take(&mut self.bridge_parents)
+            .into_iter()
+            .map(|(addr, parent)| (map_addr(addr), map_addr(parent)))
+            .collect();
+
+        self.duplicate_devices = std::mem::take(&mut self.duplicate_devices)
+            .into_iter()
+            .map(map_addr)
+            .collect();
+    }
+
+    //
+    // Replaces each device by one parsed from its rewritten section.
+    //
+    pub(crate) fn rewrite_devices<F: FnMut(&PciDevice) -> String>(&mut self, mut rewrite: F) {
+        self.pci_devices = std::mem::take(&mut self.pci_devices)
+            .into_values()
+            .map(|dev| {
+                let dev = PciDevice::parse(&rewrite(&dev)).unwrap();
+
+                (dev.addr(), dev)
+            })
+            .collect();
+    }
+}
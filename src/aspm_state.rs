@@ -0,0 +1,38 @@
+use std::fmt::{Display, Formatter};
+
+/// A set of PCIe Active State Power Management link states, as listed in
+/// LnkCap (supported) or LnkCtl (enabled).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AspmState {
+    None,
+    L0s,
+    L1,
+    L0sL1,
+}
+
+impl AspmState {
+    //
+    // Parses the text following "ASPM " in LnkCap ("not supported", "L1",
+    // "L0s L1", ...) or LnkCtl ("Disabled", "L1 Enabled", ...).
+    //
+    pub(crate) fn parse(text: &str) -> Option<AspmState> {
+        match text.strip_suffix(" Enabled").unwrap_or(text) {
+            "not supported" | "Disabled" => Some(AspmState::None),
+            "L0s" => Some(AspmState::L0s),
+            "L1" => Some(AspmState::L1),
+            "L0s L1" => Some(AspmState::L0sL1),
+            _ => None,
+        }
+    }
+}
+
+impl Display for AspmState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            AspmState::None => write!(f, "off"),
+            AspmState::L0s => write!(f, "L0s"),
+            AspmState::L1 => write!(f, "L1"),
+            AspmState::L0sL1 => write!(f, "L0s L1"),
+        }
+    }
+}
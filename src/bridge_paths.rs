@@ -0,0 +1,114 @@
+use crate::{Machine, PciAddr, PciDevice};
+
+impl Machine {
+    //
+    // Adds a device to the machine, and records the bridge that its bridge
+    // path, if it has one, says it is behind.  Returns the device that it
+    // replaced, if any.
+    //
+    pub(crate) fn insert_device(&mut self, pci_device: PciDevice) -> Option<PciDevice> {
+        let addr = pci_device.addr();
+
+        match pci_device.bridge_path().last() {
+            Some(parent) => self.bridge_parents.insert(addr, *parent),
+            None => self.bridge_parents.remove(&addr),
+        };
+
+        self.pci_devices.insert(addr, pci_device)
+    }
+
+    pub(crate) fn remove_device(&mut self, addr: &PciAddr) {
+        self.bridge_parents.remove(addr);
+        self.pci_devices.remove(addr);
+    }
+
+    //
+    // With bridge paths, the devices behind a bridge are those whose path
+    // ends in it, whatever the bridge's bus range says.  A bridge that no
+    // path ends in, such as a port with an empty slot, falls back to its
+    // bus range.
+    //
+    fn path_children(&self, dev: &PciDevice) -> Option<Vec<PciAddr>> {
+        if !self.bridge_paths {
+            return None;
+        }
+
+        let children = self
+            .bridge_parents
+            .iter()
+            .filter(|(_, parent)| **parent == dev.addr())
+            .map(|(child, _)| *child)
+            .collect::<Vec<_>>();
+
+        (!children.is_empty()).then_some(children)
+    }
+
+    //
+    // The devices directly behind a bridge, ordered as by bus_devices.
+    //
+    pub(crate) fn child_devices(&self, dev: &PciDevice) -> Vec<PciAddr> {
+        if let Some(children) = self.path_children(dev) {
+            return children;
+        }
+
+        dev.secondary_bus()
+            .map_or_else(Vec::new, |bus| self.bus_devices(dev.addr().domain(), bus))
+    }
+
+    //
+    // All devices behind a bridge, including those behind other bridges
+    // further down, ordered by bus and then as by bus_devices.
+    //
+    pub(crate) fn descendant_devices(&self, dev: &PciDevice) -> Vec<PciAddr> {
+        if self.path_children(dev).is_some() {
+            return self
+                .bridge_parents
+                .keys()
+                .filter(|child| {
+                    std::iter::successors(self.bridge_parents.get(child), |parent| {
+                        self.bridge_parents.get(parent)
+                    })
+                    .take(256)
+                    .any(|parent| *parent == dev.addr())
+                })
+                .copied()
+                .collect();
+        }
+
+        match dev.secondary_bus() {
+            Some(secondary_bus) => self.range_devices(
+                dev.addr().domain(),
+                secondary_bus,
+                dev.subordinate_bus().unwrap_or(secondary_bus),
+            ),
+            None => Vec::new(),
+        }
+    }
+
+    //
+    // A bridge's secondary bus, which with bridge paths is the bus of the
+    // devices whose path ends in it.
+    //
+    pub(crate) fn secondary_bus(&self, dev: &PciDevice) -> Option<u8> {
+        match self.path_children(dev) {
+            Some(children) => Some(children[0].bus()),
+            None => dev.secondary_bus(),
+        }
+    }
+
+    //
+    // A bridge's subordinate bus, which with bridge paths is the highest
+    // bus of any device behind it.
+    //
+    pub(crate) fn subordinate_bus(&self, dev: &PciDevice) -> Option<u8> {
+        if self.path_children(dev).is_some() {
+            return self
+                .descendant_devices(dev)
+                .iter()
+                .map(|dev_addr| dev_addr.bus())
+                .max();
+        }
+
+        dev.subordinate_bus()
+    }
+}
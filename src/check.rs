@@ -0,0 +1,111 @@
+use std::{collections::BTreeSet, io::Error};
+
+use crate::graph_writer::{Attrs, GraphWriter};
+
+//
+// A GraphWriter that doesn't write anything, but keeps track of which
+// nodes are declared and referenced, for Machine::check_graph.
+//
+#[derive(Default)]
+pub struct CheckWriter {
+    declared: BTreeSet<String>,
+    referenced: BTreeSet<String>,
+    edges: Vec<(String, String)>,
+    clusters: usize,
+    problems: Vec<String>,
+}
+
+impl CheckWriter {
+    pub fn problems(self) -> Vec<String> {
+        self.problems
+    }
+}
+
+impl GraphWriter for CheckWriter {
+    fn begin_graph(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn end_graph(&mut self) -> Result<(), Error> {
+        if self.clusters != 0 {
+            self.problems.push("unterminated cluster".to_string());
+        }
+
+        for (from, to) in &self.edges {
+            for id in [from, to] {
+                if !self.declared.contains(id) {
+                    self.problems.push(format!(
+                        "edge {} -- {} uses undeclared node {}",
+                        from, to, id
+                    ));
+                }
+            }
+        }
+
+        for id in &self.declared {
+            if !self.referenced.contains(id) {
+                self.problems
+                    .push(format!("node {} is never referenced", id));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn blank(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn section(&mut self, _title: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn comment(&mut self, _text: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn node(&mut self, id: &str, _attrs: &Attrs) -> Result<(), Error> {
+        if !self.declared.insert(id.to_string()) {
+            self.problems.push(format!("node {} is declared twice", id));
+        }
+
+        Ok(())
+    }
+
+    fn edge(&mut self, from: &str, to: &str, _attrs: &Attrs) -> Result<(), Error> {
+        self.referenced.insert(from.to_string());
+        self.referenced.insert(to.to_string());
+        self.edges.push((from.to_string(), to.to_string()));
+
+        Ok(())
+    }
+
+    fn begin_cluster(&mut self, _index: u32, _label: Option<&str>) -> Result<(), Error> {
+        self.clusters += 1;
+
+        Ok(())
+    }
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error> {
+        if self.clusters == 0 {
+            self.problems.push(format!(
+                "node {} is a cluster member outside of a cluster",
+                id
+            ));
+        }
+
+        self.referenced.insert(id.to_string());
+
+        Ok(())
+    }
+
+    fn end_cluster(&mut self) -> Result<(), Error> {
+        if self.clusters == 0 {
+            self.problems.push("unbalanced end of cluster".to_string());
+        } else {
+            self.clusters -= 1;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use std::io::{Error, Write};
+
+use crate::Machine;
+
+static CSV_HEADER: [&str; 10] = [
+    "address",
+    "vendor_id",
+    "device_id",
+    "short_name",
+    "numa_node",
+    "speed",
+    "width",
+    "capable_speed",
+    "capable_width",
+    "serial_number",
+];
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_row<T: Write>(w: &mut T, fields: &[String]) -> Result<(), Error> {
+    writeln!(
+        w,
+        "{}",
+        fields
+            .iter()
+            .map(|field| quote(field))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+impl Machine {
+    /// Writes one CSV row per PCIe endpoint to `w`, preceded by a header row.
+    pub fn write_csv<T: Write>(&self, w: &mut T) -> Result<(), Error> {
+        write_row(w, &CSV_HEADER.map(|field| field.to_string()))?;
+
+        for dev in self.endpoints() {
+            let lnk_sta = dev.lnk_sta();
+            let lnk_cap = dev.lnk_cap();
+
+            write_row(
+                w,
+                &[
+                    dev.addr().to_string(),
+                    format!("{:04x}", dev.vendor_id()),
+                    format!("{:04x}", dev.device_id()),
+                    self.short_name(dev).unwrap_or_default().to_string(),
+                    dev.numa_node()
+                        .map_or("".to_string(), |numa_node| numa_node.to_string()),
+                    lnk_sta
+                        .as_ref()
+                        .map_or("".to_string(), |lnk_sta| lnk_sta.speed().to_string()),
+                    lnk_sta
+                        .as_ref()
+                        .map_or("".to_string(), |lnk_sta| lnk_sta.width().to_string()),
+                    lnk_cap
+                        .as_ref()
+                        .map_or("".to_string(), |lnk_cap| lnk_cap.speed().to_string()),
+                    lnk_cap
+                        .as_ref()
+                        .map_or("".to_string(), |lnk_cap| lnk_cap.width().to_string()),
+                    dev.serial_number().map_or("".to_string(), |serial_number| {
+                        format!("{:016x}", serial_number)
+                    }),
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+use crate::ParseError;
+
+/// User-supplied device names, which take precedence over the built-in
+/// short name table.
+#[derive(Clone, Debug, Default)]
+pub struct DeviceNames {
+    names: HashMap<(u16, u16), String>,
+}
+
+impl DeviceNames {
+    /// Reads device names from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<DeviceNames, ParseError> {
+        DeviceNames::parse(&mut File::open(path)?)
+    }
+
+    /// Reads device names from `src`, which contains one `vendor:device=name`
+    /// entry per line, with the IDs in hex.  Empty lines and lines starting
+    /// with `#` are ignored.
+    pub fn parse<T: Read>(src: &mut T) -> Result<DeviceNames, ParseError> {
+        let mut data = String::new();
+
+        src.read_to_string(&mut data)?;
+
+        let mut names = HashMap::new();
+
+        for (index, line) in data.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (ids, name) = parse_name_line(line)
+                .ok_or_else(|| ParseError::MalformedLine(index + 1, line.to_string()))?;
+
+            names.insert(ids, name.to_string());
+        }
+
+        Ok(DeviceNames { names })
+    }
+
+    pub fn device_name(&self, vendor_id: u16, device_id: u16) -> Option<&str> {
+        self.names
+            .get(&(vendor_id, device_id))
+            .map(|name| name.as_str())
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u16, u16, &str)> {
+        self.names
+            .iter()
+            .map(|((vendor_id, device_id), name)| (*vendor_id, *device_id, name.as_str()))
+    }
+}
+
+fn parse_name_line(line: &str) -> Option<((u16, u16), &str)> {
+    let (ids, name) = line.split_once('=')?;
+    let (vendor_id, device_id) = ids.trim().split_once(':')?;
+    let name = name.trim();
+
+    if vendor_id.len() != 4 || device_id.len() != 4 || name.is_empty() {
+        return None;
+    }
+
+    let vendor_id = u16::from_str_radix(vendor_id, 16).ok()?;
+    let device_id = u16::from_str_radix(device_id, 16).ok()?;
+
+    Some(((vendor_id, device_id), name))
+}
@@ -0,0 +1,184 @@
+use std::io::{Error, Write};
+
+use crate::{
+    GraphOptions,
+    graph_writer::{Attrs, GraphWriter},
+};
+
+pub struct DotWriter<'a, T: Write> {
+    w: &'a mut T,
+    options: &'a GraphOptions,
+}
+
+impl<'a, T: Write> DotWriter<'a, T> {
+    pub fn new(w: &'a mut T, options: &'a GraphOptions) -> DotWriter<'a, T> {
+        DotWriter { w, options }
+    }
+
+    fn attrs(&self, attrs: &Attrs) -> String {
+        let mut list = Vec::new();
+
+        if !attrs.fields.is_empty() {
+            let fields = attrs
+                .fields
+                .iter()
+                .map(|field| escape_field(field))
+                .collect::<Vec<_>>();
+
+            list.push(format!("label=\"{}\"", fields.join("|")));
+        } else if let Some(label) = &attrs.label {
+            list.push(format!("label=\"{}\"", escape(label)));
+        }
+
+        if let Some(shape) = attrs.shape {
+            list.push(format!("shape={}", shape));
+        }
+
+        if let Some(color) = attrs.color {
+            list.push(format!("color={}", color));
+        }
+
+        if let Some(fillcolor) = attrs.fillcolor {
+            list.push(format!("style=filled fillcolor={}", fillcolor));
+        }
+
+        if let Some(lhead) = &attrs.lhead {
+            list.push(format!("lhead={}", lhead));
+        }
+
+        if let Some(penwidth) = attrs.penwidth {
+            list.push(format!("penwidth={}", penwidth));
+        }
+
+        if let Some(tooltip) = &attrs.tooltip {
+            list.push(format!("tooltip=\"{}\"", escape(tooltip)));
+        }
+
+        if list.is_empty() {
+            "".to_string()
+        } else {
+            format!(" [ {} ]", list.join(" "))
+        }
+    }
+}
+
+//
+// Escapes a string for use inside a double-quoted DOT string.  DMI slot
+// designations in particular can contain quotes.
+//
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+//
+// Record fields additionally need the characters that delimit fields and
+// ports escaped.
+//
+fn escape_field(field: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in field.chars() {
+        match c {
+            '{' | '}' | '|' | '<' | '>' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+impl<T: Write> GraphWriter for DotWriter<'_, T> {
+    fn begin_graph(&mut self) -> Result<(), Error> {
+        writeln!(
+            self.w,
+            "{} pci {{",
+            if self.options.directed {
+                "digraph"
+            } else {
+                "graph"
+            }
+        )?;
+        writeln!(self.w, "\trankdir={};", self.options.rankdir.as_str())?;
+        writeln!(self.w, "\tcompound=true;")?;
+
+        if let Some(dpi) = self.options.dpi {
+            writeln!(self.w, "\tgraph [dpi={}];", dpi)?;
+        }
+
+        if let Some(fontsize) = self.options.fontsize {
+            writeln!(self.w, "\tnode [fontsize={}];", fontsize)?;
+        }
+
+        if let Some(graph_attrs) = &self.options.graph_attrs {
+            writeln!(self.w, "\tgraph [{}];", graph_attrs)?;
+        }
+
+        if let Some(node_attrs) = &self.options.node_attrs {
+            writeln!(self.w, "\tnode [{}];", node_attrs)?;
+        }
+
+        Ok(())
+    }
+
+    fn end_graph(&mut self) -> Result<(), Error> {
+        writeln!(self.w, "}}")
+    }
+
+    fn blank(&mut self) -> Result<(), Error> {
+        writeln!(self.w)
+    }
+
+    fn section(&mut self, title: &str) -> Result<(), Error> {
+        writeln!(self.w)?;
+        writeln!(
+            self.w,
+            "\t######################################################################"
+        )?;
+        writeln!(self.w, "\t# {}", title)
+    }
+
+    fn comment(&mut self, text: &str) -> Result<(), Error> {
+        writeln!(self.w, "\t# {}", text)
+    }
+
+    fn node(&mut self, id: &str, attrs: &Attrs) -> Result<(), Error> {
+        writeln!(self.w, "\t\"{}\"{};", escape(id), self.attrs(attrs))
+    }
+
+    fn edge(&mut self, from: &str, to: &str, attrs: &Attrs) -> Result<(), Error> {
+        writeln!(
+            self.w,
+            "\t\"{}\" {} \"{}\"{};",
+            escape(from),
+            if self.options.directed { "->" } else { "--" },
+            escape(to),
+            self.attrs(attrs)
+        )
+    }
+
+    fn begin_cluster(&mut self, index: u32, label: Option<&str>) -> Result<(), Error> {
+        writeln!(self.w, "\tsubgraph cluster{} {{", index)?;
+
+        if let Some(label) = label {
+            writeln!(self.w, "\t\tlabel=\"{}\";", escape(label))?;
+        }
+
+        Ok(())
+    }
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error> {
+        writeln!(self.w, "\t\t\"{}\";", escape(id))
+    }
+
+    fn end_cluster(&mut self) -> Result<(), Error> {
+        writeln!(self.w, "\t}}")
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt::{Display, Formatter};
+
+/// A PCIe link's ASPM exit latency, as listed in LnkCap.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExitLatency {
+    /// Less than this many nanoseconds.
+    Below(u32),
+    Unlimited,
+}
+
+impl ExitLatency {
+    //
+    // Parses an exit latency as lspci shows it, e.g. "<64ns", "<2us" or
+    // "unlimited".  Older versions of lspci show the largest encodable
+    // latency as e.g. ">64us" instead of "unlimited".
+    //
+    pub(crate) fn parse(text: &str) -> Option<ExitLatency> {
+        if text == "unlimited" || text.starts_with('>') {
+            return Some(ExitLatency::Unlimited);
+        }
+
+        let text = text.strip_prefix('<')?;
+
+        let (value, scale) = if let Some(value) = text.strip_suffix("ns") {
+            (value, 1)
+        } else if let Some(value) = text.strip_suffix("us") {
+            (value, 1000)
+        } else if let Some(value) = text.strip_suffix("ms") {
+            (value, 1000000)
+        } else {
+            return None;
+        };
+
+        value
+            .parse::<u32>()
+            .ok()
+            .and_then(|value| value.checked_mul(scale))
+            .map(ExitLatency::Below)
+    }
+}
+
+impl Display for ExitLatency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ExitLatency::Below(ns) if *ns >= 1000 && ns % 1000 == 0 => {
+                write!(f, "<{}us", ns / 1000)
+            }
+            ExitLatency::Below(ns) => write!(f, "<{}ns", ns),
+            ExitLatency::Unlimited => write!(f, "unlimited"),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::io::Error;
+
+use crate::graph_writer::{Attrs, GraphWriter};
+
+pub struct ExplainCluster {
+    pub index: u32,
+    pub label: Option<String>,
+    pub members: Vec<String>,
+}
+
+//
+// A GraphWriter that doesn't write anything, but keeps track of which
+// clusters are drawn and which nodes end up in them, for
+// Machine::explain_clusters.  A cluster that is begun more than once, such
+// as the cluster for the root ports on a NUMA node, is only listed once.
+//
+#[derive(Default)]
+pub struct ExplainWriter {
+    clusters: Vec<ExplainCluster>,
+    open_clusters: Vec<usize>,
+}
+
+impl ExplainWriter {
+    pub fn clusters(self) -> Vec<ExplainCluster> {
+        self.clusters
+    }
+}
+
+impl GraphWriter for ExplainWriter {
+    fn begin_graph(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn end_graph(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn blank(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn section(&mut self, _title: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn comment(&mut self, _text: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn node(&mut self, _id: &str, _attrs: &Attrs) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn edge(&mut self, _from: &str, _to: &str, _attrs: &Attrs) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn begin_cluster(&mut self, index: u32, label: Option<&str>) -> Result<(), Error> {
+        let position = match self
+            .clusters
+            .iter()
+            .position(|cluster| cluster.index == index)
+        {
+            Some(position) => position,
+            None => {
+                self.clusters.push(ExplainCluster {
+                    index,
+                    label: label.map(|label| label.to_string()),
+                    members: Vec::new(),
+                });
+
+                self.clusters.len() - 1
+            }
+        };
+
+        self.open_clusters.push(position);
+
+        Ok(())
+    }
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error> {
+        if let Some(position) = self.open_clusters.last() {
+            let members = &mut self.clusters[*position].members;
+
+            if !members.iter().any(|member| member == id) {
+                members.push(id.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn end_cluster(&mut self) -> Result<(), Error> {
+        self.open_clusters.pop();
+
+        Ok(())
+    }
+}
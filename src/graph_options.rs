@@ -0,0 +1,147 @@
+use crate::PciAddr;
+
+/// How devices are grouped into clusters in the graph.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum GroupBy {
+    /// Group root ports by NUMA node or PCH, and switches, bridges and
+    /// multi-function devices by the physical device they belong to.
+    #[default]
+    Topology,
+    /// Group devices by the IOMMU group they are in.
+    Iommu,
+}
+
+/// The direction in which the graph is laid out.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RankDir {
+    #[default]
+    LeftRight,
+    TopBottom,
+    RightLeft,
+    BottomTop,
+}
+
+impl RankDir {
+    /// Returns the graphviz `rankdir` attribute value for this direction.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RankDir::LeftRight => "LR",
+            RankDir::TopBottom => "TB",
+            RankDir::RightLeft => "RL",
+            RankDir::BottomTop => "BT",
+        }
+    }
+}
+
+/// The fixed text used in the graph, e.g. for diagrams in other languages.
+#[derive(Debug)]
+pub struct Labels {
+    /// The label of the clusters that PCIe switches are drawn in.
+    pub pcie_switch: String,
+    /// The label of the clusters that PCIe to PCI bridges are drawn in.
+    pub pci_bridge: String,
+    /// The first line of root port labels, above the root port's address.
+    pub root_port: String,
+}
+
+impl Default for Labels {
+    fn default() -> Labels {
+        Labels {
+            pcie_switch: "PCIe switch".to_string(),
+            pci_bridge: "PCI bridge".to_string(),
+            root_port: "Root port".to_string(),
+        }
+    }
+}
+
+/// Options controlling how `Machine::write_graph` renders the graph.
+#[derive(Debug, Default)]
+pub struct GraphOptions {
+    /// Show each link's usable bandwidth alongside its speed and width.
+    pub show_bandwidth: bool,
+    /// How devices are grouped into clusters.
+    pub group_by: GroupBy,
+    /// Emit a directed graph, with edges pointing downstream.
+    pub directed: bool,
+    /// The direction in which the graph is laid out.
+    pub rankdir: RankDir,
+    /// Show devices that aren't reachable from any root port in a cluster
+    /// of their own.
+    pub show_orphans: bool,
+    /// Fill device nodes with a color depending on their device class.
+    pub color_by_class: bool,
+    /// Draw a legend explaining the shapes and colors used in the graph.
+    pub legend: bool,
+    /// The fixed text used in the graph.
+    pub labels: Labels,
+    /// Show the ASPM states enabled on each link, and the ones supported
+    /// if they differ.
+    pub show_aspm: bool,
+    /// Leave out links whose maximum speed, in GT/s, is below this, along
+    /// with the devices behind them.
+    pub min_speed: Option<f32>,
+    /// Show each device's revision in its label.
+    pub show_rev: bool,
+    /// Label endpoints that don't support Function Level Reset, which
+    /// can't be reliably reset when they are passed through to a virtual
+    /// machine.
+    pub show_flr: bool,
+    /// Show the NUMA node of each root port and device in its label.
+    pub show_numa: bool,
+    /// Leave out the root ports on the PCH, along with everything behind
+    /// them.
+    pub hide_pch: bool,
+    /// Only draw the root ports attached to this NUMA node, along with
+    /// everything behind them, or the root ports that aren't attached to
+    /// any NUMA node if `Some(None)`.
+    pub numa_node: Option<Option<usize>>,
+    /// Only draw the devices whose addresses start with one of these
+    /// prefixes, e.g. `0000:c0:`, along with everything behind them and
+    /// the ports leading up to them.  Everything is drawn if this is empty.
+    pub include: Vec<String>,
+    /// Leave out the devices whose addresses start with one of these
+    /// prefixes, along with everything behind them.
+    pub exclude: Vec<String>,
+    /// Only draw this port and everything behind it, rather than everything
+    /// behind all root ports.  See `Machine::subtree_root` for which devices
+    /// the graph can start at.
+    pub root: Option<PciAddr>,
+    /// Draw sibling endpoints that only differ in their addresses, such as
+    /// the drives in a bay of identical NVMe drives, as a single node.
+    pub collapse_identical: bool,
+    /// A template for the labels of the clusters that root ports are
+    /// grouped in, e.g. `NUMA {numa} Bus {bus}`, rather than the NUMA node
+    /// or PCH.  `{numa}`, `{socket}` and `{bus}` are replaced by the root
+    /// port's NUMA node, CPU socket and bus number, or `?` if unknown, and
+    /// root ports are grouped by what the template expands to.
+    pub group_label: Option<String>,
+    /// Order the downstream ports of each switch by the port number that
+    /// they advertise in LnkCap, rather than by address.
+    pub order_by_port: bool,
+    /// Give device nodes a tooltip with the device's full name, link
+    /// capabilities and status, driver, NUMA node and serial number.  Only
+    /// affects graphviz output.
+    pub tooltips: bool,
+    /// Draw links with a pen width that grows with their lane count, so
+    /// that wide links stand out.  Only affects graphviz output.
+    pub scale_edges: bool,
+    /// Draw the functions of each multi-function endpoint as the fields of
+    /// a single record-shaped node, rather than as a cluster of nodes.  Only
+    /// affects graphviz output, and only when grouping by topology.
+    pub record_functions: bool,
+    /// Don't group devices into clusters at all, for graphviz layout
+    /// engines such as neato and fdp that don't handle clusters well.
+    pub no_clusters: bool,
+    /// Don't color links and devices with problems red.
+    pub no_color: bool,
+    /// The resolution graphviz should render bitmap output at.  Only
+    /// affects graphviz output.
+    pub dpi: Option<u32>,
+    /// The default font size of node labels, in points.  Only affects
+    /// graphviz output.
+    pub fontsize: Option<f32>,
+    /// Extra graphviz graph attributes, e.g. `splines=ortho bgcolor=white`.
+    pub graph_attrs: Option<String>,
+    /// Extra graphviz attributes for all nodes.
+    pub node_attrs: Option<String>,
+}
@@ -0,0 +1,223 @@
+use std::io::Error;
+
+//
+// Node and edge attributes.  Labels are plain text, possibly spanning
+// multiple lines, and each GraphWriter escapes them as its format needs.
+// Data items are for formats that can carry machine-readable attributes,
+// and are ignored by the others.  Likewise, record fields are drawn instead
+// of the label by formats that have record-shaped nodes.
+//
+#[derive(Clone, Debug, Default)]
+pub struct Attrs {
+    pub label: Option<String>,
+    pub shape: Option<&'static str>,
+    pub color: Option<&'static str>,
+    pub fillcolor: Option<&'static str>,
+    pub lhead: Option<String>,
+    pub tooltip: Option<String>,
+    pub penwidth: Option<f32>,
+    pub fields: Vec<String>,
+    pub data: Vec<(&'static str, String)>,
+}
+
+impl Attrs {
+    pub fn label<T: Into<String>>(label: T) -> Attrs {
+        Attrs {
+            label: Some(label.into()),
+            ..Attrs::default()
+        }
+    }
+
+    pub fn shape(mut self, shape: &'static str) -> Attrs {
+        self.shape = Some(shape);
+        self
+    }
+
+    pub fn color(mut self, color: &'static str) -> Attrs {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn fillcolor(mut self, fillcolor: &'static str) -> Attrs {
+        self.fillcolor = Some(fillcolor);
+        self
+    }
+
+    pub fn lhead(mut self, lhead: String) -> Attrs {
+        self.lhead = Some(lhead);
+        self
+    }
+
+    pub fn tooltip(mut self, tooltip: String) -> Attrs {
+        self.tooltip = Some(tooltip);
+        self
+    }
+
+    pub fn penwidth(mut self, penwidth: f32) -> Attrs {
+        self.penwidth = Some(penwidth);
+        self
+    }
+
+    pub fn fields(mut self, fields: Vec<String>) -> Attrs {
+        self.fields = fields;
+        self
+    }
+
+    pub fn data<T: ToString>(mut self, key: &'static str, value: T) -> Attrs {
+        self.data.push((key, value.to_string()));
+        self
+    }
+}
+
+//
+// The output side of Machine's graph rendering, implemented once per
+// output format.  Node identifiers are passed in as-is, and are mapped
+// to the output format's identifier syntax by the writer.
+//
+pub trait GraphWriter {
+    fn begin_graph(&mut self) -> Result<(), Error>;
+
+    fn end_graph(&mut self) -> Result<(), Error>;
+
+    fn blank(&mut self) -> Result<(), Error>;
+
+    fn section(&mut self, title: &str) -> Result<(), Error>;
+
+    fn comment(&mut self, text: &str) -> Result<(), Error>;
+
+    fn node(&mut self, id: &str, attrs: &Attrs) -> Result<(), Error>;
+
+    fn edge(&mut self, from: &str, to: &str, attrs: &Attrs) -> Result<(), Error>;
+
+    fn begin_cluster(&mut self, index: u32, label: Option<&str>) -> Result<(), Error>;
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error>;
+
+    fn end_cluster(&mut self) -> Result<(), Error>;
+}
+
+//
+// A GraphWriter that prefixes all node identifiers passed through it, so
+// that several machines can be rendered into the same graph without their
+// node identifiers colliding.
+//
+pub struct PrefixWriter<'a, G: GraphWriter> {
+    g: &'a mut G,
+    prefix: String,
+}
+
+impl<'a, G: GraphWriter> PrefixWriter<'a, G> {
+    pub fn new(g: &'a mut G, prefix: String) -> PrefixWriter<'a, G> {
+        PrefixWriter { g, prefix }
+    }
+
+    fn id(&self, id: &str) -> String {
+        format!("{}{}", self.prefix, id)
+    }
+}
+
+impl<G: GraphWriter> GraphWriter for PrefixWriter<'_, G> {
+    fn begin_graph(&mut self) -> Result<(), Error> {
+        self.g.begin_graph()
+    }
+
+    fn end_graph(&mut self) -> Result<(), Error> {
+        self.g.end_graph()
+    }
+
+    fn blank(&mut self) -> Result<(), Error> {
+        self.g.blank()
+    }
+
+    fn section(&mut self, title: &str) -> Result<(), Error> {
+        self.g.section(title)
+    }
+
+    fn comment(&mut self, text: &str) -> Result<(), Error> {
+        self.g.comment(text)
+    }
+
+    fn node(&mut self, id: &str, attrs: &Attrs) -> Result<(), Error> {
+        self.g.node(&self.id(id), attrs)
+    }
+
+    fn edge(&mut self, from: &str, to: &str, attrs: &Attrs) -> Result<(), Error> {
+        self.g.edge(&self.id(from), &self.id(to), attrs)
+    }
+
+    fn begin_cluster(&mut self, index: u32, label: Option<&str>) -> Result<(), Error> {
+        self.g.begin_cluster(index, label)
+    }
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error> {
+        self.g.cluster_member(&self.id(id))
+    }
+
+    fn end_cluster(&mut self) -> Result<(), Error> {
+        self.g.end_cluster()
+    }
+}
+
+//
+// A GraphWriter that drops the colors of all nodes and edges passed
+// through it, for GraphOptions::no_color.  Fill colors are kept, as they
+// have to be asked for explicitly.
+//
+pub struct NoColorWriter<'a, G: GraphWriter> {
+    g: &'a mut G,
+}
+
+impl<'a, G: GraphWriter> NoColorWriter<'a, G> {
+    pub fn new(g: &'a mut G) -> NoColorWriter<'a, G> {
+        NoColorWriter { g }
+    }
+}
+
+fn without_color(attrs: &Attrs) -> Attrs {
+    Attrs {
+        color: None,
+        ..attrs.clone()
+    }
+}
+
+impl<G: GraphWriter> GraphWriter for NoColorWriter<'_, G> {
+    fn begin_graph(&mut self) -> Result<(), Error> {
+        self.g.begin_graph()
+    }
+
+    fn end_graph(&mut self) -> Result<(), Error> {
+        self.g.end_graph()
+    }
+
+    fn blank(&mut self) -> Result<(), Error> {
+        self.g.blank()
+    }
+
+    fn section(&mut self, title: &str) -> Result<(), Error> {
+        self.g.section(title)
+    }
+
+    fn comment(&mut self, text: &str) -> Result<(), Error> {
+        self.g.comment(text)
+    }
+
+    fn node(&mut self, id: &str, attrs: &Attrs) -> Result<(), Error> {
+        self.g.node(id, &without_color(attrs))
+    }
+
+    fn edge(&mut self, from: &str, to: &str, attrs: &Attrs) -> Result<(), Error> {
+        self.g.edge(from, to, &without_color(attrs))
+    }
+
+    fn begin_cluster(&mut self, index: u32, label: Option<&str>) -> Result<(), Error> {
+        self.g.begin_cluster(index, label)
+    }
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error> {
+        self.g.cluster_member(id)
+    }
+
+    fn end_cluster(&mut self) -> Result<(), Error> {
+        self.g.end_cluster()
+    }
+}
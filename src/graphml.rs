@@ -0,0 +1,307 @@
+use std::{
+    collections::HashMap,
+    io::{Error, Write},
+};
+
+use crate::{
+    GraphOptions,
+    graph_writer::{Attrs, GraphWriter},
+};
+
+//
+// The <data> keys that GraphMlWriter declares, as (id, for, type).
+//
+static KEYS: [(&str, &str, &str); 6] = [
+    ("label", "all", "string"),
+    ("vendor", "node", "string"),
+    ("device", "node", "string"),
+    ("numa", "node", "int"),
+    ("speed", "edge", "double"),
+    ("width", "edge", "int"),
+];
+
+struct GraphMlNode {
+    id: String,
+    label: Option<String>,
+    data: Vec<(&'static str, String)>,
+    parent: Option<u32>,
+}
+
+struct GraphMlCluster {
+    index: u32,
+    label: Option<String>,
+    parent: Option<u32>,
+}
+
+struct GraphMlEdge {
+    from: String,
+    to: String,
+    label: Option<String>,
+    data: Vec<(&'static str, String)>,
+}
+
+//
+// GraphML wants the nodes in a cluster to be declared inside that cluster's
+// nested <graph> element, while pcigraph declares cluster membership after
+// the fact, so the whole graph is buffered and written out by end_graph.
+//
+pub struct GraphMlWriter<'a, T: Write> {
+    w: &'a mut T,
+    options: &'a GraphOptions,
+    nodes: Vec<GraphMlNode>,
+    node_indices: HashMap<String, usize>,
+    clusters: Vec<GraphMlCluster>,
+    open_clusters: Vec<u32>,
+    edges: Vec<GraphMlEdge>,
+}
+
+impl<'a, T: Write> GraphMlWriter<'a, T> {
+    pub fn new(w: &'a mut T, options: &'a GraphOptions) -> GraphMlWriter<'a, T> {
+        GraphMlWriter {
+            w,
+            options,
+            nodes: Vec::new(),
+            node_indices: HashMap::new(),
+            clusters: Vec::new(),
+            open_clusters: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    fn node_mut(&mut self, id: &str) -> &mut GraphMlNode {
+        let index = *self.node_indices.entry(id.to_string()).or_insert_with(|| {
+            self.nodes.push(GraphMlNode {
+                id: id.to_string(),
+                label: None,
+                data: Vec::new(),
+                parent: self.open_clusters.last().copied(),
+            });
+
+            self.nodes.len() - 1
+        });
+
+        &mut self.nodes[index]
+    }
+
+    fn write_data(&mut self, indent: &str, key: &str, value: &str) -> Result<(), Error> {
+        writeln!(
+            self.w,
+            "{}<data key=\"{}\">{}</data>",
+            indent,
+            key,
+            escape(value)
+        )
+    }
+
+    fn write_graph_contents(&mut self, parent: Option<u32>, depth: usize) -> Result<(), Error> {
+        let indent = "  ".repeat(depth + 1);
+
+        let clusters = self
+            .clusters
+            .iter()
+            .filter(|cluster| cluster.parent == parent)
+            .map(|cluster| (cluster.index, cluster.label.clone()))
+            .collect::<Vec<_>>();
+
+        for (index, label) in clusters {
+            writeln!(self.w, "{}<node id=\"cluster{}\">", indent, index)?;
+
+            if let Some(label) = label {
+                self.write_data(&format!("{}  ", indent), "label", &label)?;
+            }
+
+            writeln!(
+                self.w,
+                "{}  <graph id=\"cluster{}:\" edgedefault=\"{}\">",
+                indent,
+                index,
+                self.edgedefault()
+            )?;
+
+            self.write_graph_contents(Some(index), depth + 2)?;
+
+            writeln!(self.w, "{}  </graph>", indent)?;
+            writeln!(self.w, "{}</node>", indent)?;
+        }
+
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent == parent)
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        for index in nodes {
+            let id = escape(&self.nodes[index].id);
+            let label = self.nodes[index].label.clone();
+            let data = self.nodes[index].data.clone();
+
+            if label.is_none() && data.is_empty() {
+                writeln!(self.w, "{}<node id=\"{}\"/>", indent, id)?;
+                continue;
+            }
+
+            writeln!(self.w, "{}<node id=\"{}\">", indent, id)?;
+
+            if let Some(label) = label {
+                self.write_data(&format!("{}  ", indent), "label", &label)?;
+            }
+
+            for (key, value) in data {
+                self.write_data(&format!("{}  ", indent), key, &value)?;
+            }
+
+            writeln!(self.w, "{}</node>", indent)?;
+        }
+
+        Ok(())
+    }
+
+    fn edgedefault(&self) -> &'static str {
+        if self.options.directed {
+            "directed"
+        } else {
+            "undirected"
+        }
+    }
+}
+
+fn escape(str: &str) -> String {
+    str.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+        .replace('\n', "&#10;")
+}
+
+impl<T: Write> GraphWriter for GraphMlWriter<'_, T> {
+    fn begin_graph(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn end_graph(&mut self) -> Result<(), Error> {
+        writeln!(self.w, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            self.w,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        )?;
+
+        for (id, key_for, key_type) in KEYS {
+            writeln!(
+                self.w,
+                "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                id, key_for, id, key_type
+            )?;
+        }
+
+        writeln!(
+            self.w,
+            "  <graph id=\"pci\" edgedefault=\"{}\">",
+            self.edgedefault()
+        )?;
+
+        self.write_graph_contents(None, 1)?;
+
+        let edges = std::mem::take(&mut self.edges);
+
+        for edge in &edges {
+            let from = escape(&edge.from);
+            let to = escape(&edge.to);
+
+            if edge.label.is_none() && edge.data.is_empty() {
+                writeln!(self.w, "    <edge source=\"{}\" target=\"{}\"/>", from, to)?;
+                continue;
+            }
+
+            writeln!(self.w, "    <edge source=\"{}\" target=\"{}\">", from, to)?;
+
+            if let Some(label) = &edge.label {
+                self.write_data("      ", "label", label)?;
+            }
+
+            for (key, value) in &edge.data {
+                self.write_data("      ", key, value)?;
+            }
+
+            writeln!(self.w, "    </edge>")?;
+        }
+
+        writeln!(self.w, "  </graph>")?;
+        writeln!(self.w, "</graphml>")
+    }
+
+    fn blank(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn section(&mut self, _title: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn comment(&mut self, _text: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn node(&mut self, id: &str, attrs: &Attrs) -> Result<(), Error> {
+        let node = self.node_mut(id);
+
+        if attrs.label.is_some() {
+            node.label = attrs.label.clone();
+        }
+
+        node.data.extend(attrs.data.iter().cloned());
+
+        Ok(())
+    }
+
+    fn edge(&mut self, from: &str, to: &str, attrs: &Attrs) -> Result<(), Error> {
+        //
+        // Every edge endpoint has to be declared somewhere.
+        //
+        self.node_mut(from);
+        self.node_mut(to);
+
+        self.edges.push(GraphMlEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: attrs.label.clone(),
+            data: attrs.data.clone(),
+        });
+
+        Ok(())
+    }
+
+    fn begin_cluster(&mut self, index: u32, label: Option<&str>) -> Result<(), Error> {
+        //
+        // The same cluster can be opened more than once, e.g. once for
+        // each root port in a NUMA node.
+        //
+        if !self.clusters.iter().any(|cluster| cluster.index == index) {
+            self.clusters.push(GraphMlCluster {
+                index,
+                label: label.map(|label| label.to_string()),
+                parent: self.open_clusters.last().copied(),
+            });
+        }
+
+        self.open_clusters.push(index);
+
+        Ok(())
+    }
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error> {
+        let cluster = self.open_clusters.last().copied();
+
+        self.node_mut(id).parent = cluster;
+
+        Ok(())
+    }
+
+    fn end_cluster(&mut self) -> Result<(), Error> {
+        self.open_clusters.pop();
+
+        Ok(())
+    }
+}
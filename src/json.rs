@@ -0,0 +1,76 @@
+use std::{
+    collections::BTreeMap,
+    io::{Error, Write},
+};
+
+use serde::Serialize;
+
+use crate::{LnkCap, LnkSta, Machine, PciDevice};
+
+#[derive(Serialize)]
+struct JsonMachine<'a> {
+    dmi_slots: BTreeMap<String, &'a str>,
+    pci_devices: Vec<JsonPciDevice<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonPciDevice<'a> {
+    addr: String,
+    vendor_id: String,
+    device_id: String,
+    short_name: Option<&'a str>,
+    lnk_cap: Option<LnkCap>,
+    lnk_sta: Option<LnkSta>,
+    numa_node: Option<usize>,
+    secondary_bus: Option<String>,
+    subordinate_bus: Option<String>,
+    serial_number: Option<String>,
+}
+
+impl<'a> JsonPciDevice<'a> {
+    fn new(machine: &'a Machine, dev: &PciDevice) -> JsonPciDevice<'a> {
+        JsonPciDevice {
+            addr: dev.addr().to_string(),
+            vendor_id: format!("{:04x}", dev.vendor_id()),
+            device_id: format!("{:04x}", dev.device_id()),
+            short_name: machine.short_name(dev),
+            lnk_cap: dev.lnk_cap(),
+            lnk_sta: dev.lnk_sta(),
+            numa_node: dev.numa_node(),
+            secondary_bus: dev.secondary_bus().map(|bus| format!("{:02x}", bus)),
+            subordinate_bus: dev.subordinate_bus().map(|bus| format!("{:02x}", bus)),
+            serial_number: dev
+                .serial_number()
+                .map(|serial_number| format!("{:016x}", serial_number)),
+        }
+    }
+}
+
+impl Machine {
+    /// Writes the parsed devices and DMI System Slots to `w` as JSON.
+    pub fn write_json<T: Write>(&self, w: &mut T) -> Result<(), Error> {
+        //
+        // Both maps are BTreeMaps keyed by PCI address, and struct fields
+        // are serialized in declaration order, so the output for a given
+        // capture is always byte-identical.
+        //
+        let machine = JsonMachine {
+            dmi_slots: self
+                .dmi_slots
+                .iter()
+                .map(|(addr, name)| (addr.to_string(), name.as_str()))
+                .collect(),
+            pci_devices: self
+                .pci_devices
+                .values()
+                .map(|dev| JsonPciDevice::new(self, dev))
+                .collect(),
+        };
+
+        serde_json::to_writer_pretty(&mut *w, &machine)?;
+
+        writeln!(w)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+//! Turns `lspci` (and optionally `dmidecode`) output into a graphviz graph
+//! of a machine's PCI(e) topology.
+
+mod anonymize;
+mod aspm_state;
+mod bridge_paths;
+mod check;
+mod csv;
+mod device_names;
+mod dot;
+mod exit_latency;
+mod explain;
+mod graph_options;
+mod graph_writer;
+mod graphml;
+mod json;
+mod link_speed;
+mod lnk_cap;
+mod lnk_sta;
+mod machine;
+mod machine_readable;
+mod mermaid;
+mod name_resolver;
+mod parse_error;
+mod pci_addr;
+mod pci_device;
+mod pci_device_builder;
+mod pci_ids;
+mod plantuml;
+mod section;
+mod sysfs;
+mod tree;
+
+pub use aspm_state::AspmState;
+pub use device_names::DeviceNames;
+pub use exit_latency::ExitLatency;
+pub use graph_options::{GraphOptions, GroupBy, Labels, RankDir};
+pub use lnk_cap::LnkCap;
+pub use lnk_sta::LnkSta;
+pub use machine::Machine;
+pub use name_resolver::NameResolver;
+pub use parse_error::ParseError;
+pub use pci_addr::{ParsePciAddrError, PciAddr};
+pub use pci_device::PciDevice;
+pub use pci_device_builder::{PciDeviceBuilder, PortType};
+pub use pci_ids::PciIds;
+pub use section::Section;
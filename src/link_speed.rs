@@ -0,0 +1,24 @@
+static GENERATIONS: [(f32, u8); 6] = [
+    (2.5, 1),
+    (5.0, 2),
+    (8.0, 3),
+    (16.0, 4),
+    (32.0, 5),
+    (64.0, 6),
+];
+
+//
+// Links up to 5GT/s use 8b/10b encoding, faster links use 128b/130b.
+//
+pub fn bandwidth_gbps(gt: f32, width: u8) -> f32 {
+    let encoding = if gt < 8.0 { 8.0 / 10.0 } else { 128.0 / 130.0 };
+
+    gt * encoding * width as f32 / 8.0
+}
+
+pub fn generation(gt: f32) -> Option<u8> {
+    GENERATIONS
+        .iter()
+        .find(|(speed, _)| *speed == gt)
+        .map(|(_, generation)| *generation)
+}
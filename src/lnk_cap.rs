@@ -1,19 +1,78 @@
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug)]
+use serde::Serialize;
+
+use crate::link_speed;
+
+/// A PCIe link's capabilities (maximum speed and width, and the port
+/// number), from LnkCap.
+#[derive(Debug, Serialize)]
 pub struct LnkCap {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    port: Option<u8>,
+    #[serde(rename = "speed")]
     gt: f32,
     width: u8,
 }
 
 impl LnkCap {
     pub fn new(gt: f32, width: u8) -> LnkCap {
-        LnkCap { gt, width }
+        LnkCap {
+            port: None,
+            gt,
+            width,
+        }
+    }
+
+    /// Sets the port number that the port advertises.
+    pub fn with_port(mut self, port: u8) -> LnkCap {
+        self.port = Some(port);
+        self
+    }
+
+    /// Returns the port number that the port advertises, which switches
+    /// usually number their downstream ports by in the same way as they are
+    /// labeled on the board.
+    pub fn port(&self) -> Option<u8> {
+        self.port
+    }
+
+    /// Returns the link's maximum speed in GT/s.
+    pub fn speed(&self) -> f32 {
+        self.gt
+    }
+
+    /// Returns the link's maximum width in lanes.
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Returns the link's usable bandwidth in GB/s, after line encoding
+    /// overhead.
+    pub fn bandwidth_gbps(&self) -> f32 {
+        link_speed::bandwidth_gbps(self.gt, self.width)
+    }
+
+    /// Returns the PCIe generation corresponding to the link's speed, if it
+    /// is a known one.
+    pub fn generation(&self) -> Option<u8> {
+        link_speed::generation(self.gt)
     }
 }
 
 impl Display for LnkCap {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}GT/s x{}", self.gt, self.width)
+        write!(f, "{}GT/s", self.gt)?;
+
+        //
+        // The alternate form ("{:#}") also shows the PCIe generation.
+        //
+        if f.alternate()
+            && let Some(generation) = self.generation()
+        {
+            write!(f, " (Gen{})", generation)?;
+        }
+
+        write!(f, " x{}", self.width)
     }
 }
@@ -1,7 +1,13 @@
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug)]
+use serde::Serialize;
+
+use crate::{LnkCap, link_speed};
+
+/// A PCIe link's negotiated speed and width, from LnkSta.
+#[derive(Debug, Serialize)]
 pub struct LnkSta {
+    #[serde(rename = "speed")]
     gt: f32,
     width: u8,
     downgraded: bool,
@@ -15,17 +21,74 @@ impl LnkSta {
             downgraded,
         }
     }
+
+    /// Returns the link's negotiated speed in GT/s.
+    pub fn speed(&self) -> f32 {
+        self.gt
+    }
+
+    /// Returns the link's negotiated width in lanes.
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Returns whether lspci flagged the link's speed or width as
+    /// downgraded.
+    pub fn is_downgraded(&self) -> bool {
+        self.downgraded
+    }
+
+    /// Returns the link's usable bandwidth in GB/s, after line encoding
+    /// overhead.
+    pub fn bandwidth_gbps(&self) -> f32 {
+        link_speed::bandwidth_gbps(self.gt, self.width)
+    }
+
+    /// Returns the PCIe generation corresponding to the link's speed, if it
+    /// is a known one.
+    pub fn generation(&self) -> Option<u8> {
+        link_speed::generation(self.gt)
+    }
+
+    /// Returns whether the link failed to train, which lspci shows as a
+    /// width of x0.
+    pub fn is_link_down(&self) -> bool {
+        self.width == 0
+    }
+
+    /// Returns whether the link negotiated a lower speed or a narrower width
+    /// than `lnk_cap` advertises.
+    pub fn is_degraded_against(&self, lnk_cap: &LnkCap) -> bool {
+        self.gt < lnk_cap.speed() || self.width < lnk_cap.width()
+    }
+
+    /// Returns whether the link runs below the speed that `lnk_cap`
+    /// advertises only because it was limited to `target_speed` (from
+    /// LnkCtl2), at its full width.
+    pub fn is_capped_against(&self, lnk_cap: &LnkCap, target_speed: f32) -> bool {
+        self.gt < lnk_cap.speed() && self.gt == target_speed && self.width >= lnk_cap.width()
+    }
 }
 
 impl Display for LnkSta {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}GT/s", self.gt)?;
+
+        //
+        // The alternate form ("{:#}") also shows the PCIe generation.
+        //
+        if f.alternate()
+            && let Some(generation) = self.generation()
+        {
+            write!(f, " (Gen{})", generation)?;
+        }
+
         write!(
             f,
-            "{}GT/s x{}{}",
-            self.gt,
+            " x{}{}",
             self.width,
             if self.downgraded {
-                "\\n(downgraded)"
+                "\n(downgraded)"
             } else {
                 ""
             }
@@ -0,0 +1,2540 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter},
+    io::{Error, ErrorKind, Read, Write, sink},
+    sync::OnceLock,
+};
+
+use flate2::read::MultiGzDecoder;
+use regex::Regex;
+
+use crate::{
+    AspmState, DeviceNames, GraphOptions, GroupBy, LnkCap, LnkSta, NameResolver, ParseError,
+    PciAddr, PciDevice, PciIds, Section,
+    check::CheckWriter,
+    dot::DotWriter,
+    explain::ExplainWriter,
+    graph_writer::{Attrs, GraphWriter, NoColorWriter, PrefixWriter},
+    graphml::GraphMlWriter,
+    mermaid::MermaidWriter,
+    plantuml::PlantUmlWriter,
+};
+
+/// A machine's PCI devices and DMI System Slot records.
+#[derive(Debug, Default)]
+pub struct Machine {
+    pub(crate) dmi_slots: BTreeMap<PciAddr, String>,
+    pub(crate) pci_devices: BTreeMap<PciAddr, PciDevice>,
+    pub(crate) bridge_parents: BTreeMap<PciAddr, PciAddr>,
+    pci_ids: Option<PciIds>,
+    name_resolver: NameResolver,
+    pub(crate) sections: usize,
+    unknown_sections: usize,
+    pub(crate) duplicate_devices: Vec<PciAddr>,
+    strict: bool,
+    pub(crate) bridge_paths: bool,
+    tail: String,
+    tail_addr: Option<PciAddr>,
+    tail_unknown: bool,
+}
+
+impl Machine {
+    /// Parses `lspci -nnvv` output, optionally mixed with `dmidecode` output,
+    /// and adds the PCI devices and DMI System Slots found to the machine.
+    /// The input may be gzip-compressed.
+    ///
+    /// This can be called repeatedly to feed in the output piece by piece,
+    /// e.g. as it arrives over a serial console, and the pieces don't need
+    /// to end at a section boundary.  The last section of each piece is
+    /// parsed again along with the next piece, and errors in it are only
+    /// reported once the piece ends in a complete line.  A piece that fails
+    /// to parse leaves the machine as it was.
+    pub fn parse<T: Read>(&mut self, src: &mut T) -> Result<(), ParseError> {
+        let mut data = read_input(src)?;
+
+        //
+        // The last section of the previous piece of input may continue in
+        // this one, so parse it again along with this piece.  It is only
+        // taken back out of the machine once this piece has parsed without
+        // errors.
+        //
+        data.insert_str(0, &self.tail);
+
+        //
+        // Captures that passed through Windows tools may have CRLF line
+        // endings, which would defeat the section splitting below as well
+        // as the per-field regexes.
+        //
+        let data = data.replace("\r\n", "\n");
+
+        static PCI_DEVICE_HEADER_RE: OnceLock<Regex> = OnceLock::new();
+
+        let pci_device_header_re = PCI_DEVICE_HEADER_RE.get_or_init(|| {
+            Regex::new(concat!(
+                r"^(?:[0-9a-f]{4,8}:)?",
+                r"(?:[0-9a-f]{2}:[0-9a-f]{2}\.[0-7]/)*",
+                r"[0-9a-f]{2}:[0-9a-f]{2}\.[0-7] "
+            ))
+            .unwrap()
+        });
+
+        //
+        // Some kernels' lspci output has blank lines in the middle of a
+        // device's capability dump.  The lines of a device's section other
+        // than the first are always indented, so an indented chunk following
+        // a device's section is a continuation of it rather than a section
+        // of its own.
+        //
+        // `lspci -mm` and non-verbose `lspci` print a line per device, with
+        // no blank lines in between, so a device header line always starts
+        // a new section.
+        //
+        // Runs of more than one blank line leave chunks that start with a
+        // newline, which are trimmed so that they are recognized as well.
+        //
+        let mut sections = Vec::<String>::new();
+
+        for chunk in data
+            .split("\n\n")
+            .map(|chunk| chunk.trim_start_matches('\n'))
+            .filter(|chunk| !chunk.is_empty())
+        {
+            let continuation = chunk.starts_with('\t')
+                && sections
+                    .last()
+                    .is_some_and(|section| pci_device_header_re.is_match(section));
+
+            for (index, line) in chunk.split('\n').enumerate() {
+                let append = if index == 0 {
+                    continuation
+                } else {
+                    !pci_device_header_re.is_match(line)
+                };
+
+                match sections.last_mut() {
+                    Some(section) if append => {
+                        section.push('\n');
+                        section.push_str(line);
+                    }
+                    _ => sections.push(line.to_string()),
+                }
+            }
+        }
+
+        static PCI_IDS_RE: OnceLock<Regex> = OnceLock::new();
+
+        let pci_ids_re =
+            PCI_IDS_RE.get_or_init(|| Regex::new(r"\[[0-9a-f]{4}:[0-9a-f]{4}\]").unwrap());
+
+        //
+        // A piece of input that doesn't end in a blank line may be cut off
+        // in the middle of its last section.
+        //
+        let tail = match sections.last() {
+            Some(section) if !data.ends_with("\n\n") => section.clone(),
+            _ => String::new(),
+        };
+
+        //
+        // If it is cut off in the middle of a line, its last section can't
+        // be judged yet.
+        //
+        let incomplete = !data.ends_with('\n');
+
+        let mut parsed = Vec::with_capacity(sections.len());
+        let mut addrs = BTreeSet::new();
+
+        for (index, section) in sections.iter().enumerate() {
+            let is_tail = index + 1 == sections.len() && !tail.is_empty();
+
+            let section_parsed = Section::parse(section);
+
+            match &section_parsed {
+                Section::DmiSlot(..) => {}
+                Section::PciDevice(pci_device) => {
+                    let addr = pci_device.addr();
+
+                    //
+                    // The previous piece's tail device is replaced by its
+                    // reparsed section rather than duplicated by it.
+                    //
+                    let duplicate = !addrs.insert(addr)
+                        || (self.pci_devices.contains_key(&addr) && self.tail_addr != Some(addr));
+
+                    if duplicate && self.strict {
+                        return Err(ParseError::DuplicateDevice(addr));
+                    }
+                }
+                Section::Unknown => {
+                    if pci_device_header_re.is_match(section) && !(is_tail && incomplete) {
+                        let line = section.lines().next().unwrap_or_default();
+
+                        if !pci_ids_re.is_match(line) {
+                            return Err(ParseError::MissingIds(line.to_string()));
+                        }
+
+                        return Err(ParseError::MalformedSection(line.to_string()));
+                    }
+                }
+            }
+
+            parsed.push(section_parsed);
+        }
+
+        if !self.tail.is_empty() {
+            self.sections -= 1;
+
+            if std::mem::take(&mut self.tail_unknown) {
+                self.unknown_sections -= 1;
+            }
+
+            if let Some(addr) = self.tail_addr.take() {
+                self.remove_device(&addr);
+            }
+        }
+
+        self.tail = tail;
+        self.sections += parsed.len();
+
+        let count = parsed.len();
+
+        for (index, section) in parsed.into_iter().enumerate() {
+            let is_tail = index + 1 == count && !self.tail.is_empty();
+
+            match section {
+                Section::DmiSlot(addr, name) => {
+                    self.dmi_slots.insert(addr, name);
+                }
+                Section::PciDevice(pci_device) => {
+                    let addr = pci_device.addr();
+
+                    if is_tail {
+                        self.tail_addr = Some(addr);
+                    }
+
+                    if self.insert_device(pci_device).is_some() {
+                        self.duplicate_devices.push(addr);
+                    }
+                }
+                Section::Unknown => {
+                    self.unknown_sections += 1;
+
+                    if is_tail {
+                        self.tail_unknown = true;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the PCI devices and DMI System Slots of `other`, e.g. a machine
+    /// that part of the same input was parsed into, to the machine.  Devices
+    /// in `other` replace devices with the same address, which are reported
+    /// by `duplicate_devices` regardless of strict mode.
+    pub fn merge(&mut self, other: Machine) {
+        self.dmi_slots.extend(other.dmi_slots);
+        self.duplicate_devices.extend(other.duplicate_devices);
+
+        for (addr, pci_device) in other.pci_devices {
+            if self.insert_device(pci_device).is_some() {
+                self.duplicate_devices.push(addr);
+            }
+        }
+
+        self.sections += other.sections;
+        self.unknown_sections += other.unknown_sections;
+    }
+
+    /// Adds a PCI device, e.g. one built with `PciDevice::builder`, to the
+    /// machine.  The device replaces a device with the same address, which
+    /// is reported by `duplicate_devices`.
+    pub fn add_device(&mut self, pci_device: PciDevice) {
+        let addr = pci_device.addr();
+
+        if self.insert_device(pci_device).is_some() {
+            self.duplicate_devices.push(addr);
+        }
+    }
+
+    /// Parses a capture of several hosts, with a marker line before each
+    /// host's output, into a machine per host, keyed by host name.  `marker`
+    /// is the format of the marker lines, with `{host}` in place of the host
+    /// name, e.g. `### {host} ###`.  The input may be gzip-compressed.
+    ///
+    /// The machines get the strict mode, bridge path mode, `pci.ids` database
+    /// and device names of this machine.  Output for a host that appears
+    /// more than once is parsed into the same machine.
+    pub fn parse_hosts<T: Read>(
+        &self,
+        src: &mut T,
+        marker: &str,
+    ) -> Result<BTreeMap<String, Machine>, ParseError> {
+        let (marker_prefix, marker_suffix) = marker.split_once("{host}").ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("host marker \"{}\" lacks {{host}}", marker),
+            )
+        })?;
+
+        let data = read_input(src)?;
+
+        let mut hosts = Vec::<(String, String)>::new();
+
+        for line in data.split_inclusive('\n') {
+            let trimmed = line.trim_end();
+
+            if let Some(host) = trimmed
+                .strip_prefix(marker_prefix)
+                .and_then(|rest| rest.strip_suffix(marker_suffix))
+                .map(str::trim)
+                .filter(|host| !host.is_empty())
+            {
+                hosts.push((host.to_string(), String::new()));
+            } else {
+                match hosts.last_mut() {
+                    Some((_, output)) => output.push_str(line),
+                    None if trimmed.is_empty() => {}
+                    None => return Err(ParseError::MissingHostMarker(trimmed.to_string())),
+                }
+            }
+        }
+
+        let mut machines = BTreeMap::<String, Machine>::new();
+
+        for (host, mut output) in hosts {
+            //
+            // Make sure that the last section of each host's output is
+            // parsed as a complete section.
+            //
+            output.truncate(output.trim_end_matches('\n').len());
+            output.push_str("\n\n");
+
+            machines
+                .entry(host)
+                .or_insert_with(|| Machine {
+                    pci_ids: self.pci_ids.clone(),
+                    name_resolver: self.name_resolver.clone(),
+                    strict: self.strict,
+                    bridge_paths: self.bridge_paths,
+                    ..Machine::default()
+                })
+                .parse(&mut output.as_bytes())?;
+        }
+
+        Ok(machines)
+    }
+
+    /// Makes `parse` fail if it finds two device sections with the same
+    /// address, rather than letting the later one replace the earlier one.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Makes the machine take the bridge paths that `lspci -PP` prints for
+    /// each device as authoritative for which bridge the device is behind,
+    /// rather than the bridges' secondary and subordinate bus numbers.
+    pub fn set_bridge_paths(&mut self, bridge_paths: bool) {
+        self.bridge_paths = bridge_paths;
+    }
+
+    /// Returns the addresses of the device sections that replaced an earlier
+    /// section with the same address during parsing.
+    pub fn duplicate_devices(&self) -> &[PciAddr] {
+        &self.duplicate_devices
+    }
+
+    /// Sets the `pci.ids` database used to name devices that are missing
+    /// from the built-in short name table.
+    pub fn set_pci_ids(&mut self, pci_ids: PciIds) {
+        self.pci_ids = Some(pci_ids);
+    }
+
+    /// Sets the user-supplied device names, which take precedence over the
+    /// built-in short name table.
+    pub fn set_device_names(&mut self, device_names: DeviceNames) {
+        for (vendor_id, device_id, name) in device_names.iter() {
+            self.name_resolver.add_name(vendor_id, device_id, name);
+        }
+    }
+
+    /// Sets the resolver used to look up short device names, replacing the
+    /// default one, which only knows about the built-in short name table.
+    pub fn set_name_resolver(&mut self, name_resolver: NameResolver) {
+        self.name_resolver = name_resolver;
+    }
+
+    /// Adds a short name for the given vendor and device ID, which takes
+    /// precedence over the built-in short name table.
+    pub fn add_name<T: Into<String>>(&mut self, vendor_id: u16, device_id: u16, name: T) {
+        self.name_resolver.add_name(vendor_id, device_id, name);
+    }
+
+    /// Returns the machine's PCI devices that `f` returns true for, in PCI
+    /// address order.
+    pub fn devices_where<F: Fn(&PciDevice) -> bool>(
+        &self,
+        f: F,
+    ) -> impl Iterator<Item = &PciDevice> {
+        self.pci_devices.values().filter(move |dev| f(dev))
+    }
+
+    /// Returns the number of sections of input seen by `parse`, or of
+    /// devices seen by `parse_sysfs`, whether or not anything was found in
+    /// them.
+    pub fn sections_seen(&self) -> usize {
+        self.sections
+    }
+
+    /// Returns the number of sections of input seen by `parse` that were
+    /// neither a PCI device nor a DMI System Slot record, such as the other
+    /// records in `dmidecode` output.  See `Section::parse`.
+    pub fn unknown_sections(&self) -> usize {
+        self.unknown_sections
+    }
+
+    /// Returns the machine's PCI devices that have no PCIe role that the
+    /// graph knows how to place, i.e. that aren't a root port, switch port,
+    /// PCIe to PCI bridge or endpoint.  These include host bridges and
+    /// conventional PCI devices.
+    pub fn unclassified_devices(&self) -> impl Iterator<Item = &PciDevice> {
+        self.devices_where(|dev| {
+            !dev.is_root_port()
+                && !dev.is_upstream_port()
+                && !dev.is_downstream_port()
+                && !dev.is_pci_bridge()
+                && !dev.is_endpoint()
+        })
+    }
+
+    /// Returns the machine's PCIe endpoints.
+    pub fn endpoints(&self) -> impl Iterator<Item = &PciDevice> {
+        self.devices_where(|dev| dev.is_endpoint())
+    }
+
+    /// Returns the machine's PCIe root ports.
+    pub fn root_ports(&self) -> impl Iterator<Item = &PciDevice> {
+        self.devices_where(|dev| dev.is_root_port())
+    }
+
+    /// Returns the machine's PCI devices that are attached to NUMA node
+    /// `node`.
+    pub fn on_numa_node(&self, node: usize) -> impl Iterator<Item = &PciDevice> {
+        self.devices_where(move |dev| dev.numa_node() == Some(node))
+    }
+
+    /// Returns the NUMA nodes that the machine's root ports are attached to,
+    /// including `None` if some of them aren't attached to any.
+    pub fn numa_nodes(&self) -> BTreeSet<Option<usize>> {
+        self.root_ports()
+            .filter(|dev| self.secondary_bus(dev).is_some())
+            .map(|dev| dev.numa_node())
+            .collect()
+    }
+
+    /// Returns the sum of the negotiated bandwidths, in GB/s, of the links
+    /// of all endpoints behind `port`, e.g. to spot oversubscribed root
+    /// ports.  The functions of a multi-function device share a link, which
+    /// is only counted once.
+    pub fn downstream_bandwidth_gbps(&self, port: &PciDevice) -> f32 {
+        let mut bandwidth_gbps = 0.0;
+        let mut devices = BTreeSet::new();
+
+        for dev_addr in self.child_devices(port) {
+            let dev = self.pci_devices.get(&dev_addr).unwrap();
+
+            //
+            // Guard against bridges that claim their own bus as their
+            // secondary bus.
+            //
+            if self
+                .secondary_bus(dev)
+                .is_some_and(|bus| bus != dev_addr.bus())
+            {
+                bandwidth_gbps += self.downstream_bandwidth_gbps(dev);
+            } else if dev.is_endpoint()
+                && devices.insert(dev_addr.device())
+                && let Some(lnk_sta) = dev.lnk_sta()
+            {
+                bandwidth_gbps += lnk_sta.bandwidth_gbps();
+            }
+        }
+
+        bandwidth_gbps
+    }
+
+    /// Returns the name of the DMI System Slot record for the device at
+    /// `addr`, if there is one.
+    pub fn dmi_slot_for(&self, addr: PciAddr) -> Option<&str> {
+        self.dmi_slots.get(&addr).map(|name| name.as_str())
+    }
+
+    /// Returns the machine's DMI System Slot records, by PCI address.
+    pub fn slots(&self) -> impl Iterator<Item = (&PciAddr, &str)> {
+        self.dmi_slots
+            .iter()
+            .map(|(addr, name)| (addr, name.as_str()))
+    }
+
+    /// Returns the name of the slot behind `port`, from the DMI System Slot
+    /// records or else from the Physical Slot of the device in the slot.
+    pub fn slot_name(&self, port: &PciDevice) -> Option<String> {
+        let downstream_addr = PciAddr::new(port.addr().domain(), self.secondary_bus(port)?, 0, 0);
+
+        //
+        // In the ORACLE SERVER E4-2c, a DMI System Slot handle refers to the PCI bus
+        // address of the Root Complex's Root Port or the upstream bridge's Downstream
+        // Port, and not to the PCI bus address of the downstream bridge's Upstream
+        // Port or the downstream Endpoint.  For this reason, we re-query for the
+        // parent's PCI bus address if we don't find a System Slot handle for the
+        // downstream address.
+        //
+        // Some machines' DMI System Slot handles don't match up with either
+        // address, so as a last resort, we use the Physical Slot reported by
+        // lspci for the downstream device (function 0 on the downstream bus).
+        //
+        self.dmi_slot_for(downstream_addr)
+            .or_else(|| self.dmi_slot_for(port.addr()))
+            .map(|name| name.to_string())
+            .or_else(|| {
+                self.pci_devices
+                    .get(&downstream_addr)
+                    .and_then(|dev| dev.physical_slot())
+                    .map(|physical_slot| format!("Slot {}", physical_slot))
+            })
+    }
+
+    pub(crate) fn short_name<'a>(&'a self, dev: &PciDevice) -> Option<&'a str> {
+        self.name_resolver.short_name(dev)
+    }
+
+    //
+    // Returns the addresses of the devices on the buses from `first` up to
+    // `last`, ordered by bus and then as by bus_devices.
+    //
+    pub(crate) fn range_devices(&self, domain: u32, first: u8, last: u8) -> Vec<PciAddr> {
+        (first..=last)
+            .flat_map(|bus| self.bus_devices(domain, bus))
+            .collect()
+    }
+
+    //
+    // Returns the addresses of the devices on a bus, ordered by device and
+    // function number regardless of the order they appeared in the input.
+    // The endpoints on a bus are drawn as a chain in this order, and the
+    // functions of a multi-function device are grouped by relying on them
+    // being adjacent in it.
+    //
+    pub(crate) fn bus_devices(&self, domain: u32, bus: u8) -> Vec<PciAddr> {
+        let mut bus_devices = self
+            .pci_devices
+            .keys()
+            .filter(|dev_addr| dev_addr.domain() == domain && dev_addr.bus() == bus)
+            .copied()
+            .collect::<Vec<_>>();
+
+        bus_devices.sort_by_key(|dev_addr| dev_addr.devfn);
+
+        bus_devices
+    }
+}
+
+//
+// A short summary of the machine's contents, for poking at it interactively.
+// The derived Debug implementation shows everything.
+//
+impl Display for Machine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let plural = |count: usize, singular: &str, plural: &str| {
+            format!("{} {}", count, if count == 1 { singular } else { plural })
+        };
+
+        write!(
+            f,
+            "{}, {}, {}, {}",
+            plural(self.pci_devices.len(), "PCI device", "PCI devices"),
+            plural(self.root_ports().count(), "root port", "root ports"),
+            plural(self.dmi_slots.len(), "DMI slot", "DMI slots"),
+            plural(self.cluster_identifiers().len(), "cluster", "clusters")
+        )
+    }
+}
+
+//
+// Reads all of `src`, decompressing it if it is gzip-compressed.
+//
+fn read_input<T: Read>(src: &mut T) -> Result<String, ParseError> {
+    let mut raw = Vec::new();
+
+    src.read_to_end(&mut raw)?;
+
+    let mut data = String::new();
+
+    if raw.starts_with(&[0x1f, 0x8b]) {
+        MultiGzDecoder::new(raw.as_slice()).read_to_string(&mut data)?;
+    } else {
+        data = String::from_utf8(raw).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+    }
+
+    Ok(data)
+}
+
+//
+// Node fill colors by device class code, for GraphOptions::color_by_class.
+//
+fn class_color(class_code: Option<u16>) -> &'static str {
+    match class_code {
+        // Mass storage controllers.
+        Some(0x0100..=0x01ff) => "lightblue",
+        // Network controllers.
+        Some(0x0200..=0x02ff) => "palegreen",
+        // Display controllers and processing accelerators.
+        Some(0x0300..=0x03ff | 0x1200..=0x12ff) => "orange",
+        // Bridges.
+        Some(0x0600..=0x06ff) => "lightgrey",
+        // USB controllers.
+        Some(0x0c03) => "khaki",
+        _ => "white",
+    }
+}
+
+//
+// What endpoints have to have in common to be collapsed into one node by
+// --collapse-identical.  A device whose link trained differently from its
+// siblings is worth seeing on its own.
+//
+type EndpointKey = (u16, u16, Option<(u16, u16)>, Option<(u32, u8, bool)>);
+
+fn endpoint_key(dev: &PciDevice) -> EndpointKey {
+    (
+        dev.vendor_id(),
+        dev.device_id(),
+        dev.subsystem_id(),
+        dev.lnk_sta().map(|lnk_sta| {
+            (
+                lnk_sta.speed().to_bits(),
+                lnk_sta.width(),
+                lnk_sta.is_downgraded(),
+            )
+        }),
+    )
+}
+
+fn aspm_label(dev: &PciDevice) -> Option<String> {
+    let aspm_enabled = dev.aspm_enabled()?;
+
+    let mut label = match dev.aspm_capable() {
+        Some(aspm_capable) if aspm_capable != aspm_enabled => {
+            format!("ASPM {} (cap {})", aspm_enabled, aspm_capable)
+        }
+        _ => format!("ASPM {}", aspm_enabled),
+    };
+
+    //
+    // The L1 exit latency is what a link in L1 adds to the latency of the
+    // first access to the device after it goes idle.
+    //
+    if matches!(aspm_enabled, AspmState::L1 | AspmState::L0sL1)
+        && let Some(l1_exit_latency) = dev.l1_exit_latency()
+    {
+        label.push_str(&format!("\nL1 exit {}", l1_exit_latency));
+    }
+
+    Some(label)
+}
+
+//
+// Ports and bridges without ACS enabled let the devices behind them talk
+// to each other without going through the IOMMU, which undermines device
+// passthrough unless the kernel's ACS override is in use.
+//
+fn acs_attrs(dev: &PciDevice, mut attrs: Attrs) -> Attrs {
+    if dev.acs_enabled() == Some(false) {
+        let label = attrs.label.take().unwrap_or_else(|| dev.addr().to_string());
+
+        attrs.label = Some(format!("{}\nACS disabled", label));
+    }
+
+    attrs
+}
+
+//
+// Works out why a cluster was given the identifier that it was, from the
+// devices in it.
+//
+fn cluster_reason(
+    machine: &Machine,
+    write_state: &MachineWriteState,
+    identifier: &str,
+    members: &[&PciDevice],
+) -> String {
+    if identifier == "unreachable" {
+        return "devices not reachable from any root port".to_string();
+    }
+
+    if identifier.starts_with("iommu group ") {
+        return "IOMMU group".to_string();
+    }
+
+    for dev in members {
+        let (unique_id, reason) = machine.pci_device_unique_id_reason(dev);
+
+        if dev.is_root_port() && write_state.group_name(dev) == identifier {
+            let reason = match (dev.numa_node(), dev.cpu_socket()) {
+                _ if write_state.options.group_label.is_some() => "--group-label template",
+                (Some(_), _) => "NUMA node of the root ports",
+                (None, Some(_)) => "CPU socket of the root ports' local CPUs",
+                (None, None) => "bus number, as the NUMA node isn't known",
+            };
+
+            return reason.to_string();
+        }
+
+        if unique_id == identifier {
+            return reason.to_string();
+        }
+    }
+
+    "unknown".to_string()
+}
+
+//
+// Whether a link runs below its maximum speed only because its Target Link
+// Speed was lowered, which either end of the link can have done.
+//
+fn is_capped(ends: [&PciDevice; 2], lnk_sta: &LnkSta, lnk_cap: &LnkCap) -> bool {
+    ends.iter()
+        .filter_map(|dev| dev.target_link_speed())
+        .any(|target_speed| lnk_sta.is_capped_against(lnk_cap, target_speed))
+}
+
+//
+// 32-bit FNV-1a, which is simple and stable across Rust versions, unlike
+// the standard library's hashers.
+//
+fn fnv1a(str: &str) -> u32 {
+    str.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+struct MachineWriteState<'a> {
+    options: &'a GraphOptions,
+    cluster_prefix: String,
+    clusters: BTreeMap<String, u32>,
+    cluster_indices: BTreeSet<u32>,
+    devices: BTreeSet<PciAddr>,
+    collapsed: BTreeMap<PciAddr, usize>,
+    kept: Option<BTreeSet<PciAddr>>,
+}
+
+impl MachineWriteState<'_> {
+    fn new(options: &GraphOptions) -> MachineWriteState<'_> {
+        MachineWriteState {
+            options,
+            cluster_prefix: String::new(),
+            clusters: BTreeMap::new(),
+            cluster_indices: BTreeSet::new(),
+            devices: BTreeSet::new(),
+            collapsed: BTreeMap::new(),
+            kept: None,
+        }
+    }
+
+    fn is_kept(&self, dev_addr: &PciAddr) -> bool {
+        self.kept
+            .as_ref()
+            .is_none_or(|kept| kept.contains(dev_addr))
+    }
+
+    //
+    // Cluster indices are derived from a hash of the cluster identifier,
+    // rather than handed out in order, so that a cluster keeps its index
+    // when other clusters come and go, which keeps diffs between graphs of
+    // different captures readable.  Collisions are resolved by probing for
+    // the next free index.
+    //
+    fn get_cluster_index(&mut self, identifier: &str) -> u32 {
+        let identifier = format!("{}{}", self.cluster_prefix, identifier);
+
+        if let Some(index) = self.clusters.get(&identifier) {
+            return *index;
+        }
+
+        let mut index = fnv1a(&identifier);
+
+        while !self.cluster_indices.insert(index) {
+            index = index.wrapping_add(1);
+        }
+
+        self.clusters.insert(identifier, index);
+
+        index
+    }
+
+    //
+    // The name of the cluster that a root port is drawn in, which is also
+    // what root ports are grouped by.  When a --group-label template is
+    // given, root ports are grouped by what the template expands to.
+    //
+    fn group_name(&self, dev: &PciDevice) -> String {
+        let Some(template) = &self.options.group_label else {
+            return dev.device_group_name();
+        };
+
+        let unknown = || "?".to_string();
+
+        template
+            .replace(
+                "{numa}",
+                &dev.numa_node().map_or_else(unknown, |n| n.to_string()),
+            )
+            .replace(
+                "{socket}",
+                &dev.cpu_socket().map_or_else(unknown, |n| n.to_string()),
+            )
+            .replace("{bus}", &format!("{:02x}", dev.addr().bus()))
+    }
+
+    fn numa_label(&self, label: String, dev: &PciDevice) -> String {
+        match dev.numa_node() {
+            Some(numa_node) if self.options.show_numa => format!("{}\nNUMA {}", label, numa_node),
+            _ => label,
+        }
+    }
+
+    fn topology_clusters(&self) -> bool {
+        self.options.group_by == GroupBy::Topology && !self.options.no_clusters
+    }
+
+    fn iommu_clusters(&self) -> bool {
+        self.options.group_by == GroupBy::Iommu && !self.options.no_clusters
+    }
+
+    //
+    // The functions of a multi-function device can be in different IOMMU
+    // groups, so they can only be drawn as a single node when the graph is
+    // clustered by topology, or not clustered at all.
+    //
+    fn record_functions(&self) -> bool {
+        self.options.record_functions && (self.topology_clusters() || self.options.no_clusters)
+    }
+
+    fn link_label<T: Display>(&self, link: &T, bandwidth_gbps: f32) -> String {
+        if self.options.show_bandwidth {
+            format!("{}\n{:.1} GB/s", link, bandwidth_gbps)
+        } else {
+            link.to_string()
+        }
+    }
+
+    //
+    // The attributes of the link below a port, as the port advertises it.
+    // Some emulated root ports have no LnkCap, in which case their links
+    // are drawn without a label.
+    //
+    fn port_link_attrs(&self, port: &PciDevice) -> Attrs {
+        match port.lnk_cap() {
+            Some(lnk_cap) => self.scale_edge(
+                Attrs::label(self.link_label(&lnk_cap, lnk_cap.bandwidth_gbps())),
+                lnk_cap.width(),
+            ),
+            None => Attrs::default(),
+        }
+    }
+
+    //
+    // Pen widths grow logarithmically with the lane count, from 1 for x1
+    // links to 4 for x16 links.
+    //
+    fn scale_edge(&self, attrs: Attrs, width: u8) -> Attrs {
+        if self.options.scale_edges && width > 0 {
+            attrs.penwidth(1.0 + 0.75 * f32::from(width).log2())
+        } else {
+            attrs
+        }
+    }
+}
+
+impl Machine {
+    /// Writes the machine's PCI topology to `w` as a graphviz graph.
+    pub fn write_graph<T: Write>(&self, w: &mut T, options: &GraphOptions) -> Result<(), Error> {
+        self.render(
+            &mut DotWriter::new(w, options),
+            &mut MachineWriteState::new(options),
+        )
+    }
+
+    /// Writes the machine's PCI topology to `w` as a Mermaid flowchart.
+    pub fn write_mermaid<T: Write>(&self, w: &mut T, options: &GraphOptions) -> Result<(), Error> {
+        self.render(
+            &mut MermaidWriter::new(w, options),
+            &mut MachineWriteState::new(options),
+        )
+    }
+
+    /// Writes the machine's PCI topology to `w` as GraphML.
+    pub fn write_graphml<T: Write>(&self, w: &mut T, options: &GraphOptions) -> Result<(), Error> {
+        self.render(
+            &mut GraphMlWriter::new(w, options),
+            &mut MachineWriteState::new(options),
+        )
+    }
+
+    /// Writes the machine's PCI topology to `w` as a PlantUML component
+    /// diagram.
+    pub fn write_plantuml<T: Write>(&self, w: &mut T, options: &GraphOptions) -> Result<(), Error> {
+        self.render(
+            &mut PlantUmlWriter::new(w, options),
+            &mut MachineWriteState::new(options),
+        )
+    }
+
+    /// Renders the machine's PCI topology without writing it anywhere, and
+    /// returns a description of each inconsistency found in the graph, such
+    /// as edges to nodes that were never declared.
+    pub fn check_graph(&self, options: &GraphOptions) -> Vec<String> {
+        let mut check_writer = CheckWriter::default();
+
+        self.render(&mut check_writer, &mut MachineWriteState::new(options))
+            .expect("checking can't fail");
+
+        check_writer.problems()
+    }
+
+    /// Writes a report of the clusters that the machine's PCI topology
+    /// would be drawn in to `w`: for each cluster, the identifier that
+    /// its index was derived from, why that identifier was chosen, and
+    /// the addresses of its members.
+    pub fn explain_clusters<T: Write>(
+        &self,
+        w: &mut T,
+        options: &GraphOptions,
+    ) -> Result<(), Error> {
+        let mut explain_writer = ExplainWriter::default();
+        let mut write_state = MachineWriteState::new(options);
+
+        self.render(&mut explain_writer, &mut write_state)?;
+
+        let identifiers = write_state
+            .clusters
+            .iter()
+            .map(|(identifier, index)| (*index, identifier.as_str()))
+            .collect::<BTreeMap<_, _>>();
+
+        for (position, cluster) in explain_writer.clusters().iter().enumerate() {
+            let identifier = identifiers.get(&cluster.index).copied().unwrap_or("");
+
+            let members = cluster
+                .members
+                .iter()
+                .filter_map(|member| member.parse::<PciAddr>().ok())
+                .filter_map(|addr| self.pci_devices.get(&addr))
+                .collect::<Vec<_>>();
+
+            if position > 0 {
+                writeln!(w)?;
+            }
+
+            writeln!(
+                w,
+                "cluster{} ({})",
+                cluster.index,
+                cluster.label.as_deref().unwrap_or("multi-function device")
+            )?;
+            writeln!(w, "  identifier: {}", identifier)?;
+            writeln!(
+                w,
+                "  reason: {}",
+                cluster_reason(self, &write_state, identifier, &members)
+            )?;
+
+            for member in &cluster.members {
+                writeln!(w, "  member: {}", member)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the PCI topologies of several machines to `w` as a single
+    /// graphviz graph, with each machine in a cluster labeled with its
+    /// host name.
+    pub fn write_hosts_graph<T: Write>(
+        w: &mut T,
+        hosts: &[(&str, &Machine)],
+        options: &GraphOptions,
+    ) -> Result<(), Error> {
+        Machine::render_hosts(&mut DotWriter::new(w, options), hosts, options)
+    }
+
+    /// Writes the PCI topologies of several machines to `w` as a single
+    /// Mermaid flowchart, with each machine in a labeled subgraph.
+    pub fn write_hosts_mermaid<T: Write>(
+        w: &mut T,
+        hosts: &[(&str, &Machine)],
+        options: &GraphOptions,
+    ) -> Result<(), Error> {
+        Machine::render_hosts(&mut MermaidWriter::new(w, options), hosts, options)
+    }
+
+    /// Writes the PCI topologies of several machines to `w` as a single
+    /// GraphML graph, with each machine in a labeled nested graph.
+    pub fn write_hosts_graphml<T: Write>(
+        w: &mut T,
+        hosts: &[(&str, &Machine)],
+        options: &GraphOptions,
+    ) -> Result<(), Error> {
+        Machine::render_hosts(&mut GraphMlWriter::new(w, options), hosts, options)
+    }
+
+    /// Writes the PCI topologies of several machines to `w` as a single
+    /// PlantUML component diagram, with each machine in a labeled package.
+    pub fn write_hosts_plantuml<T: Write>(
+        w: &mut T,
+        hosts: &[(&str, &Machine)],
+        options: &GraphOptions,
+    ) -> Result<(), Error> {
+        Machine::render_hosts(&mut PlantUmlWriter::new(w, options), hosts, options)
+    }
+
+    /// Returns the addresses of the devices at the downstream end of links
+    /// that trained at a lower speed or a narrower width than the devices
+    /// support, which are drawn in red in the graph.  Links that are down,
+    /// or that run at a lower speed only because their Target Link Speed
+    /// was lowered, aren't included.
+    pub fn degraded_links(&self) -> Vec<PciAddr> {
+        self.pci_devices
+            .values()
+            .filter(|port| port.is_root_port() || port.is_downstream_port())
+            .filter_map(|port| {
+                let dev_addr = *self.child_devices(port).first()?;
+
+                let dev = self.pci_devices.get(&dev_addr).unwrap();
+
+                let lnk_sta = dev.lnk_sta()?;
+                let lnk_cap = dev.lnk_cap()?;
+
+                (self.pci_device_unique_id(port) != self.pci_device_unique_id(dev)
+                    && !lnk_sta.is_link_down()
+                    && !is_capped([port, dev], &lnk_sta, &lnk_cap)
+                    && lnk_sta.is_degraded_against(&lnk_cap))
+                .then_some(dev_addr)
+            })
+            .collect()
+    }
+
+    /// Writes a one-line summary of the machine's PCIe topology to `w`,
+    /// e.g. `4 roots, 2 switches, 18 endpoints, 1 degraded link`.
+    pub fn write_summary<T: Write>(&self, w: &mut T) -> Result<(), Error> {
+        let plural = |count: usize, singular: &str, plural: &str| {
+            format!("{} {}", count, if count == 1 { singular } else { plural })
+        };
+
+        writeln!(
+            w,
+            "{}, {}, {}, {}",
+            plural(
+                self.root_ports()
+                    .filter(|dev| self.secondary_bus(dev).is_some())
+                    .count(),
+                "root",
+                "roots"
+            ),
+            plural(
+                self.devices_where(|dev| dev.is_upstream_port()).count(),
+                "switch",
+                "switches"
+            ),
+            plural(self.endpoints().count(), "endpoint", "endpoints"),
+            plural(
+                self.degraded_links().len(),
+                "degraded link",
+                "degraded links"
+            )
+        )
+    }
+
+    /// Returns the device at `addr` if the graph can be drawn starting at it
+    /// with `GraphOptions::root`, which it can if it is a root port or a
+    /// switch port with a secondary bus.
+    pub fn subtree_root(&self, addr: PciAddr) -> Option<&PciDevice> {
+        self.pci_devices.get(&addr).filter(|dev| {
+            self.secondary_bus(dev).is_some()
+                && (dev.is_root_port() || dev.is_upstream_port() || dev.is_downstream_port())
+        })
+    }
+
+    /// Returns the root ports and switch downstream ports that have no
+    /// LnkCap, such as some emulated root ports, and whose links are
+    /// therefore drawn without a label.
+    pub fn ports_without_lnk_cap(&self) -> Vec<PciAddr> {
+        self.pci_devices
+            .values()
+            .filter(|dev| self.secondary_bus(dev).is_some())
+            .filter(|dev| dev.is_root_port() || dev.is_downstream_port())
+            .filter(|dev| dev.lnk_cap().is_none())
+            .map(|dev| dev.addr())
+            .collect()
+    }
+
+    /// Returns the devices that aren't reachable from any root port, and are
+    /// therefore missing from the graph.  Devices on the same bus as a root
+    /// port, such as host bridges and Root Complex Integrated Endpoints, are
+    /// expected not to be reachable, and aren't included.
+    pub fn unreachable_devices(&self) -> Vec<PciAddr> {
+        let options = GraphOptions::default();
+        let mut write_state = MachineWriteState::new(&options);
+
+        self.render(&mut DotWriter::new(&mut sink(), &options), &mut write_state)
+            .expect("writing to a sink can't fail");
+
+        self.orphans(&write_state.devices)
+    }
+
+    fn orphans(&self, devices: &BTreeSet<PciAddr>) -> Vec<PciAddr> {
+        let root_buses = self
+            .root_ports()
+            .map(|dev| (dev.addr().domain(), dev.addr().bus()))
+            .collect::<BTreeSet<_>>();
+
+        self.pci_devices
+            .keys()
+            .filter(|dev_addr| {
+                !devices.contains(dev_addr)
+                    && !root_buses.contains(&(dev_addr.domain(), dev_addr.bus()))
+            })
+            .copied()
+            .collect()
+    }
+
+    fn render<G: GraphWriter>(
+        &self,
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+    ) -> Result<(), Error> {
+        g.begin_graph()?;
+
+        self.render_body(g, write_state)?;
+
+        Machine::render_legend(g, write_state)?;
+
+        g.end_graph()
+    }
+
+    fn render_hosts<G: GraphWriter>(
+        g: &mut G,
+        hosts: &[(&str, &Machine)],
+        options: &GraphOptions,
+    ) -> Result<(), Error> {
+        g.begin_graph()?;
+
+        //
+        // The write state is shared between machines, so that cluster
+        // indices are unique across the whole graph, but the cluster
+        // identifiers and the set of devices drawn are per machine.
+        //
+        let mut write_state = MachineWriteState::new(options);
+
+        for (index, (host, machine)) in hosts.iter().enumerate() {
+            let prefix = format!("host{}:", index);
+
+            write_state.cluster_prefix = prefix.clone();
+            write_state.devices.clear();
+
+            g.section(&format!("host {}", host))?;
+
+            if !options.no_clusters {
+                g.blank()?;
+                g.begin_cluster(write_state.get_cluster_index("host"), Some(host))?;
+            }
+
+            machine.render_body(&mut PrefixWriter::new(g, prefix), &mut write_state)?;
+
+            if !options.no_clusters {
+                g.end_cluster()?;
+            }
+        }
+
+        Machine::render_legend(g, &mut write_state)?;
+
+        g.end_graph()
+    }
+
+    fn render_legend<G: GraphWriter>(
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+    ) -> Result<(), Error> {
+        if !write_state.options.legend {
+            Ok(())
+        } else if write_state.options.no_color {
+            Machine::write_legend(&mut NoColorWriter::new(g), write_state)
+        } else {
+            Machine::write_legend(g, write_state)
+        }
+    }
+
+    //
+    // The legend uses the same attributes as the nodes and edges that it
+    // explains, so keep it in sync with render_devices, write_bus and
+    // device_attrs.
+    //
+    fn write_legend<G: GraphWriter>(
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+    ) -> Result<(), Error> {
+        g.section("legend")?;
+
+        let mut nodes = vec![
+            (
+                "legend:port",
+                Attrs::label("Root port or slot").shape("rectangle"),
+            ),
+            ("legend:device", Attrs::label("PCIe device")),
+            ("legend:degraded", Attrs::label("PCIe device")),
+            (
+                "legend:link_down",
+                Attrs::label("Link down, or switch\nwithout downstream ports").color("red"),
+            ),
+            (
+                "legend:legacy",
+                Attrs::label("Conventional PCI device").shape("hexagon"),
+            ),
+        ];
+
+        if write_state.options.color_by_class {
+            for (id, label, class_code) in [
+                ("legend:storage", "Storage controller", Some(0x0108)),
+                ("legend:network", "Network controller", Some(0x0200)),
+                ("legend:display", "GPU or accelerator", Some(0x0302)),
+                ("legend:bridge", "Bridge", Some(0x0604)),
+                ("legend:usb", "USB controller", Some(0x0c03)),
+                ("legend:other", "Other device", None),
+            ] {
+                nodes.push((id, Attrs::label(label).fillcolor(class_color(class_code))));
+            }
+        }
+
+        g.blank()?;
+
+        for (id, attrs) in &nodes {
+            g.node(id, attrs)?;
+        }
+
+        g.blank()?;
+        g.begin_cluster(write_state.get_cluster_index("legend"), Some("Legend"))?;
+
+        for (id, _) in &nodes {
+            g.cluster_member(id)?;
+        }
+
+        g.end_cluster()?;
+
+        g.blank()?;
+        g.edge(
+            "legend:port",
+            "legend:device",
+            &Attrs::label("Link speed and width"),
+        )?;
+        g.edge(
+            "legend:port",
+            "legend:degraded",
+            &Attrs::label("Degraded link").color("red"),
+        )?;
+        g.edge(
+            "legend:port",
+            "legend:link_down",
+            &Attrs::label("LINK DOWN").color("red"),
+        )
+    }
+
+    fn render_body<G: GraphWriter>(
+        &self,
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+    ) -> Result<(), Error> {
+        if write_state.options.no_color {
+            self.render_devices(&mut NoColorWriter::new(g), write_state)
+        } else {
+            self.render_devices(g, write_state)
+        }
+    }
+
+    fn render_devices<G: GraphWriter>(
+        &self,
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+    ) -> Result<(), Error> {
+        write_state.kept = self.kept_devices(write_state.options);
+
+        //
+        // A graph that starts at a switch port is drawn from there, and a
+        // graph that starts at a root port is drawn as usual, but with just
+        // the one root port.
+        //
+        if let Some(root) = write_state.options.root {
+            let dev = self.subtree_root(root).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} is not a PCIe port with a secondary bus", root),
+                )
+            })?;
+
+            let numa_node_matches = write_state
+                .options
+                .numa_node
+                .is_none_or(|numa_node| dev.numa_node() == numa_node);
+
+            if numa_node_matches && dev.is_upstream_port() {
+                g.section(&format!("switch {}", root))?;
+
+                self.write_switch(g, write_state, root)?;
+            } else if numa_node_matches && dev.is_downstream_port() {
+                g.section(&format!("downstream port {}", root))?;
+
+                g.blank()?;
+                g.node(&root.to_string(), &acs_attrs(dev, Attrs::default()))?;
+
+                write_state.devices.insert(root);
+
+                self.write_bus(
+                    g,
+                    write_state,
+                    dev,
+                    root.domain(),
+                    self.secondary_bus(dev).unwrap(),
+                )?;
+            }
+        }
+
+        for (addr, dev) in &self.pci_devices {
+            if dev.is_root_port() && write_state.options.root.is_none_or(|root| root == *addr) {
+                //
+                // Dell PowerEdge R730xd PCI device 00:00.0 (Host Bridge) claims to be a
+                // PCI Express (v2) Root Port, but has a type 0 configuration space header.
+                // Ignore Root Ports that don't have a type 1 configuration space header.
+                //
+                if let Some(secondary_bus) = self.secondary_bus(dev)
+                    && !self.is_pruned(write_state, dev)
+                    && write_state.is_kept(addr)
+                    && !(write_state.options.hide_pch && dev.device_group_name().starts_with("PCH"))
+                    && write_state
+                        .options
+                        .numa_node
+                        .is_none_or(|numa_node| dev.numa_node() == numa_node)
+                {
+                    g.section(&format!("root port {}", addr))?;
+
+                    let mut label = format!("{}\n{}", write_state.options.labels.root_port, addr);
+
+                    if write_state.options.show_bandwidth {
+                        let bandwidth_gbps = self.downstream_bandwidth_gbps(dev);
+
+                        if bandwidth_gbps > 0.0 {
+                            label.push_str(&format!("\nΣ {:.1} GB/s", bandwidth_gbps));
+                        }
+                    }
+
+                    g.blank()?;
+                    g.node(
+                        &addr.to_string(),
+                        &acs_attrs(
+                            dev,
+                            Attrs::label(write_state.numa_label(label, dev)).shape("rectangle"),
+                        ),
+                    )?;
+
+                    write_state.devices.insert(*addr);
+
+                    if write_state.topology_clusters() {
+                        let device_group_name = write_state.group_name(dev);
+                        let cluster_id = write_state.get_cluster_index(&device_group_name);
+
+                        g.blank()?;
+                        g.begin_cluster(cluster_id, Some(&device_group_name))?;
+                        g.cluster_member(&addr.to_string())?;
+                        g.end_cluster()?;
+                    }
+
+                    self.write_bus(g, write_state, dev, addr.domain(), secondary_bus)?;
+                }
+            }
+        }
+
+        if write_state.iommu_clusters() {
+            self.write_iommu_groups(g, write_state)?;
+        }
+
+        //
+        // When the graph is split by NUMA node, the devices that aren't
+        // reachable from any root port are drawn along with the root ports
+        // that aren't attached to any NUMA node.
+        //
+        if write_state.options.show_orphans
+            && write_state
+                .options
+                .numa_node
+                .is_none_or(|numa_node| numa_node.is_none())
+        {
+            //
+            // Devices left out by --min-speed, --hide-pch, a NUMA node
+            // filter or --include and --exclude are still reachable.
+            //
+            let orphans = if write_state.options.min_speed.is_some()
+                || write_state.options.root.is_some()
+                || write_state.options.hide_pch
+                || write_state.options.numa_node.is_some()
+                || write_state.kept.is_some()
+            {
+                self.unreachable_devices()
+            } else {
+                self.orphans(&write_state.devices)
+            };
+
+            if !orphans.is_empty() {
+                g.section("devices not reachable from any root port")?;
+
+                g.blank()?;
+
+                for dev_addr in &orphans {
+                    let dev = self.pci_devices.get(dev_addr).unwrap();
+
+                    g.node(&dev_addr.to_string(), &self.device_attrs(dev, write_state))?;
+                }
+
+                if !write_state.options.no_clusters {
+                    g.blank()?;
+
+                    g.begin_cluster(
+                        write_state.get_cluster_index("unreachable"),
+                        Some("unreachable"),
+                    )?;
+
+                    for dev_addr in &orphans {
+                        g.cluster_member(&dev_addr.to_string())?;
+                    }
+
+                    g.end_cluster()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    //
+    // IOMMU groups can span buses, so the clusters for them are emitted
+    // after the walk, over all devices that ended up in the graph.
+    //
+    fn write_iommu_groups<G: GraphWriter>(
+        &self,
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+    ) -> Result<(), Error> {
+        let mut iommu_groups = BTreeMap::<u32, Vec<PciAddr>>::new();
+
+        for dev_addr in &write_state.devices {
+            let dev = self.pci_devices.get(dev_addr).unwrap();
+
+            if let Some(iommu_group) = dev.iommu_group() {
+                iommu_groups.entry(iommu_group).or_default().push(*dev_addr);
+            }
+        }
+
+        for (iommu_group, dev_addrs) in iommu_groups {
+            let cluster_id = write_state.get_cluster_index(&format!("iommu group {}", iommu_group));
+
+            g.blank()?;
+            g.begin_cluster(cluster_id, Some(&format!("IOMMU group {}", iommu_group)))?;
+
+            for dev_addr in dev_addrs {
+                g.cluster_member(&dev_addr.to_string())?;
+            }
+
+            g.end_cluster()?;
+        }
+
+        Ok(())
+    }
+
+    //
+    // Whether the link behind `port` is slower than --min-speed allows.  A
+    // link's maximum speed is limited by both of its ends, so look at the
+    // LnkCap of the device behind the port as well.
+    //
+    fn is_pruned(&self, write_state: &MachineWriteState, port: &PciDevice) -> bool {
+        let Some(min_speed) = write_state.options.min_speed else {
+            return false;
+        };
+
+        let downstream_dev = self
+            .child_devices(port)
+            .first()
+            .map(|dev_addr| self.pci_devices.get(dev_addr).unwrap());
+
+        [Some(port), downstream_dev]
+            .into_iter()
+            .flatten()
+            .filter_map(|dev| dev.lnk_cap())
+            .any(|lnk_cap| lnk_cap.speed() < min_speed)
+    }
+
+    //
+    // The devices that --include and --exclude leave in the graph: those
+    // that are in the subtree below an included device, or above one, and
+    // not in the subtree below an excluded device.  Addresses match if
+    // their formatted form starts with the given prefix.
+    //
+    fn kept_devices(&self, options: &GraphOptions) -> Option<BTreeSet<PciAddr>> {
+        if options.include.is_empty() && options.exclude.is_empty() {
+            return None;
+        }
+
+        let mut ports = self
+            .pci_devices
+            .values()
+            .filter_map(|dev| Some(((dev.addr().domain(), self.secondary_bus(dev)?), dev.addr())))
+            .collect::<BTreeMap<_, _>>();
+
+        //
+        // Devices on the buses behind a PCI bridge that no port leads to are
+        // drawn behind the bridge.
+        //
+        for dev in self.pci_devices.values().filter(|dev| dev.is_pci_bridge()) {
+            if let Some(secondary_bus) = self.secondary_bus(dev)
+                && let Some(subordinate_bus) = self.subordinate_bus(dev)
+            {
+                for bus in secondary_bus..=subordinate_bus {
+                    ports
+                        .entry((dev.addr().domain(), bus))
+                        .or_insert(dev.addr());
+                }
+            }
+        }
+
+        let matches = |dev_addr: &PciAddr, prefixes: &[String]| {
+            let dev_addr = dev_addr.to_string();
+
+            prefixes.iter().any(|prefix| dev_addr.starts_with(prefix))
+        };
+
+        let mut kept = BTreeSet::new();
+
+        for dev_addr in self.pci_devices.keys() {
+            //
+            // The device followed by the ports above it, up to its root
+            // port.  The length is capped in case of bus number loops.
+            //
+            let path = std::iter::successors(Some(*dev_addr), |dev_addr| {
+                ports
+                    .get(&(dev_addr.domain(), dev_addr.bus()))
+                    .filter(|port_addr| *port_addr != dev_addr)
+                    .copied()
+            })
+            .take(256)
+            .collect::<Vec<_>>();
+
+            if path
+                .iter()
+                .any(|dev_addr| matches(dev_addr, &options.exclude))
+            {
+                continue;
+            }
+
+            if options.include.is_empty()
+                || path
+                    .iter()
+                    .any(|dev_addr| matches(dev_addr, &options.include))
+            {
+                kept.extend(path);
+            }
+        }
+
+        Some(kept)
+    }
+
+    //
+    // The endpoint behind a downstream port, if it is the only device on
+    // the port's secondary bus.
+    //
+    fn lone_endpoint(&self, port_addr: PciAddr) -> Option<PciAddr> {
+        let port = self.pci_devices.get(&port_addr).unwrap();
+
+        match self.child_devices(port)[..] {
+            [dev_addr] if self.pci_devices.get(&dev_addr).unwrap().is_endpoint() => Some(dev_addr),
+            _ => None,
+        }
+    }
+
+    //
+    // With --collapse-identical, a set of sibling endpoints (or of sibling
+    // downstream ports with a single endpoint behind each) that only differ
+    // in their addresses is drawn as just the first one, labeled with how
+    // many there are.  `endpoint` maps each sibling to the endpoint that it
+    // is compared by, if any.  Returns the siblings that are to be drawn.
+    //
+    fn collapse_identical<F: Fn(PciAddr) -> Option<PciAddr>>(
+        &self,
+        write_state: &mut MachineWriteState,
+        siblings: &[PciAddr],
+        endpoint: F,
+    ) -> Vec<PciAddr> {
+        if !write_state.options.collapse_identical {
+            return siblings.to_vec();
+        }
+
+        let mut groups = BTreeMap::<_, Vec<(PciAddr, PciAddr)>>::new();
+        let mut kept = Vec::new();
+
+        for sibling_addr in siblings {
+            match endpoint(*sibling_addr) {
+                Some(dev_addr) => {
+                    let dev = self.pci_devices.get(&dev_addr).unwrap();
+
+                    groups
+                        .entry(endpoint_key(dev))
+                        .or_default()
+                        .push((*sibling_addr, dev_addr));
+                }
+                None => kept.push(*sibling_addr),
+            }
+        }
+
+        for group in groups.values() {
+            let (sibling_addr, dev_addr) = group[0];
+
+            if group.len() > 1 {
+                write_state.collapsed.insert(sibling_addr, group.len());
+                write_state.collapsed.insert(dev_addr, group.len());
+
+                //
+                // The endpoints behind the downstream ports that aren't
+                // drawn still count as reachable.
+                //
+                write_state
+                    .devices
+                    .extend(group.iter().map(|(_, dev_addr)| *dev_addr));
+            }
+
+            kept.push(sibling_addr);
+        }
+
+        kept.sort();
+
+        kept
+    }
+
+    //
+    // A bifurcated slot shows up as several devices behind the slot's port,
+    // each with a link narrower than the slot, and with the widths of
+    // their links adding up to the width of the slot.  Returns how the slot
+    // is split up, e.g. "4×4" or "8+4+4".
+    //
+    fn bifurcation(&self, parent_dev: &PciDevice, bus_devices: &[PciAddr]) -> Option<String> {
+        let slot_width = parent_dev.lnk_cap()?.width();
+
+        let widths = bus_devices
+            .chunk_by(|a, b| a.device() == b.device())
+            .filter_map(|functions| {
+                functions.iter().find_map(|dev_addr| {
+                    let dev = self.pci_devices.get(dev_addr).unwrap();
+
+                    dev.lnk_sta().map(|lnk_sta| lnk_sta.width())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if widths.len() < 2
+            || widths.iter().map(|width| *width as u32).sum::<u32>() != slot_width as u32
+        {
+            return None;
+        }
+
+        if widths.iter().all(|width| *width == widths[0]) {
+            Some(format!("{}×{}", widths.len(), widths[0]))
+        } else {
+            Some(
+                widths
+                    .iter()
+                    .map(|width| width.to_string())
+                    .collect::<Vec<_>>()
+                    .join("+"),
+            )
+        }
+    }
+
+    fn write_bus<G: GraphWriter>(
+        &self,
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+        parent_dev: &PciDevice,
+        domain: u32,
+        bus: u8,
+    ) -> Result<(), Error> {
+        g.blank()?;
+        g.comment(&format!("domain {:04x} bus {:02x}", domain, bus))?;
+
+        if self.is_pruned(write_state, parent_dev) {
+            return g.comment("left out because of --min-speed");
+        }
+
+        let mut bus_devices = self.child_devices(parent_dev);
+
+        if !bus_devices.is_empty() {
+            bus_devices.retain(|dev_addr| write_state.is_kept(dev_addr));
+
+            if bus_devices.is_empty() {
+                return g.comment("left out because of --include or --exclude");
+            }
+        }
+
+        let slot_name = self.slot_name(parent_dev);
+
+        g.blank()?;
+
+        let intermediate = if slot_name.is_some() {
+            format!("{}_{:02x}", parent_dev.addr(), bus)
+        } else {
+            format!("{}", parent_dev.addr())
+        };
+
+        if let Some(slot_name) = &slot_name {
+            g.edge(
+                &parent_dev.addr().to_string(),
+                &intermediate,
+                &write_state.port_link_attrs(parent_dev),
+            )?;
+
+            let slot_name = if parent_dev.hotplug_capable() == Some(true) {
+                format!("{} (hotplug)", slot_name)
+            } else {
+                slot_name.to_string()
+            };
+
+            //
+            // A power limit of zero means that firmware didn't set one.
+            //
+            let slot_name = match parent_dev.slot_power_limit() {
+                Some(watts) if watts > 0.0 => format!("{}\n{}W", slot_name, watts),
+                _ => slot_name,
+            };
+
+            let label = match self.bifurcation(parent_dev, &bus_devices) {
+                Some(bifurcation) => format!("{}\nbifurcated {}", slot_name, bifurcation),
+                None => slot_name,
+            };
+
+            g.node(&intermediate, &Attrs::label(label).shape("rectangle"))?;
+        }
+
+        let upstream_ports = bus_devices
+            .iter()
+            .filter(|dev_addr| self.pci_devices.get(dev_addr).unwrap().is_upstream_port())
+            .copied()
+            .collect::<Vec<_>>();
+
+        let pci_bridges = bus_devices
+            .iter()
+            .filter(|dev_addr| self.pci_devices.get(dev_addr).unwrap().is_pci_bridge())
+            .copied()
+            .collect::<Vec<_>>();
+
+        let all_endpoints = bus_devices
+            .iter()
+            .filter(|dev_addr| self.pci_devices.get(dev_addr).unwrap().is_endpoint())
+            .copied()
+            .collect::<Vec<_>>();
+
+        let endpoints = self.collapse_identical(write_state, &all_endpoints, Some);
+
+        if let Some(first_dev_addr) = bus_devices.first() {
+            let first_dev = self.pci_devices.get(first_dev_addr).unwrap();
+
+            write_state.devices.insert(*first_dev_addr);
+
+            let attrs = if self.pci_device_unique_id(parent_dev)
+                != self.pci_device_unique_id(first_dev)
+                && let Some(lnk_sta) = first_dev.lnk_sta()
+            {
+                let mut attrs = match first_dev.lnk_cap() {
+                    _ if lnk_sta.is_link_down() => Attrs::label("LINK DOWN").color("red"),
+                    Some(lnk_cap) if is_capped([parent_dev, first_dev], &lnk_sta, &lnk_cap) => {
+                        let lnk_sta = LnkSta::new(lnk_sta.speed(), lnk_sta.width(), false);
+
+                        Attrs::label(write_state.link_label(
+                            &format!("{}\n(capped)", lnk_sta),
+                            lnk_sta.bandwidth_gbps(),
+                        ))
+                    }
+                    Some(lnk_cap) if lnk_sta.is_degraded_against(&lnk_cap) => {
+                        Attrs::label(write_state.link_label(
+                            &format!("{} (cap {})", lnk_sta, lnk_cap),
+                            lnk_sta.bandwidth_gbps(),
+                        ))
+                        .color("red")
+                    }
+                    _ => Attrs::label(write_state.link_label(&lnk_sta, lnk_sta.bandwidth_gbps())),
+                };
+
+                //
+                // Retimers are mostly found on riser cables and long board
+                // traces, which is where signal integrity problems tend to
+                // be as well.
+                //
+                let retimers = [parent_dev, first_dev]
+                    .iter()
+                    .filter_map(|dev| dev.retimer_count())
+                    .max()
+                    .unwrap_or(0);
+
+                if retimers > 0
+                    && let Some(label) = &mut attrs.label
+                {
+                    if retimers == 1 {
+                        label.push_str("\n(retimer)");
+                    } else {
+                        label.push_str(&format!("\n({} retimers)", retimers));
+                    }
+                }
+
+                //
+                // Links with a separate reference clock at each end, as is
+                // common with riser cards and cabled PCIe, are more prone to
+                // clocking problems, such as mismatched spread spectrum
+                // clocking.
+                //
+                if [parent_dev, first_dev]
+                    .iter()
+                    .any(|dev| dev.common_clock() == Some(false))
+                    && let Some(label) = &mut attrs.label
+                {
+                    label.push_str("\n(sep-clk)");
+                }
+
+                //
+                // Errors logged by AER at either end usually point at a
+                // marginal link, even if it trained at its full speed and
+                // width.
+                //
+                if [parent_dev, first_dev]
+                    .iter()
+                    .any(|dev| dev.aer_has_errors() == Some(true))
+                {
+                    if let Some(label) = &mut attrs.label {
+                        label.push_str("\n(AER errors)");
+                    }
+
+                    attrs = attrs.color("red");
+                }
+
+                if write_state.options.show_aspm
+                    && let Some(aspm_label) = aspm_label(first_dev)
+                    && let Some(label) = &mut attrs.label
+                {
+                    label.push('\n');
+                    label.push_str(&aspm_label);
+                }
+
+                write_state
+                    .scale_edge(attrs, lnk_sta.width())
+                    .data("speed", lnk_sta.speed())
+                    .data("width", lnk_sta.width())
+            } else {
+                Attrs::default()
+            };
+
+            //
+            // If the downstream device is a multi-function endpoint, its
+            // functions are drawn in a cluster of their own (see below), and
+            // we draw the link into that cluster rather than to function 0,
+            // unless the functions are drawn as a single record node.
+            //
+            let attrs = if upstream_ports.is_empty()
+                && pci_bridges.is_empty()
+                && write_state.topology_clusters()
+                && !write_state.record_functions()
+                && endpoints.first() == Some(first_dev_addr)
+                && endpoints
+                    .get(1)
+                    .is_some_and(|dev_addr| dev_addr.device() == first_dev_addr.device())
+            {
+                let unique_id = self.pci_device_unique_id(first_dev);
+
+                attrs.lhead(format!(
+                    "cluster{}",
+                    write_state.get_cluster_index(&unique_id)
+                ))
+            } else {
+                attrs
+            };
+
+            g.edge(&intermediate, &first_dev_addr.to_string(), &attrs)?;
+        } else {
+            //
+            // A card that is present but failed to train a link shows up
+            // as a link of width x0 on the port, with nothing behind it.
+            // Empty slots also have their link down, so only flag this if
+            // the slot reports that a card is present.
+            //
+            let link_down = parent_dev.is_card_present()
+                && parent_dev
+                    .lnk_sta()
+                    .is_some_and(|lnk_sta| lnk_sta.is_link_down());
+
+            Machine::write_empty_bus(
+                g,
+                &intermediate,
+                domain,
+                bus,
+                &if link_down {
+                    Attrs::label("LINK DOWN").color("red")
+                } else if slot_name.is_none() {
+                    write_state.port_link_attrs(parent_dev)
+                } else {
+                    Attrs::default()
+                },
+                link_down,
+            )?;
+        }
+
+        if !upstream_ports.is_empty() {
+            for dev_addr in upstream_ports {
+                self.write_switch(g, write_state, dev_addr)?;
+            }
+        } else if !pci_bridges.is_empty() {
+            for dev_addr in pci_bridges {
+                let dev = self.pci_devices.get(&dev_addr).unwrap();
+
+                write_state.devices.insert(dev_addr);
+
+                if write_state.topology_clusters() {
+                    let unique_id = self.pci_device_unique_id(dev);
+
+                    g.blank()?;
+
+                    g.begin_cluster(
+                        write_state.get_cluster_index(&unique_id),
+                        Some(&write_state.options.labels.pci_bridge),
+                    )?;
+
+                    g.cluster_member(&dev_addr.to_string())?;
+
+                    g.end_cluster()?;
+                }
+
+                g.blank()?;
+
+                g.node(&dev_addr.to_string(), &acs_attrs(dev, Attrs::default()))?;
+
+                let secondary_bus = self.secondary_bus(dev).unwrap();
+                let subordinate_bus = self.subordinate_bus(dev).unwrap_or(secondary_bus);
+
+                g.blank()?;
+
+                if subordinate_bus > secondary_bus {
+                    g.comment(&format!(
+                        "domain {:04x} buses {:02x}-{:02x}",
+                        domain, secondary_bus, subordinate_bus
+                    ))?;
+                } else {
+                    g.comment(&format!("domain {:04x} bus {:02x}", domain, secondary_bus))?;
+                }
+
+                //
+                // Conventional PCI has no notion of ports, so the devices on
+                // all buses behind the bridge are drawn behind it, rather than
+                // just those on its secondary bus.  This way, devices behind
+                // PCI-to-PCI bridges further down aren't left out.
+                //
+                let mut secondary_devices = self.descendant_devices(dev);
+
+                if secondary_devices.is_empty() {
+                    g.blank()?;
+
+                    Machine::write_empty_bus(
+                        g,
+                        &dev_addr.to_string(),
+                        domain,
+                        secondary_bus,
+                        &Attrs::default(),
+                        false,
+                    )?;
+                }
+
+                secondary_devices.retain(|dev_addr| write_state.is_kept(dev_addr));
+
+                write_state.devices.extend(&secondary_devices);
+
+                for secondary_device in &secondary_devices {
+                    g.blank()?;
+
+                    g.edge(
+                        &dev_addr.to_string(),
+                        &secondary_device.to_string(),
+                        &Attrs::default(),
+                    )?;
+
+                    g.blank()?;
+
+                    let dev = self.pci_devices.get(secondary_device).unwrap();
+
+                    g.node(
+                        &secondary_device.to_string(),
+                        &self.device_attrs(dev, write_state),
+                    )?;
+
+                    //
+                    // The devices behind PCI-to-PCI bridges further down are
+                    // drawn behind this bridge, but a bridge with nothing
+                    // behind it gets a placeholder for its empty bus.
+                    //
+                    if let Some(bridge_secondary_bus) = self.secondary_bus(dev)
+                        && self.descendant_devices(dev).is_empty()
+                    {
+                        g.blank()?;
+
+                        Machine::write_empty_bus(
+                            g,
+                            &secondary_device.to_string(),
+                            domain,
+                            bridge_secondary_bus,
+                            &Attrs::default(),
+                            false,
+                        )?;
+                    }
+                }
+            }
+        } else if let Some(first_dev_addr) = endpoints.first() {
+            let first_dev = self.pci_devices.get(first_dev_addr).unwrap();
+
+            write_state.devices.extend(&all_endpoints);
+
+            if write_state.record_functions() {
+                return self.write_function_records(g, write_state, &endpoints);
+            }
+
+            g.blank()?;
+
+            g.node(
+                &first_dev_addr.to_string(),
+                &self.device_attrs(first_dev, write_state),
+            )?;
+
+            if endpoints.len() > 1 {
+                if write_state.topology_clusters() {
+                    //
+                    // Group the functions of each multi-function device.
+                    //
+                    for functions in endpoints.chunk_by(|a, b| a.device() == b.device()) {
+                        if functions.len() > 1 {
+                            let dev = self.pci_devices.get(&functions[0]).unwrap();
+
+                            let unique_id = self.pci_device_unique_id(dev);
+
+                            g.blank()?;
+
+                            g.begin_cluster(write_state.get_cluster_index(&unique_id), None)?;
+
+                            for dev_addr in functions {
+                                g.cluster_member(&dev_addr.to_string())?;
+                            }
+
+                            g.end_cluster()?;
+                        }
+                    }
+                }
+
+                for a_b in endpoints.windows(2) {
+                    g.blank()?;
+
+                    g.edge(&a_b[0].to_string(), &a_b[1].to_string(), &Attrs::default())?;
+
+                    let dev = self.pci_devices.get(&a_b[1]).unwrap();
+
+                    g.node(&a_b[1].to_string(), &self.device_attrs(dev, write_state))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    //
+    // Draws a placeholder node for a bus that a port or bridge leads to but
+    // that has no devices on it, linked to from `from`, so that the chain of
+    // nodes leading to the bus doesn't just end.
+    //
+    fn write_empty_bus<G: GraphWriter>(
+        g: &mut G,
+        from: &str,
+        domain: u32,
+        bus: u8,
+        attrs: &Attrs,
+        link_down: bool,
+    ) -> Result<(), Error> {
+        let bus_node = format!("bus {:04x}:{:02x}", domain, bus);
+
+        g.edge(from, &bus_node, attrs)?;
+
+        g.blank()?;
+
+        g.node(
+            &bus_node,
+            &if link_down {
+                Attrs::default().shape("rectangle").color("red")
+            } else {
+                Attrs::default().shape("rectangle")
+            },
+        )
+    }
+
+    //
+    // Draws the switch with the given upstream port, and everything behind
+    // it.
+    //
+    fn write_switch<G: GraphWriter>(
+        &self,
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+        dev_addr: PciAddr,
+    ) -> Result<(), Error> {
+        let dev = self.pci_devices.get(&dev_addr).unwrap();
+
+        let downstream_port_bus = self.secondary_bus(dev);
+
+        let mut downstream_ports = self.child_devices(dev);
+
+        //
+        // A switch that was captured while it was being reset can have no
+        // downstream ports, which is flagged on the upstream port rather
+        // than drawn as an empty switch.
+        //
+        if downstream_ports.is_empty() {
+            write_state.devices.insert(dev_addr);
+
+            g.blank()?;
+
+            g.node(
+                &dev_addr.to_string(),
+                &Attrs::label(format!("{}\nno downstream ports", dev_addr)).color("red"),
+            )?;
+
+            return Ok(());
+        }
+
+        let downstream_port_bus = downstream_port_bus.unwrap();
+
+        downstream_ports.retain(|dev_addr| write_state.is_kept(dev_addr));
+
+        //
+        // Downstream ports that don't advertise a port number go
+        // last, in address order.
+        //
+        if write_state.options.order_by_port {
+            downstream_ports.sort_by_key(|port_addr| {
+                let port = self.pci_devices.get(port_addr).unwrap();
+
+                port.lnk_cap()
+                    .and_then(|lnk_cap| lnk_cap.port())
+                    .map_or((1, 0), |port| (0, port))
+            });
+        }
+
+        write_state.devices.insert(dev_addr);
+        write_state.devices.extend(&downstream_ports);
+
+        let downstream_ports =
+            self.collapse_identical(write_state, &downstream_ports, |port_addr| {
+                self.lone_endpoint(port_addr)
+            });
+
+        if write_state.topology_clusters() {
+            let unique_id = self.pci_device_unique_id(dev);
+
+            g.blank()?;
+
+            g.begin_cluster(
+                write_state.get_cluster_index(&unique_id),
+                Some(&write_state.options.labels.pcie_switch),
+            )?;
+
+            g.cluster_member(&dev_addr.to_string())?;
+
+            for downstream_port_addr in &downstream_ports {
+                g.cluster_member(&downstream_port_addr.to_string())?;
+            }
+
+            g.end_cluster()?;
+        }
+
+        g.blank()?;
+
+        g.node(&dev_addr.to_string(), &Attrs::default())?;
+
+        for downstream_port_addr in &downstream_ports {
+            let downstream_port = self.pci_devices.get(downstream_port_addr).unwrap();
+
+            g.node(
+                &downstream_port_addr.to_string(),
+                &acs_attrs(
+                    downstream_port,
+                    match write_state.collapsed.get(downstream_port_addr) {
+                        Some(count) => Attrs::label(format!("{}× {}", count, downstream_port_addr)),
+                        None => Attrs::default(),
+                    },
+                ),
+            )?;
+        }
+
+        g.blank()?;
+
+        g.comment(&format!(
+            "domain {:04x} bus {:02x} is a switch internal bus",
+            dev_addr.domain(),
+            downstream_port_bus
+        ))?;
+
+        for downstream_port_addr in &downstream_ports {
+            g.blank()?;
+            g.edge(
+                &dev_addr.to_string(),
+                &downstream_port_addr.to_string(),
+                &Attrs::default(),
+            )?;
+        }
+
+        for downstream_port_addr in &downstream_ports {
+            let downstream_port = self.pci_devices.get(downstream_port_addr).unwrap();
+
+            let secondary_bus = self.secondary_bus(downstream_port).unwrap();
+
+            self.write_bus(
+                g,
+                write_state,
+                downstream_port,
+                downstream_port_addr.domain(),
+                secondary_bus,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn device_name(&self, dev: &PciDevice) -> String {
+        self.short_name(dev)
+            .or_else(|| {
+                self.pci_ids
+                    .as_ref()
+                    .and_then(|pci_ids| pci_ids.device_name(dev.vendor_id(), dev.device_id()))
+            })
+            .or_else(|| dev.full_name())
+            .map_or_else(
+                || format!("unknown {:04x}:{:04x}", dev.vendor_id(), dev.device_id()),
+                |name| name.to_string(),
+            )
+    }
+
+    //
+    // A device configured for a smaller Max Payload Size than both it and
+    // the port it is attached to support moves data in more, smaller TLPs
+    // than it needs to.  The port can legitimately hold the device back,
+    // so the port's capability is taken into account if it is known.
+    //
+    fn mps_suboptimal(&self, dev: &PciDevice) -> bool {
+        let (Some(supported), Some(configured)) = (dev.max_payload_supported(), dev.max_payload())
+        else {
+            return false;
+        };
+
+        let port_supported = self
+            .pci_devices
+            .values()
+            .find(|port| {
+                port.addr().domain() == dev.addr().domain()
+                    && self.secondary_bus(port) == Some(dev.addr().bus())
+            })
+            .and_then(|port| port.max_payload_supported());
+
+        supported.min(port_supported.unwrap_or(supported)) > configured
+    }
+
+    fn device_label(&self, dev: &PciDevice, write_state: &MachineWriteState) -> String {
+        let mut lines = match write_state.collapsed.get(&dev.addr()) {
+            Some(count) => vec![format!("{}× {}", count, self.device_name(dev))],
+            None => vec![self.device_name(dev)],
+        };
+
+        if let Some(kernel_driver) = dev.kernel_driver() {
+            lines.push(kernel_driver.to_string());
+        }
+
+        lines.push(dev.addr().to_string());
+
+        if self.mps_suboptimal(dev) {
+            lines.push("MPS suboptimal".to_string());
+        }
+
+        if dev.aer_has_errors() == Some(true) {
+            lines.push("AER errors".to_string());
+        }
+
+        if write_state.options.show_flr && dev.is_endpoint() && dev.supports_flr() == Some(false) {
+            lines.push("no FLR".to_string());
+        }
+
+        if write_state.options.show_rev
+            && let Some(revision) = dev.revision()
+        {
+            lines.push(format!("rev {:02x}", revision));
+        }
+
+        write_state.numa_label(lines.join("\n"), dev)
+    }
+
+    //
+    // Draws each multi-function device among `endpoints` as a single record
+    // node with a field per function, named after function 0, rather than
+    // as a cluster of nodes.
+    //
+    fn write_function_records<G: GraphWriter>(
+        &self,
+        g: &mut G,
+        write_state: &mut MachineWriteState,
+        endpoints: &[PciAddr],
+    ) -> Result<(), Error> {
+        let mut previous: Option<PciAddr> = None;
+
+        for functions in endpoints.chunk_by(|a, b| a.device() == b.device()) {
+            let dev_addr = functions[0];
+            let dev = self.pci_devices.get(&dev_addr).unwrap();
+
+            let mut attrs = self.device_attrs(dev, write_state);
+
+            if functions.len() > 1 {
+                let fields = std::iter::once(format!(
+                    "{:04x}:{:02x}:{:02x}",
+                    dev_addr.domain(),
+                    dev_addr.bus(),
+                    dev_addr.device()
+                ))
+                .chain(functions.iter().map(|function_addr| {
+                    let function = self.pci_devices.get(function_addr).unwrap();
+
+                    format!(
+                        "{}: {}",
+                        function_addr.function(),
+                        self.device_name(function)
+                    )
+                }))
+                .collect::<Vec<_>>();
+
+                attrs.label = Some(fields.join("\n"));
+                attrs = attrs.shape("record").fields(fields);
+            }
+
+            if let Some(previous) = previous {
+                g.blank()?;
+
+                g.edge(
+                    &previous.to_string(),
+                    &dev_addr.to_string(),
+                    &Attrs::default(),
+                )?;
+            }
+
+            g.blank()?;
+
+            g.node(&dev_addr.to_string(), &attrs)?;
+
+            previous = Some(dev_addr);
+        }
+
+        Ok(())
+    }
+
+    fn device_attrs(&self, dev: &PciDevice, write_state: &MachineWriteState) -> Attrs {
+        let mut attrs = Attrs::label(self.device_label(dev, write_state))
+            .data("vendor", format!("{:04x}", dev.vendor_id()))
+            .data("device", format!("{:04x}", dev.device_id()));
+
+        if let Some(numa_node) = dev.numa_node() {
+            attrs = attrs.data("numa", numa_node);
+        }
+
+        if dev.lnk_sta().is_some_and(|lnk_sta| lnk_sta.is_link_down()) {
+            attrs = attrs.color("red");
+        }
+
+        if write_state.options.tooltips {
+            attrs = attrs.tooltip(self.device_tooltip(dev));
+        }
+
+        //
+        // Conventional PCI devices are drawn differently, to tell them
+        // apart from PCIe endpoints.
+        //
+        if dev.is_legacy_pci_device() {
+            attrs = attrs.shape("hexagon");
+        }
+
+        if write_state.options.color_by_class {
+            attrs.fillcolor(class_color(dev.class_code()))
+        } else {
+            attrs
+        }
+    }
+
+    //
+    // The details that are left out of device labels to keep them short,
+    // for viewers that can show tooltips, such as browsers showing SVG.
+    //
+    fn device_tooltip(&self, dev: &PciDevice) -> String {
+        let mut lines = vec![format!(
+            "{} {}",
+            dev.addr(),
+            dev.full_name()
+                .map_or_else(|| self.device_name(dev), str::to_string)
+        )];
+
+        if let Some(lnk_cap) = dev.lnk_cap() {
+            lines.push(format!("LnkCap: {:#}", lnk_cap));
+        }
+
+        if let Some(lnk_sta) = dev.lnk_sta() {
+            lines.push(format!("LnkSta: {:#}", lnk_sta).replace('\n', " "));
+        }
+
+        if let Some(kernel_driver) = dev.kernel_driver() {
+            lines.push(format!("Driver: {}", kernel_driver));
+        }
+
+        if let Some(numa_node) = dev.numa_node() {
+            lines.push(format!("NUMA node: {}", numa_node));
+        }
+
+        if let Some(serial_number) = dev.serial_number() {
+            lines.push(format!("Serial: {:016x}", serial_number));
+        }
+
+        lines.join("\n")
+    }
+
+    fn pci_device_unique_id(&self, dev: &PciDevice) -> String {
+        self.pci_device_unique_id_reason(dev).0
+    }
+
+    //
+    // Returns the identifiers of the clusters that the machine would be
+    // drawn in with the default options: one per group of root ports, one
+    // per switch and PCI bridge, and one per multi-function endpoint.  A
+    // switch without downstream ports isn't drawn as a cluster.
+    //
+    fn cluster_identifiers(&self) -> BTreeSet<String> {
+        let mut identifiers = BTreeSet::new();
+        let mut functions = BTreeMap::<(u32, u8, u8), Vec<&PciDevice>>::new();
+
+        for (addr, dev) in &self.pci_devices {
+            if dev.is_root_port() && self.secondary_bus(dev).is_some() {
+                identifiers.insert(dev.device_group_name());
+            } else if dev.is_upstream_port() {
+                if !self.child_devices(dev).is_empty() {
+                    identifiers.insert(self.pci_device_unique_id(dev));
+                }
+            } else if dev.is_pci_bridge() {
+                identifiers.insert(self.pci_device_unique_id(dev));
+            } else if dev.is_endpoint() {
+                functions
+                    .entry((addr.domain(), addr.bus(), addr.device()))
+                    .or_default()
+                    .push(dev);
+            }
+        }
+
+        for devs in functions.values().filter(|devs| devs.len() > 1) {
+            identifiers.insert(self.pci_device_unique_id(devs[0]));
+        }
+
+        identifiers
+    }
+
+    //
+    // Returns the identifier of the cluster that `dev` is drawn in, along
+    // with why it was chosen, for --explain-clusters.
+    //
+    fn pci_device_unique_id_reason(&self, dev: &PciDevice) -> (String, &'static str) {
+        if let Some(serial_number) = dev.serial_number() {
+            if dev.is_upstream_port() {
+                let downstream_ports = self.child_devices(dev);
+
+                for downstream_port_addr in &downstream_ports {
+                    let downstream_port = self.pci_devices.get(downstream_port_addr).unwrap();
+
+                    if let Some(downstream_serial_number) = downstream_port.serial_number() {
+                        //
+                        // If a downstream port for this upstream port has a different
+                        // Device Serial Number than the upstream port does, then don't
+                        // trust the Device Serial Number for the upstream port.
+                        //
+                        if serial_number != downstream_serial_number {
+                            return (
+                                format!("{}", dev.addr()),
+                                "address, as a downstream port has a different serial number",
+                            );
+                        }
+                    }
+                }
+
+                //
+                // Some vendors ship all of their switches with the same Device
+                // Serial Number.  If another upstream port reports the same
+                // Device Serial Number, then don't trust it for either of them,
+                // unless their Vendor-Specific Extended Capabilities tell them
+                // apart, as they do on some cascaded vendor switches.
+                //
+                let vsec_ids = dev.vsec_ids();
+
+                let same_serial_number = self
+                    .pci_devices
+                    .values()
+                    .filter(|other_dev| {
+                        other_dev.addr() != dev.addr()
+                            && other_dev.is_upstream_port()
+                            && other_dev.serial_number() == Some(serial_number)
+                    })
+                    .collect::<Vec<_>>();
+
+                if same_serial_number
+                    .iter()
+                    .any(|other_dev| other_dev.vsec_ids() == vsec_ids)
+                {
+                    return (
+                        format!("{}", dev.addr()),
+                        "address, as another switch has the same serial number",
+                    );
+                }
+
+                if !same_serial_number.is_empty() {
+                    let mut unique_id = format!("{:016x}", serial_number);
+
+                    for (id, len) in vsec_ids {
+                        unique_id.push_str(&format!("-{:04x}:{:03x}", id, len));
+                    }
+
+                    return (
+                        unique_id,
+                        "Device Serial Number and Vendor-Specific capabilities, as another \
+                         switch has the same serial number",
+                    );
+                }
+            }
+
+            return (format!("{:016x}", serial_number), "Device Serial Number");
+        }
+
+        (
+            format!("{}", dev.addr()),
+            "address, as there is no Device Serial Number",
+        )
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+//
+// `lspci -mm -nn` prints a line of quoted fields for each device, e.g.:
+//
+//   00:1f.3 "Audio device [0403]" "Intel Corporation [8086]" "Device [a348]" -r10 "Dell [1028]" "Device [0872]"
+//
+// This rewrites the first line of a section in that format into the header
+// line that `lspci -nn` prints, adding a Subsystem line if the section has
+// none, so that the rest of the section is parsed as usual.  Returns `None`
+// if the first line isn't in that format.
+//
+pub(crate) fn rewrite_header(desc: &str) -> Option<String> {
+    static ADDR_RE: OnceLock<Regex> = OnceLock::new();
+
+    let addr_re = ADDR_RE.get_or_init(|| {
+        Regex::new(concat!(
+            r"^((?:[0-9a-f]{4,8}:)?",
+            r"(?:[0-9a-f]{2}:[0-9a-f]{2}\.[0-7]/)*",
+            r#"[0-9a-f]{2}:[0-9a-f]{2}\.[0-7]) ""#
+        ))
+        .unwrap()
+    });
+
+    let (line, rest) = desc.split_once('\n').unwrap_or((desc, ""));
+
+    let addr = &addr_re.captures(line)?[1];
+
+    let mut fields = Vec::new();
+    let mut revision = None;
+    let mut prog_if = None;
+
+    let mut chars = line[addr.len()..].chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => {}
+            '"' => {
+                let mut field = String::new();
+
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => field.push(chars.next()?),
+                        c => field.push(c),
+                    }
+                }
+
+                fields.push(field);
+            }
+            '-' => {
+                let option = chars.next()?;
+                let value = chars.by_ref().take_while(|c| *c != ' ').collect::<String>();
+
+                match option {
+                    'r' => revision = Some(value),
+                    'p' => prog_if = Some(value),
+                    _ => {}
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    let (class, class_id) = split_id(fields.first()?)?;
+    let (vendor, vendor_id) = split_id(fields.get(1)?)?;
+    let (device, device_id) = split_id(fields.get(2)?)?;
+
+    let mut header = format!(
+        "{} {} [{}]: {} {} [{}:{}]",
+        addr, class, class_id, vendor, device, vendor_id, device_id
+    );
+
+    if let Some(revision) = revision {
+        header.push_str(&format!(" (rev {})", revision));
+    }
+
+    if let Some(prog_if) = prog_if {
+        header.push_str(&format!(" (prog-if {})", prog_if));
+    }
+
+    if !rest.contains("\tSubsystem: ")
+        && let Some((subsystem_vendor, subsystem_vendor_id)) =
+            fields.get(3).map(String::as_str).and_then(split_id)
+        && let Some((subsystem_device, subsystem_device_id)) =
+            fields.get(4).map(String::as_str).and_then(split_id)
+    {
+        header.push_str(&format!(
+            "\n\tSubsystem: {} {} [{}:{}]",
+            subsystem_vendor, subsystem_device, subsystem_vendor_id, subsystem_device_id
+        ));
+    }
+
+    Some(format!("{}\n{}", header, rest))
+}
+
+//
+// Splits a field such as `Intel Corporation [8086]` into its name and ID.
+//
+fn split_id(field: &str) -> Option<(&str, &str)> {
+    static FIELD_RE: OnceLock<Regex> = OnceLock::new();
+
+    let caps = FIELD_RE
+        .get_or_init(|| Regex::new(r"^(.*) \[([0-9a-f]{4,6})\]$").unwrap())
+        .captures(field)?;
+
+    Some((caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str()))
+}
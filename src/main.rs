@@ -1,461 +1,940 @@
-mod lnk_cap;
-mod lnk_sta;
-mod pci_addr;
-mod pci_device;
-
 use std::{
-    collections::BTreeMap,
-    io::{Error, Read, Write, stdin, stdout},
-    sync::OnceLock,
+    env,
+    fs::File,
+    io::{BufWriter, ErrorKind, IsTerminal, Read, Write, stdin, stdout},
+    process::{Command, Stdio, exit},
+    thread,
 };
 
-use lnk_cap::LnkCap;
-use lnk_sta::LnkSta;
-use pci_addr::PciAddr;
-use pci_device::PciDevice;
-use regex::Regex;
-
-#[derive(Debug, Default)]
-struct Machine {
-    dmi_slots: BTreeMap<PciAddr, String>,
-    pci_devices: BTreeMap<PciAddr, PciDevice>,
+use pcigraph::{DeviceNames, GraphOptions, GroupBy, Machine, PciIds, RankDir};
+
+#[derive(Clone, Copy, Debug, Default)]
+enum Format {
+    Csv,
+    #[default]
+    Dot,
+    GraphMl,
+    Json,
+    Mermaid,
+    PlantUml,
+    Png,
+    Svg,
+    Tree,
 }
 
-impl Machine {
-    fn parse<T: Read>(&mut self, src: &mut T) {
-        let mut data = String::new();
-
-        src.read_to_string(&mut data).unwrap();
-
-        let sections = data
-            .split("\n\n")
-            .filter(|str| !str.is_empty())
-            .collect::<Vec<&str>>();
-
-        static DMI_SLOT_RE: OnceLock<Regex> = OnceLock::new();
-
-        let dmi_slot_re = DMI_SLOT_RE.get_or_init(|| {
-            Regex::new(concat!(
-                r"(?s), DMI type 9, .*",
-                r"Designation: ([^\n]*)\n.*",
-                r"Bus Address: ([0-9a-f]{4}):([0-9a-f]{2}):([0-9a-f]{2})\.([0-7])"
-            ))
-            .unwrap()
-        });
-
-        for section in sections {
-            if let Some(caps) = dmi_slot_re.captures(section) {
-                let name = &caps[1];
-                let domain = u16::from_str_radix(&caps[2], 16).unwrap();
-                let bus = u8::from_str_radix(&caps[3], 16).unwrap();
-                let device = u8::from_str_radix(&caps[4], 16).unwrap();
-                let function = u8::from_str_radix(&caps[5], 16).unwrap();
-
-                self.dmi_slots.insert(
-                    PciAddr::new(domain, bus, device, function),
-                    name.to_string(),
-                );
-            }
-
-            if let Some(pci_device) = PciDevice::parse(section) {
-                self.pci_devices.insert(pci_device.addr(), pci_device);
-            }
+impl Format {
+    fn parse(format: &str) -> Result<Format, String> {
+        match format {
+            "csv" => Ok(Format::Csv),
+            "dot" => Ok(Format::Dot),
+            "graphml" => Ok(Format::GraphMl),
+            "json" => Ok(Format::Json),
+            "mermaid" => Ok(Format::Mermaid),
+            "plantuml" => Ok(Format::PlantUml),
+            "png" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
+            "tree" => Ok(Format::Tree),
+            _ => Err(format!("unknown output format {}", format)),
         }
     }
 
-    fn bus_devices(&self, domain: u16, bus: u8) -> Vec<PciAddr> {
-        self.pci_devices
-            .keys()
-            .filter(|dev_addr| dev_addr.domain() == domain && dev_addr.bus() == bus)
-            .copied()
-            .collect::<Vec<_>>()
+    //
+    // The output format to have graphviz render the dot output into, for
+    // the image formats.
+    //
+    fn graphviz_format(self) -> Option<&'static str> {
+        match self {
+            Format::Png => Some("png"),
+            Format::Svg => Some("svg"),
+            _ => None,
+        }
     }
 }
 
-struct MachineWriteState {
-    cluster_id: u16,
-    clusters: BTreeMap<String, u16>,
+#[derive(Debug, Default)]
+struct Options {
+    inputs: Vec<String>,
+    from_sysfs: bool,
+    lspci: Option<String>,
+    output: Option<String>,
+    format: Format,
+    pci_ids: Option<String>,
+    names: Option<String>,
+    host_marker: Option<String>,
+    check: bool,
+    explain_clusters: bool,
+    summary: bool,
+    verbose: bool,
+    strict: bool,
+    bridge_paths: bool,
+    anonymize: bool,
+    anonymize_addresses: bool,
+    split_by_numa: bool,
+    graph_options: GraphOptions,
 }
 
-impl MachineWriteState {
-    fn new() -> MachineWriteState {
-        MachineWriteState {
-            cluster_id: 0,
-            clusters: BTreeMap::new(),
-        }
-    }
-
-    fn get_cluster_index(&mut self, identifier: &str) -> u16 {
-        *self
-            .clusters
-            .entry(identifier.to_string())
-            .or_insert_with(|| {
-                self.cluster_id += 1;
-                self.cluster_id
-            })
-    }
+fn option_value<T: Iterator<Item = String>>(arg: &str, args: &mut T) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("option {} requires an argument", arg))
 }
 
-impl Machine {
-    fn write_graph<T: Write>(&self, w: &mut T) -> Result<(), Error> {
-        let mut write_state = MachineWriteState::new();
-
-        writeln!(w, "graph pci {{")?;
-        writeln!(w, "\trankdir=LR;")?;
-
-        for (addr, dev) in &self.pci_devices {
-            if dev.is_root_port() {
-                //
-                // Dell PowerEdge R730xd PCI device 00:00.0 (Host Bridge) claims to be a
-                // PCI Express (v2) Root Port, but has a type 0 configuration space header.
-                // Ignore Root Ports that don't have a type 1 configuration space header.
-                //
-                if let Some(secondary_bus) = dev.secondary_bus() {
-                    writeln!(w)?;
-                    writeln!(
-                        w,
-                        "\t######################################################################"
-                    )?;
-                    writeln!(w, "\t# root port {}", addr)?;
-
-                    writeln!(w)?;
-                    writeln!(
-                        w,
-                        "\t\"{}\" [ label=\"Root port\\n{}\" shape=rectangle ];",
-                        addr, addr
-                    )?;
-
-                    let device_group_name = dev.device_group_name();
-                    let cluster_id = write_state.get_cluster_index(&device_group_name);
-
-                    writeln!(w)?;
-                    writeln!(w, "\tsubgraph cluster{} {{", cluster_id)?;
-                    writeln!(w, "\t\tlabel=\"{}\";", device_group_name)?;
-                    writeln!(w, "\t\t\"{}\";", addr)?;
-                    writeln!(w, "\t}}")?;
-
-                    self.write_bus(w, &mut write_state, dev, addr.domain(), secondary_bus)?;
-                }
+//
+// Graphviz attribute lists given on the command line are pasted into the
+// output as-is, so make sure that they can't break out of their brackets.
+//
+fn dot_attrs(arg: &str, attrs: String) -> Result<String, String> {
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for c in attrs.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if quoted => {}
+            '[' | '{' => depth += 1,
+            ']' | '}' if depth > 0 => depth -= 1,
+            ']' | '}' | ';' | '\n' => {
+                return Err(format!("invalid attributes for {}: {}", arg, attrs));
             }
+            _ => {}
         }
+    }
 
-        writeln!(w, "}}")?;
-
-        Ok(())
+    if depth != 0 || quoted {
+        return Err(format!("unbalanced attributes for {}: {}", arg, attrs));
     }
 
-    fn write_bus<T: Write>(
-        &self,
-        w: &mut T,
-        write_state: &mut MachineWriteState,
-        parent_dev: &PciDevice,
-        domain: u16,
-        bus: u8,
-    ) -> Result<(), Error> {
-        writeln!(w)?;
-        writeln!(w, "\t# domain {:04x} bus {:02x}", domain, bus)?;
+    Ok(attrs)
+}
 
-        let bus_devices = self.bus_devices(domain, bus);
+impl Options {
+    fn parse<T: Iterator<Item = String>>(mut args: T) -> Result<Options, String> {
+        let mut options = Options::default();
 
         //
-        // In the ORACLE SERVER E4-2c, a DMI System Slot handle refers to the PCI bus
-        // address of the Root Complex's Root Port or the upstream bridge's Downstream
-        // Port, and not to the PCI bus address of the downstream bridge's Upstream
-        // Port or the downstream Endpoint.  For this reason, we re-query for the
-        // parent's PCI bus address if we don't find a System Slot handle for the
-        // downstream address.
+        // See https://no-color.org/.
         //
-        let slot_name = self
-            .dmi_slots
-            .get(&PciAddr::new(domain, bus, 0, 0))
-            .or_else(|| self.dmi_slots.get(&parent_dev.addr()));
+        options.graph_options.no_color = ["NO_COLOR", "PCIGRAPH_NO_COLOR"]
+            .iter()
+            .any(|var| env::var_os(var).is_some_and(|value| !value.is_empty()));
 
-        writeln!(w)?;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-o" | "--output" => {
+                    options.output = Some(option_value(&arg, &mut args)?);
+                }
+                "--format" => {
+                    options.format = Format::parse(&option_value(&arg, &mut args)?)?;
+                }
+                "--from-sysfs" => {
+                    options.from_sysfs = true;
+                }
+                "--lspci" => {
+                    options.lspci = Some(option_value(&arg, &mut args)?);
+                }
+                "--pci-ids" => {
+                    options.pci_ids = Some(option_value(&arg, &mut args)?);
+                }
+                "--names" => {
+                    options.names = Some(option_value(&arg, &mut args)?);
+                }
+                "--host-marker" => {
+                    let marker = option_value(&arg, &mut args)?;
 
-        let intermediate = if slot_name.is_some() {
-            format!("{}_{:02x}", parent_dev.addr(), bus)
-        } else {
-            format!("{}", parent_dev.addr())
-        };
+                    if !marker.contains("{host}") {
+                        return Err(format!("host marker {} lacks {{host}}", marker));
+                    }
 
-        if let Some(slot_name) = slot_name {
-            let parent_lnk_cap = parent_dev.lnk_cap().unwrap();
-
-            writeln!(
-                w,
-                "\t\"{}\" -- \"{}\" [ label=\"{}\" ];",
-                parent_dev.addr(),
-                intermediate,
-                parent_lnk_cap,
-            )?;
-
-            writeln!(
-                w,
-                "\t\"{}\" [ label=\"{}\" shape=rectangle ];",
-                intermediate, slot_name
-            )?;
+                    options.host_marker = Some(marker);
+                }
+                "--check" => {
+                    options.check = true;
+                }
+                "--explain-clusters" => {
+                    options.explain_clusters = true;
+                }
+                "--summary" => {
+                    options.summary = true;
+                }
+                "-v" | "--verbose" => {
+                    options.verbose = true;
+                }
+                "--strict" => {
+                    options.strict = true;
+                }
+                "--bridge-paths" => {
+                    options.bridge_paths = true;
+                }
+                "--anonymize" => {
+                    options.anonymize = true;
+                }
+                "--anonymize-addresses" => {
+                    options.anonymize = true;
+                    options.anonymize_addresses = true;
+                }
+                "--scale-edges" => {
+                    options.graph_options.scale_edges = true;
+                }
+                "--tooltips" => {
+                    options.graph_options.tooltips = true;
+                }
+                "--show-bandwidth" => {
+                    options.graph_options.show_bandwidth = true;
+                }
+                "--show-rev" => {
+                    options.graph_options.show_rev = true;
+                }
+                "--show-flr" => {
+                    options.graph_options.show_flr = true;
+                }
+                "--show-numa" => {
+                    options.graph_options.show_numa = true;
+                }
+                "--show-aspm" => {
+                    options.graph_options.show_aspm = true;
+                }
+                "--min-speed" => {
+                    let min_speed = option_value(&arg, &mut args)?;
+
+                    options.graph_options.min_speed = Some(
+                        min_speed
+                            .parse::<f32>()
+                            .map_err(|_| format!("invalid speed {}", min_speed))?,
+                    );
+                }
+                "--dpi" => {
+                    let dpi = option_value(&arg, &mut args)?;
+
+                    options.graph_options.dpi = Some(
+                        dpi.parse::<u32>()
+                            .ok()
+                            .filter(|dpi| *dpi > 0)
+                            .ok_or_else(|| format!("invalid dpi {}", dpi))?,
+                    );
+                }
+                "--fontsize" => {
+                    let fontsize = option_value(&arg, &mut args)?;
+
+                    options.graph_options.fontsize = Some(
+                        fontsize
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|fontsize| fontsize.is_finite() && *fontsize > 0.0)
+                            .ok_or_else(|| format!("invalid font size {}", fontsize))?,
+                    );
+                }
+                "--hide-pch" => {
+                    options.graph_options.hide_pch = true;
+                }
+                "--graph-attrs" => {
+                    options.graph_options.graph_attrs =
+                        Some(dot_attrs(&arg, option_value(&arg, &mut args)?)?);
+                }
+                "--node-attrs" => {
+                    options.graph_options.node_attrs =
+                        Some(dot_attrs(&arg, option_value(&arg, &mut args)?)?);
+                }
+                "--include" => {
+                    options
+                        .graph_options
+                        .include
+                        .push(option_value(&arg, &mut args)?);
+                }
+                "--root" => {
+                    options.graph_options.root = Some(
+                        option_value(&arg, &mut args)?
+                            .parse()
+                            .map_err(|err| format!("{}", err))?,
+                    );
+                }
+                "--exclude" => {
+                    options
+                        .graph_options
+                        .exclude
+                        .push(option_value(&arg, &mut args)?);
+                }
+                "--order-by-port" => {
+                    options.graph_options.order_by_port = true;
+                }
+                "--record-functions" => {
+                    options.graph_options.record_functions = true;
+                }
+                "--split-by-numa" => {
+                    options.split_by_numa = true;
+                }
+                "--collapse-identical" => {
+                    options.graph_options.collapse_identical = true;
+                }
+                "--no-clusters" => {
+                    options.graph_options.no_clusters = true;
+                }
+                "--no-color" => {
+                    options.graph_options.no_color = true;
+                }
+                "--directed" => {
+                    options.graph_options.directed = true;
+                }
+                "--rankdir" => {
+                    options.graph_options.rankdir = match option_value(&arg, &mut args)?.as_str() {
+                        "LR" => RankDir::LeftRight,
+                        "TB" => RankDir::TopBottom,
+                        "RL" => RankDir::RightLeft,
+                        "BT" => RankDir::BottomTop,
+                        rankdir => return Err(format!("unknown rankdir {}", rankdir)),
+                    };
+                }
+                "--show-orphans" => {
+                    options.graph_options.show_orphans = true;
+                }
+                "--group-label" => {
+                    options.graph_options.group_label = Some(option_value(&arg, &mut args)?);
+                }
+                "--switch-label" => {
+                    options.graph_options.labels.pcie_switch = option_value(&arg, &mut args)?;
+                }
+                "--bridge-label" => {
+                    options.graph_options.labels.pci_bridge = option_value(&arg, &mut args)?;
+                }
+                "--root-port-label" => {
+                    options.graph_options.labels.root_port = option_value(&arg, &mut args)?;
+                }
+                "--color-by-class" => {
+                    options.graph_options.color_by_class = true;
+                }
+                "--legend" => {
+                    options.graph_options.legend = true;
+                }
+                "--group-by" => {
+                    options.graph_options.group_by = match option_value(&arg, &mut args)?.as_str() {
+                        "topology" => GroupBy::Topology,
+                        "iommu" => GroupBy::Iommu,
+                        group_by => return Err(format!("unknown grouping {}", group_by)),
+                    };
+                }
+                _ if arg.starts_with('-') => {
+                    return Err(format!("unknown option {}", arg));
+                }
+                _ => {
+                    options.inputs.push(arg);
+                }
+            }
         }
 
-        if let Some(first_dev_addr) = bus_devices.first() {
-            let first_dev = self.pci_devices.get(first_dev_addr).unwrap();
-
-            let label =
-                if self.pci_device_unique_id(parent_dev) != self.pci_device_unique_id(first_dev) {
-                    first_dev
-                        .lnk_sta()
-                        .map(|lnk_sta| format!(" [ label=\"{}\" ]", lnk_sta))
-                        .unwrap()
-                } else {
-                    "".to_string()
-                };
-
-            // TODO: lhead into the cluster in case of multi-function device
-            writeln!(
-                w,
-                "\t\"{}\" -- \"{}\"{};",
-                intermediate, first_dev_addr, label
-            )?;
-        } else {
-            let parent_lnk_cap = parent_dev.lnk_cap().unwrap();
-
-            writeln!(
-                w,
-                "\t\"{}\" -- \"bus {:04x}:{:02x}\"{};",
-                intermediate,
-                domain,
-                bus,
-                if slot_name.is_none() {
-                    format!(" [ label=\"{}\" ]", parent_lnk_cap)
-                } else {
-                    "".to_string()
-                }
-            )?;
-
-            writeln!(w)?;
-
-            writeln!(
-                w,
-                "\t\"bus {:04x}:{:02x}\" [ shape=rectangle ];",
-                domain, bus
-            )?;
+        if options.from_sysfs && !options.inputs.is_empty() {
+            return Err("--from-sysfs can't be combined with input files".to_string());
         }
 
-        let upstream_ports = bus_devices
-            .iter()
-            .filter(|dev_addr| self.pci_devices.get(dev_addr).unwrap().is_upstream_port())
-            .copied()
-            .collect::<Vec<_>>();
-
-        let pci_bridges = bus_devices
-            .iter()
-            .filter(|dev_addr| self.pci_devices.get(dev_addr).unwrap().is_pci_bridge())
-            .copied()
-            .collect::<Vec<_>>();
-
-        let endpoints = bus_devices
-            .iter()
-            .filter(|dev_addr| self.pci_devices.get(dev_addr).unwrap().is_endpoint())
-            .copied()
-            .collect::<Vec<_>>();
+        Ok(options)
+    }
+}
 
-        if !upstream_ports.is_empty() {
-            for dev_addr in upstream_ports {
-                let dev = self.pci_devices.get(&dev_addr).unwrap();
+const USAGE: &str = "\
+usage: pcigraph [OPTIONS] [INPUT...]
+
+options:
+    -o, --output FILE             write output to FILE instead of stdout
+    --from-sysfs                  read devices from /sys/bus/pci instead of lspci
+                                  output
+    --lspci PATH                  lspci binary to run when stdin is a terminal
+                                  (default: lspci)
+    --format FORMAT               output format: dot (default), csv, graphml,
+                                  json, mermaid, plantuml, png, svg or tree
+    --pci-ids FILE                read device names from FILE
+    --names FILE                  read vendor:device=name overrides from FILE
+    --host-marker TEMPLATE        split inputs into hosts at lines of the form
+                                  TEMPLATE, with {host} being the host name
+    --check                       check the graph for inconsistencies instead
+                                  of writing it
+    --explain-clusters            list the clusters the graph would have, and
+                                  why, instead of writing it
+    --summary                     write a one-line summary of each input instead
+                                  of the graph
+    -v, --verbose                 print a summary of what was parsed
+    --strict                      fail on duplicate device sections instead
+                                  of warning about them
+    --bridge-paths                place devices behind bridges by the paths
+                                  that `lspci -PP` prints
+    --anonymize                   replace serial numbers by pseudonyms
+    --anonymize-addresses         also renumber PCI domains and buses
+    --show-bandwidth              show usable bandwidth on links
+    --show-rev                    show device revisions
+    --show-flr                    label endpoints that don't support Function
+                                  Level Reset
+    --show-numa                   show the NUMA node of root ports and devices
+    --show-aspm                   show enabled and supported ASPM states on links
+    --scale-edges                 draw wider links with thicker lines
+                                  (dot output only)
+    --tooltips                    give devices tooltips with more details, for
+                                  SVG output (dot output only)
+    --group-by topology|iommu     how to cluster devices (default: topology)
+    --group-label TEMPLATE        label root port clusters with TEMPLATE, with
+                                  {numa}, {socket} and {bus} substituted
+    --switch-label TEXT           label PCIe switch clusters with TEXT
+                                  (default: PCIe switch)
+    --bridge-label TEXT           label PCI bridge clusters with TEXT
+                                  (default: PCI bridge)
+    --root-port-label TEXT        label root ports with TEXT
+                                  (default: Root port)
+    --no-clusters                 don't group devices into clusters
+    --min-speed GT/S              leave out links slower than GT/S, and what is
+                                  behind them
+    --hide-pch                    leave out the PCH and what is behind it
+    --include PREFIX              only show devices whose addresses start with
+                                  PREFIX, and what is behind them (repeatable)
+    --exclude PREFIX              leave out devices whose addresses start with
+                                  PREFIX, and what is behind them (repeatable)
+    --root ADDR                   only show the root or switch port ADDR, and
+                                  what is behind it
+    --collapse-identical          draw identical sibling endpoints as one node
+    --order-by-port               order switch downstream ports by their
+                                  advertised port number
+    --record-functions            draw multi-function devices as a single node
+    --split-by-numa               write a graph per NUMA node, to
+                                  OUTPUT-numaN.dot (default: pci-numaN.dot)
+    --no-color                    don't color links and devices with problems
+                                  (also set by NO_COLOR or PCIGRAPH_NO_COLOR)
+    --directed                    emit a directed graph
+    --rankdir LR|TB|RL|BT         graph layout direction (default: LR)
+    --show-orphans                show devices not reachable from any root port
+    --color-by-class              fill device nodes by device class
+    --legend                      draw a legend explaining shapes and colors
+    --dpi N                       render bitmap output at N dots per inch
+                                  (dot output only)
+    --fontsize N                  default node label font size, in points
+                                  (dot output only)
+    --graph-attrs ATTRS           extra graphviz graph attributes, e.g.
+                                  'splines=ortho bgcolor=white'
+    --node-attrs ATTRS            extra graphviz attributes for all nodes
+";
+
+const DEFAULT_PCI_IDS_PATHS: [&str; 2] = ["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids"];
+
+const SYSFS_PCI_PATH: &str = "/sys/bus/pci";
+
+fn fail(message: &str) -> ! {
+    eprintln!("pcigraph: {}", message);
+    exit(1);
+}
 
-                let downstream_port_bus = dev.secondary_bus().unwrap();
+//
+// Runs lspci with the flags that pcigraph expects, for when there is no
+// input to read.
+//
+fn run_lspci(lspci: &str) -> Vec<u8> {
+    let output = Command::new(lspci)
+        .args(["-nnvv"])
+        .output()
+        .unwrap_or_else(|err| {
+            if err.kind() == ErrorKind::NotFound {
+                fail(&format!(
+                    "{} not found; install pciutils, use --lspci to point at lspci, \
+                     or pipe lspci -nnvv output into pcigraph",
+                    lspci
+                ))
+            } else {
+                fail(&format!("can't run {}: {}", lspci, err))
+            }
+        });
 
-                let downstream_ports = self.bus_devices(dev_addr.domain(), downstream_port_bus);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        fail(&format!(
+            "{} failed ({}){}",
+            lspci,
+            output.status,
+            stderr
+                .lines()
+                .next()
+                .map_or("".to_string(), |line| format!(": {}", line))
+        ));
+    }
 
-                let unique_id = self.pci_device_unique_id(dev);
+    output.stdout
+}
 
-                writeln!(w)?;
+//
+// Has graphviz render dot output into the given output format.
+//
+fn run_dot(dot_output: &[u8], graphviz_format: &str) -> Vec<u8> {
+    let mut child = Command::new("dot")
+        .arg(format!("-T{}", graphviz_format))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|err| {
+            if err.kind() == ErrorKind::NotFound {
+                fail(&format!(
+                    "dot not found; install graphviz to render {} output, \
+                     or use --format dot and render it yourself",
+                    graphviz_format
+                ))
+            } else {
+                fail(&format!("can't run dot: {}", err))
+            }
+        });
 
-                writeln!(
-                    w,
-                    "\tsubgraph cluster{} {{",
-                    write_state.get_cluster_index(&unique_id)
-                )?;
+    //
+    // Feed dot from a separate thread, so that it can't block on writing
+    // its output while we block on writing its input.
+    //
+    let mut stdin = child.stdin.take().unwrap();
+    let dot_output = dot_output.to_vec();
+
+    let writer = thread::spawn(move || stdin.write_all(&dot_output));
+
+    let output = child
+        .wait_with_output()
+        .unwrap_or_else(|err| fail(&format!("can't run dot: {}", err)));
+
+    //
+    // If dot exits without reading all of its input, writing it fails,
+    // but the exit status below says more about why.
+    //
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        fail(&format!(
+            "dot failed ({}){}",
+            output.status,
+            stderr
+                .lines()
+                .next()
+                .map_or("".to_string(), |line| format!(": {}", line))
+        ));
+    }
 
-                writeln!(w, "\t\tlabel=\"PCIe switch\";")?;
+    output.stdout
+}
 
-                writeln!(w, "\t\t\"{}\";", dev_addr)?;
+fn warn_unreachable(machine: &Machine, host: Option<&str>) {
+    let orphans = machine.unreachable_devices();
+
+    if !orphans.is_empty() {
+        eprintln!(
+            "pcigraph: warning: {}not reachable from any root port: {}",
+            host.map_or("".to_string(), |host| format!("{}: ", host)),
+            orphans
+                .iter()
+                .map(|dev_addr| dev_addr.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
 
-                for downstream_port_addr in &downstream_ports {
-                    writeln!(w, "\t\t\"{}\";", downstream_port_addr)?;
-                }
+fn warn_missing_lnk_cap(machine: &Machine, host: Option<&str>) {
+    let ports = machine.ports_without_lnk_cap();
+
+    if !ports.is_empty() {
+        eprintln!(
+            "pcigraph: warning: {}ports without LnkCap, their links are unlabeled: {}",
+            host.map_or("".to_string(), |host| format!("{}: ", host)),
+            ports
+                .iter()
+                .map(|dev_addr| dev_addr.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
 
-                writeln!(w, "\t}}")?;
+fn warn_duplicates(machine: &Machine, host: Option<&str>) {
+    let duplicates = machine.duplicate_devices();
+
+    if !duplicates.is_empty() {
+        eprintln!(
+            "pcigraph: warning: {}duplicate device sections, input may be doubled: {}",
+            host.map_or("".to_string(), |host| format!("{}: ", host)),
+            duplicates
+                .iter()
+                .map(|dev_addr| dev_addr.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
 
-                writeln!(w)?;
+//
+// Writes a graph for each NUMA node to BASE-numaN.dot, and one for the root
+// ports that aren't attached to any NUMA node to BASE-no-numa.dot.
+//
+fn write_split_by_numa(machine: &Machine, base: &str, graph_options: &mut GraphOptions) {
+    for numa_node in machine.numa_nodes() {
+        let path = match numa_node {
+            Some(numa_node) => format!("{}-numa{}.dot", base, numa_node),
+            None => format!("{}-no-numa.dot", base),
+        };
 
-                writeln!(
-                    w,
-                    "\t# domain {:04x} bus {:02x} is a switch internal bus",
-                    domain, downstream_port_bus
-                )?;
+        let file = File::create(&path)
+            .unwrap_or_else(|err| fail(&format!("can't create {}: {}", path, err)));
 
-                for downstream_port_addr in &downstream_ports {
-                    writeln!(w)?;
-                    writeln!(w, "\t\"{}\" -- \"{}\";", dev_addr, downstream_port_addr)?;
-                }
+        let mut output = BufWriter::new(file);
 
-                for downstream_port_addr in &downstream_ports {
-                    let downstream_port = self.pci_devices.get(downstream_port_addr).unwrap();
+        graph_options.numa_node = Some(numa_node);
 
-                    let secondary_bus = downstream_port.secondary_bus().unwrap();
+        machine
+            .write_graph(&mut output, graph_options)
+            .and_then(|_| output.flush())
+            .unwrap_or_else(|err| fail(&format!("error writing {}: {}", path, err)));
+    }
+}
 
-                    self.write_bus(
-                        w,
-                        write_state,
-                        downstream_port,
-                        downstream_port_addr.domain(),
-                        secondary_bus,
-                    )?;
-                }
-            }
-        } else if !pci_bridges.is_empty() {
-            for dev_addr in pci_bridges {
-                let dev = self.pci_devices.get(&dev_addr).unwrap();
+fn print_summary(machine: &Machine, host: Option<&str>) {
+    let host = host.map_or("".to_string(), |host| format!("{}: ", host));
+
+    let unclassified = machine
+        .unclassified_devices()
+        .map(|dev| dev.addr().to_string())
+        .collect::<Vec<_>>();
+
+    eprintln!(
+        "pcigraph: {}{} sections ({} unknown), {} devices, {} DMI slots, {} root ports, {} \
+         unclassified devices",
+        host,
+        machine.sections_seen(),
+        machine.unknown_sections(),
+        machine.devices_where(|_| true).count(),
+        machine.slots().count(),
+        machine.root_ports().count(),
+        unclassified.len()
+    );
+
+    if !unclassified.is_empty() {
+        eprintln!(
+            "pcigraph: {}unclassified: {}",
+            host,
+            unclassified.join(", ")
+        );
+    }
+}
 
-                let unique_id = self.pci_device_unique_id(dev);
+fn main() {
+    let mut options = Options::parse(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("pcigraph: {}", err);
+        eprint!("{}", USAGE);
+        exit(1);
+    });
+
+    let pci_ids = match &options.pci_ids {
+        Some(path) => Some(
+            PciIds::load(path).unwrap_or_else(|err| fail(&format!("can't read {}: {}", path, err))),
+        ),
+        None => DEFAULT_PCI_IDS_PATHS
+            .iter()
+            .find_map(|path| PciIds::load(path).ok()),
+    };
 
-                writeln!(w)?;
+    let device_names = options.names.as_ref().map(|path| {
+        DeviceNames::load(path).unwrap_or_else(|err| fail(&format!("can't read {}: {}", path, err)))
+    });
 
-                writeln!(
-                    w,
-                    "\tsubgraph cluster{} {{",
-                    write_state.get_cluster_index(&unique_id)
-                )?;
+    let new_machine = || {
+        let mut machine = Machine::default();
 
-                writeln!(w, "\t\tlabel=\"PCI bridge\";")?;
+        machine.set_strict(options.strict);
+        machine.set_bridge_paths(options.bridge_paths);
 
-                writeln!(w, "\t\t\"{}\";", dev_addr)?;
+        if let Some(device_names) = &device_names {
+            machine.set_device_names(device_names.clone());
+        }
 
-                writeln!(w, "\t}}")?;
+        if let Some(pci_ids) = &pci_ids {
+            machine.set_pci_ids(pci_ids.clone());
+        }
 
-                let secondary_bus = dev.secondary_bus().unwrap();
+        machine
+    };
+
+    //
+    // With --host-marker, each input is split into a machine per host, which
+    // is named after the host rather than after the input.
+    //
+    let parse_input = |mut src: &mut dyn Read, name: &str, description: &str| {
+        let fail_parse = |err| -> ! { fail(&format!("can't parse {}: {}", description, err)) };
+
+        match &options.host_marker {
+            Some(marker) => new_machine()
+                .parse_hosts(&mut src, marker)
+                .unwrap_or_else(|err| fail_parse(err))
+                .into_iter()
+                .collect::<Vec<_>>(),
+            None => {
+                let mut machine = new_machine();
+
+                machine
+                    .parse(&mut src)
+                    .unwrap_or_else(|err| fail_parse(err));
+
+                vec![(name.to_string(), machine)]
+            }
+        }
+    };
+
+    let (names, mut machines): (Vec<String>, Vec<Machine>) = if options.from_sysfs {
+        let mut machine = new_machine();
+
+        machine
+            .parse_sysfs(SYSFS_PCI_PATH)
+            .unwrap_or_else(|err| fail(&format!("can't read {}: {}", SYSFS_PCI_PATH, err)));
+
+        (vec![SYSFS_PCI_PATH.to_string()], vec![machine])
+    } else if options.inputs.is_empty() && stdin().is_terminal() {
+        let lspci = options.lspci.as_deref().unwrap_or("lspci");
+
+        parse_input(
+            &mut run_lspci(lspci).as_slice(),
+            lspci,
+            &format!("{} output", lspci),
+        )
+        .into_iter()
+        .unzip()
+    } else if options.inputs.is_empty() {
+        parse_input(&mut stdin(), "-", "input").into_iter().unzip()
+    } else {
+        options
+            .inputs
+            .iter()
+            .flat_map(|path| {
+                let mut file = File::open(path)
+                    .unwrap_or_else(|err| fail(&format!("can't open {}: {}", path, err)));
 
-                writeln!(w)?;
+                parse_input(&mut file, path, path)
+            })
+            .unzip()
+    };
 
-                writeln!(w, "\t# domain {:04x} bus {:02x}", domain, secondary_bus)?;
+    if machines.is_empty() {
+        fail("no host markers found in the input");
+    }
 
-                let secondary_devices = self.bus_devices(dev_addr.domain(), secondary_bus);
+    if machines.len() > 1
+        && !matches!(
+            options.format,
+            Format::Dot
+                | Format::GraphMl
+                | Format::Mermaid
+                | Format::PlantUml
+                | Format::Png
+                | Format::Svg
+        )
+    {
+        fail(
+            "multiple inputs or hosts are only supported for dot, graphml, mermaid, plantuml, png and svg \
+             output",
+        );
+    }
 
-                for secondary_device in &secondary_devices {
-                    writeln!(w)?;
+    if options.split_by_numa && (machines.len() > 1 || !matches!(options.format, Format::Dot)) {
+        fail("--split-by-numa is only supported for dot output from a single input and host");
+    }
 
-                    writeln!(w, "\t\"{}\" -- \"{}\";", dev_addr, secondary_device)?;
+    if options.explain_clusters && machines.len() > 1 {
+        fail("--explain-clusters is only supported for a single input and host");
+    }
 
-                    writeln!(w)?;
+    for machine in &mut machines {
+        if options.anonymize {
+            machine.anonymize_serial_numbers();
+        }
 
-                    let dev = self.pci_devices.get(secondary_device).unwrap();
+        if options.anonymize_addresses {
+            machine.anonymize_addresses();
+        }
+    }
 
-                    writeln!(
-                        w,
-                        "\t\"{}\" [ label=\"{}\\n{}\" ];",
-                        secondary_device,
-                        dev.short_name().unwrap_or(&format!(
-                            "unknown {:04x}:{:04x}",
-                            dev.vendor_id(),
-                            dev.device_id()
-                        )),
-                        secondary_device
-                    )?;
+    if let Some(root) = options.graph_options.root {
+        for (name, machine) in names.iter().zip(&machines) {
+            if machine.subtree_root(root).is_none() {
+                match machines.len() {
+                    1 => fail(&format!("{} is not a PCIe port with a secondary bus", root)),
+                    _ => fail(&format!(
+                        "{}: {} is not a PCIe port with a secondary bus",
+                        name, root
+                    )),
                 }
             }
-        } else if let Some(first_dev_addr) = endpoints.first() {
-            let first_dev = self.pci_devices.get(first_dev_addr).unwrap();
-
-            writeln!(w)?;
-
-            writeln!(
-                w,
-                "\t\"{}\" [ label=\"{}\\n{}\" ];",
-                first_dev_addr,
-                first_dev.short_name().unwrap_or(&format!(
-                    "unknown {:04x}:{:04x}",
-                    first_dev.vendor_id(),
-                    first_dev.device_id()
-                )),
-                first_dev_addr
-            )?;
+        }
+    }
 
-            if endpoints.len() > 1 {
-                let unique_id = self.pci_device_unique_id(first_dev);
+    if machines.len() == 1 {
+        warn_duplicates(&machines[0], None);
+    } else {
+        for (path, machine) in names.iter().zip(&machines) {
+            warn_duplicates(machine, Some(path));
+        }
+    }
 
-                writeln!(w)?;
+    if options.verbose {
+        if machines.len() == 1 {
+            print_summary(&machines[0], None);
+        } else {
+            for (path, machine) in names.iter().zip(&machines) {
+                print_summary(machine, Some(path));
+            }
+        }
+    }
 
-                writeln!(
-                    w,
-                    "\tsubgraph cluster{} {{",
-                    write_state.get_cluster_index(&unique_id)
-                )?;
+    if options.check {
+        let mut ok = true;
 
-                for dev_addr in &endpoints {
-                    writeln!(w, "\t\t\"{}\";", dev_addr)?;
+        for (index, machine) in machines.iter().enumerate() {
+            for problem in machine.check_graph(&options.graph_options) {
+                match names.get(index) {
+                    Some(path) if machines.len() > 1 => {
+                        eprintln!("pcigraph: {}: {}", path, problem)
+                    }
+                    _ => eprintln!("pcigraph: {}", problem),
                 }
 
-                writeln!(w, "\t}}")?;
-
-                for a_b in endpoints.windows(2) {
-                    writeln!(w)?;
-
-                    writeln!(w, "\t\"{}\" -- \"{}\";", a_b[0], a_b[1])?;
-
-                    let dev = self.pci_devices.get(&a_b[1]).unwrap();
-
-                    writeln!(
-                        w,
-                        "\t\"{}\" [ label=\"{}\\n{}\" ];",
-                        a_b[1],
-                        dev.short_name().unwrap_or(&format!(
-                            "unknown {:04x}:{:04x}",
-                            dev.vendor_id(),
-                            dev.device_id()
-                        )),
-                        a_b[1]
-                    )?;
-                }
+                ok = false;
             }
         }
 
-        Ok(())
+        exit(if ok { 0 } else { 1 });
     }
 
-    fn pci_device_unique_id(&self, dev: &PciDevice) -> String {
-        if let Some(serial_number) = dev.serial_number() {
-            if dev.is_upstream_port() {
-                let downstream_port_bus = dev.secondary_bus().unwrap();
-
-                let downstream_ports = self.bus_devices(dev.addr().domain(), downstream_port_bus);
-
-                for downstream_port_addr in &downstream_ports {
-                    let downstream_port = self.pci_devices.get(downstream_port_addr).unwrap();
-
-                    if let Some(downstream_serial_number) = downstream_port.serial_number() {
-                        //
-                        // If a downstream port for this upstream port has a different
-                        // Device Serial Number than the upstream port does, then don't
-                        // trust the Device Serial Number for the upstream port.
-                        //
-                        if serial_number != downstream_serial_number {
-                            return format!("{}", dev.addr());
-                        }
-                    }
-                }
+    if matches!(
+        options.format,
+        Format::Dot
+            | Format::GraphMl
+            | Format::Mermaid
+            | Format::PlantUml
+            | Format::Png
+            | Format::Svg
+    ) && !options.graph_options.show_orphans
+    {
+        if machines.len() == 1 {
+            warn_unreachable(&machines[0], None);
+        } else {
+            for (path, machine) in names.iter().zip(&machines) {
+                warn_unreachable(machine, Some(path));
             }
+        }
+    }
 
-            return format!("{:016x}", serial_number);
+    //
+    // --summary and --explain-clusters don't draw any links, so there are
+    // no link labels to be missing.
+    //
+    if matches!(
+        options.format,
+        Format::Dot
+            | Format::GraphMl
+            | Format::Mermaid
+            | Format::PlantUml
+            | Format::Png
+            | Format::Svg
+    ) && !options.summary
+        && !options.explain_clusters
+    {
+        if machines.len() == 1 {
+            warn_missing_lnk_cap(&machines[0], None);
+        } else {
+            for (path, machine) in names.iter().zip(&machines) {
+                warn_missing_lnk_cap(machine, Some(path));
+            }
         }
+    }
+
+    if options.split_by_numa {
+        let base = options
+            .output
+            .as_deref()
+            .map_or("pci", |path| path.strip_suffix(".dot").unwrap_or(path));
 
-        format!("{}", dev.addr())
+        write_split_by_numa(&machines[0], base, &mut options.graph_options);
+
+        return;
     }
-}
 
-fn main() {
-    let mut machine = Machine::default();
+    let mut output: Box<dyn Write> = match &options.output {
+        Some(path) => {
+            let file = File::create(path)
+                .unwrap_or_else(|err| fail(&format!("can't create {}: {}", path, err)));
+
+            Box::new(BufWriter::new(file))
+        }
+        None => Box::new(stdout()),
+    };
+
+    //
+    // For the image formats, the dot output is collected and then piped
+    // through graphviz.
+    //
+    let graphviz_format = options
+        .format
+        .graphviz_format()
+        .filter(|_| !options.explain_clusters && !options.summary);
+
+    let mut dot_output = Vec::new();
+
+    let mut graph_output: &mut dyn Write = if graphviz_format.is_some() {
+        &mut dot_output
+    } else {
+        &mut output
+    };
+
+    let result = if options.summary {
+        if machines.len() == 1 {
+            machines[0].write_summary(&mut graph_output)
+        } else {
+            names.iter().zip(&machines).try_for_each(|(path, machine)| {
+                write!(graph_output, "{}: ", path)?;
+                machine.write_summary(&mut graph_output)
+            })
+        }
+    } else if options.explain_clusters {
+        machines[0].explain_clusters(&mut graph_output, &options.graph_options)
+    } else if machines.len() == 1 {
+        let machine = &machines[0];
+
+        match options.format {
+            Format::Csv => machine.write_csv(&mut graph_output),
+            Format::Dot | Format::Png | Format::Svg => {
+                machine.write_graph(&mut graph_output, &options.graph_options)
+            }
+            Format::GraphMl => machine.write_graphml(&mut graph_output, &options.graph_options),
+            Format::Json => machine.write_json(&mut graph_output),
+            Format::Mermaid => machine.write_mermaid(&mut graph_output, &options.graph_options),
+            Format::PlantUml => machine.write_plantuml(&mut graph_output, &options.graph_options),
+            Format::Tree => machine.write_tree(&mut graph_output),
+        }
+    } else {
+        let hosts = names
+            .iter()
+            .map(|name| name.as_str())
+            .zip(&machines)
+            .collect::<Vec<_>>();
+
+        match options.format {
+            Format::Dot | Format::Png | Format::Svg => {
+                Machine::write_hosts_graph(&mut graph_output, &hosts, &options.graph_options)
+            }
+            Format::GraphMl => {
+                Machine::write_hosts_graphml(&mut graph_output, &hosts, &options.graph_options)
+            }
+            Format::Mermaid => {
+                Machine::write_hosts_mermaid(&mut graph_output, &hosts, &options.graph_options)
+            }
+            Format::PlantUml => {
+                Machine::write_hosts_plantuml(&mut graph_output, &hosts, &options.graph_options)
+            }
+            _ => unreachable!(),
+        }
+    };
 
-    machine.parse(&mut stdin());
+    let result = result.and_then(|_| match graphviz_format {
+        Some(graphviz_format) => output.write_all(&run_dot(&dot_output, graphviz_format)),
+        None => Ok(()),
+    });
 
-    machine.write_graph(&mut stdout()).unwrap();
+    result
+        .and_then(|_| output.flush())
+        .unwrap_or_else(|err| fail(&format!("error writing output: {}", err)));
 }
@@ -0,0 +1,148 @@
+use std::{
+    collections::HashSet,
+    io::{Error, Write},
+};
+
+use crate::{
+    GraphOptions,
+    graph_writer::{Attrs, GraphWriter},
+};
+
+pub struct MermaidWriter<'a, T: Write> {
+    w: &'a mut T,
+    options: &'a GraphOptions,
+    nodes: HashSet<String>,
+    edge_count: usize,
+}
+
+impl<'a, T: Write> MermaidWriter<'a, T> {
+    pub fn new(w: &'a mut T, options: &'a GraphOptions) -> MermaidWriter<'a, T> {
+        MermaidWriter {
+            w,
+            options,
+            nodes: HashSet::new(),
+            edge_count: 0,
+        }
+    }
+
+    //
+    // Graphviz implicitly creates nodes that are referenced before (or
+    // without) being declared, labeling them with their identifier.  Mermaid
+    // would label such nodes with the mangled identifier instead, so declare
+    // them with their original identifier as label on first use.
+    //
+    fn node_ref(&mut self, id: &str) -> String {
+        if self.nodes.insert(id.to_string()) {
+            format!("{}([\"{}\"])", node_id(id), escape(id))
+        } else {
+            node_id(id)
+        }
+    }
+}
+
+//
+// Mermaid node identifiers can't contain most punctuation.
+//
+fn node_id(id: &str) -> String {
+    let mangled = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+
+    format!("n{}", mangled)
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('\n', "<br>")
+}
+
+impl<T: Write> GraphWriter for MermaidWriter<'_, T> {
+    fn begin_graph(&mut self) -> Result<(), Error> {
+        writeln!(self.w, "graph {}", self.options.rankdir.as_str())
+    }
+
+    fn end_graph(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn blank(&mut self) -> Result<(), Error> {
+        writeln!(self.w)
+    }
+
+    fn section(&mut self, title: &str) -> Result<(), Error> {
+        writeln!(self.w)?;
+        writeln!(
+            self.w,
+            "    %% ####################################################################"
+        )?;
+        writeln!(self.w, "    %% {}", title)
+    }
+
+    fn comment(&mut self, text: &str) -> Result<(), Error> {
+        writeln!(self.w, "    %% {}", text)
+    }
+
+    fn node(&mut self, id: &str, attrs: &Attrs) -> Result<(), Error> {
+        self.nodes.insert(id.to_string());
+
+        let label = escape(attrs.label.as_deref().unwrap_or(id));
+
+        if attrs.shape == Some("rectangle") {
+            writeln!(self.w, "    {}[\"{}\"]", node_id(id), label)?;
+        } else if attrs.shape == Some("hexagon") {
+            writeln!(self.w, "    {}{{{{\"{}\"}}}}", node_id(id), label)?;
+        } else {
+            writeln!(self.w, "    {}([\"{}\"])", node_id(id), label)?;
+        }
+
+        if let Some(fillcolor) = attrs.fillcolor {
+            writeln!(self.w, "    style {} fill:{}", node_id(id), fillcolor)?;
+        }
+
+        Ok(())
+    }
+
+    fn edge(&mut self, from: &str, to: &str, attrs: &Attrs) -> Result<(), Error> {
+        let from = self.node_ref(from);
+        let to = self.node_ref(to);
+
+        let op = if self.options.directed { "-->" } else { "---" };
+
+        match &attrs.label {
+            Some(label) => writeln!(self.w, "    {} {}|\"{}\"| {}", from, op, escape(label), to)?,
+            None => writeln!(self.w, "    {} {} {}", from, op, to)?,
+        }
+
+        if let Some(color) = attrs.color {
+            writeln!(self.w, "    linkStyle {} stroke:{}", self.edge_count, color)?;
+        }
+
+        self.edge_count += 1;
+
+        Ok(())
+    }
+
+    fn begin_cluster(&mut self, index: u32, label: Option<&str>) -> Result<(), Error> {
+        writeln!(
+            self.w,
+            "    subgraph cluster{} [\"{}\"]",
+            index,
+            label.map_or(" ".to_string(), escape)
+        )
+    }
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error> {
+        let id = self.node_ref(id);
+
+        writeln!(self.w, "        {}", id)
+    }
+
+    fn end_cluster(&mut self) -> Result<(), Error> {
+        writeln!(self.w, "    end")
+    }
+}
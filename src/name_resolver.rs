@@ -0,0 +1,123 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::PciDevice;
+
+// (vendor ID, device ID, subsystem vendor ID, subsystem device ID)
+type SubsystemKey = (u16, u16, u16, u16);
+
+//
+// Names for specific subsystems (e.g. OEM-branded cards) take precedence
+// over the names for the underlying vendor/device ID.
+//
+static SUBSYSTEM_SHORT_NAMES: [(SubsystemKey, &str); 2] = [
+    ((0x144d, 0xa80a, 0x144d, 0xa801), "Samsung 980 PRO"),
+    ((0x8086, 0x1563, 0x8086, 0x0001), "Intel X550-T2"),
+];
+
+static SHORT_NAMES: [((u16, u16), &str); 46] = [
+    ((0x1000, 0x005d), "MegaRAID 3108"),
+    ((0x1000, 0x00b2), "switch mgmt"),
+    ((0x1000, 0x02b2), "placeholder"),
+    ((0x1000, 0xc010), "placeholder"),
+    ((0x1022, 0x1485), "AMD SPP"),
+    ((0x1022, 0x1486), "AMD PSPCPP"),
+    ((0x1022, 0x1487), "AMD HD Audio"),
+    ((0x1022, 0x148a), "dummy function"),
+    ((0x1022, 0x148c), "AMD XHCI"),
+    ((0x1022, 0x1498), "AMD PTDMA"),
+    ((0x1022, 0x149c), "AMD XHCI"),
+    ((0x1022, 0x7901), "AMD SATA"),
+    ((0x102b, 0x0522), "Matrox VGA"),
+    ((0x102b, 0x0534), "Matrox VGA"),
+    ((0x102b, 0x0536), "Matrox VGA"),
+    ((0x10de, 0x0e0f), "NVIDIA GK208 HDMP/DP Audio"),
+    ((0x10de, 0x128b), "NVIDIA GT 710"),
+    ((0x10de, 0x1af1), "A100 NVSwitch"),
+    ((0x10de, 0x20b0), "A100 SXM4 40GB"),
+    ((0x10de, 0x22a3), "H100 NVSwitch"),
+    ((0x10de, 0x2330), "H100 SXM5 80GB"),
+    ((0x10de, 0x2335), "H200 SXM5 141GB"),
+    ((0x10de, 0x2901), "B200 SXM6 192GB"),
+    ((0x10ec, 0x8125), "Realtek RTL8125 2.5GbE"),
+    ((0x1344, 0x51c3), "Micron NVMe"),
+    ((0x144d, 0xa808), "Samsung NVMe"),
+    ((0x144d, 0xa80a), "Samsung NVMe"),
+    ((0x144d, 0xa80c), "Samsung NVMe"),
+    ((0x144d, 0xa824), "Samsung NVMe"),
+    ((0x144d, 0xa825), "Samsung NVMe"),
+    ((0x14e4, 0x165f), "Broadcom BCM5720"),
+    ((0x15b3, 0x1019), "MT28800 ConnectX-5 Ex ETH"),
+    ((0x15b3, 0x101b), "MT28908 ConnectX-6 IB"),
+    ((0x15b3, 0x101d), "MT2892 ConnectX-6 Dx ETH"),
+    ((0x15b3, 0x101e), "ConnectX-7 IB VF"),
+    ((0x15b3, 0x1021), "MT2910 ConnectX-7 IB"),
+    ((0x15b3, 0xa2dc), "MT43244 BlueField-3"),
+    ((0x15b3, 0xc2d5), "MT43244 BlueField-3 mgmt"),
+    ((0x1912, 0x0014), "Renesas USB3"),
+    ((0x1a03, 0x2000), "ASPEED VGA"),
+    ((0x1a03, 0x2402), "ASPEED IPMI"),
+    ((0x1b4b, 0x2241), "Marvell NVMe"),
+    ((0x1b4b, 0x9485), "Marvell SAS/SATA"),
+    ((0x8086, 0x1563), "Intel X550"),
+    ((0x8086, 0x15f3), "Intel I225-V"),
+    ((0x8086, 0x2723), "Intel Wi-Fi 6 AX200"),
+];
+
+/// Maps vendor and device IDs to short device names.  The default resolver
+/// knows about the devices in pcigraph's built-in short name table, and more
+/// names can be added with `add_name`.
+#[derive(Clone, Debug)]
+pub struct NameResolver {
+    names: HashMap<(u16, u16), String>,
+    subsystem_names: HashMap<SubsystemKey, String>,
+    builtin_names: HashMap<(u16, u16), String>,
+}
+
+impl NameResolver {
+    pub(crate) fn builtin() -> &'static NameResolver {
+        static BUILTIN: OnceLock<NameResolver> = OnceLock::new();
+
+        BUILTIN.get_or_init(NameResolver::default)
+    }
+
+    /// Adds a name for the given vendor and device ID, which takes
+    /// precedence over the built-in names for it.
+    pub fn add_name<T: Into<String>>(&mut self, vendor_id: u16, device_id: u16, name: T) {
+        self.names.insert((vendor_id, device_id), name.into());
+    }
+
+    /// Returns the short name for `dev`, if there is one.
+    pub fn short_name(&self, dev: &PciDevice) -> Option<&str> {
+        let key = (dev.vendor_id(), dev.device_id());
+
+        if let Some(name) = self.names.get(&key) {
+            return Some(name);
+        }
+
+        if let Some((subsystem_vendor_id, subsystem_device_id)) = dev.subsystem_id()
+            && let Some(name) =
+                self.subsystem_names
+                    .get(&(key.0, key.1, subsystem_vendor_id, subsystem_device_id))
+        {
+            return Some(name);
+        }
+
+        self.builtin_names.get(&key).map(|name| name.as_str())
+    }
+}
+
+impl Default for NameResolver {
+    fn default() -> NameResolver {
+        NameResolver {
+            names: HashMap::new(),
+            subsystem_names: SUBSYSTEM_SHORT_NAMES
+                .iter()
+                .map(|(key, name)| (*key, name.to_string()))
+                .collect(),
+            builtin_names: SHORT_NAMES
+                .iter()
+                .map(|(key, name)| (*key, name.to_string()))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use crate::PciAddr;
+
+/// The error returned when `Machine::parse`, `Machine::parse_hosts` or
+/// `DeviceNames::parse` fails.
+#[derive(Debug)]
+pub enum ParseError {
+    /// Reading the input failed.
+    Io(std::io::Error),
+    /// A section looks like a PCI device, but can't be parsed as one.  The
+    /// section's first line is included.
+    MalformedSection(String),
+    /// A PCI device section lacks the `[vendor:device]` IDs that `lspci -nn`
+    /// prints.  The section's first line is included.
+    MissingIds(String),
+    /// A line in a device names file can't be parsed.  The line number and
+    /// the line itself are included.
+    MalformedLine(usize, String),
+    /// Two PCI device sections have the same address, which usually means
+    /// that the input was captured or concatenated twice.  Only returned in
+    /// strict mode.
+    DuplicateDevice(PciAddr),
+    /// Input to `Machine::parse_hosts` doesn't start with a host marker
+    /// line.  The first line of the input is included.
+    MissingHostMarker(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ParseError::Io(err) => write!(f, "I/O error: {}", err),
+            ParseError::MalformedSection(line) => {
+                write!(f, "malformed PCI device section: \"{}\"", line)
+            }
+            ParseError::MissingIds(line) => write!(
+                f,
+                "PCI device section without vendor and device IDs: \"{}\" \
+                 (was lspci run without -nn?)",
+                line
+            ),
+            ParseError::MalformedLine(number, line) => {
+                write!(f, "malformed line {}: \"{}\"", number, line)
+            }
+            ParseError::DuplicateDevice(addr) => {
+                write!(f, "duplicate PCI device section for {}", addr)
+            }
+            ParseError::MissingHostMarker(line) => {
+                write!(f, "input before the first host marker: \"{}\"", line)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(err) => Some(err),
+            ParseError::MalformedSection(_)
+            | ParseError::MissingIds(_)
+            | ParseError::MalformedLine(..)
+            | ParseError::DuplicateDevice(_)
+            | ParseError::MissingHostMarker(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ParseError {
+    fn from(err: std::io::Error) -> ParseError {
+        ParseError::Io(err)
+    }
+}
@@ -1,14 +1,19 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
+/// A PCI device address (domain, bus, device and function).
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct PciAddr {
-    pub domain: u16,
+    pub domain: u32,
     pub bus: u8,
     pub devfn: u8,
 }
 
 impl PciAddr {
-    pub fn new(domain: u16, bus: u8, device: u8, function: u8) -> PciAddr {
+    pub fn new(domain: u32, bus: u8, device: u8, function: u8) -> PciAddr {
         PciAddr {
             domain,
             bus,
@@ -16,7 +21,7 @@ impl PciAddr {
         }
     }
 
-    pub fn domain(&self) -> u16 {
+    pub fn domain(&self) -> u32 {
         self.domain
     }
 
@@ -45,3 +50,64 @@ impl Display for PciAddr {
         )
     }
 }
+
+/// The error returned when parsing a malformed PCI address string.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParsePciAddrError {
+    addr: String,
+}
+
+impl Display for ParsePciAddrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "invalid PCI address \"{}\"", self.addr)
+    }
+}
+
+impl Error for ParsePciAddrError {}
+
+fn parse_hex_field(field: &str, max_digits: usize, max_value: u32) -> Option<u32> {
+    if field.is_empty() || field.len() > max_digits || !field.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    u32::from_str_radix(field, 16)
+        .ok()
+        .filter(|value| *value <= max_value)
+}
+
+impl FromStr for PciAddr {
+    type Err = ParsePciAddrError;
+
+    //
+    // Accepts the "[domain:]bus:device.function" syntax used by lspci, where
+    // the domain defaults to 0000 when omitted.  Domains are four hex digits,
+    // except on systems with more than 65536 of them, such as some PCIe
+    // fabrics, where lspci shows up to eight.
+    //
+    fn from_str(s: &str) -> Result<PciAddr, ParsePciAddrError> {
+        let err = || ParsePciAddrError {
+            addr: s.to_string(),
+        };
+
+        let (domain, bus, devfn) = match s.split(':').collect::<Vec<&str>>()[..] {
+            [bus, devfn] => ("0", bus, devfn),
+            [domain, bus, devfn] => (domain, bus, devfn),
+            _ => return Err(err()),
+        };
+
+        let (device, function) = devfn.split_once('.').ok_or_else(err)?;
+
+        let domain = parse_hex_field(domain, 8, 0xffffffff).ok_or_else(err)?;
+        let bus = parse_hex_field(bus, 2, 0xff).ok_or_else(err)?;
+        let device = parse_hex_field(device, 2, 0x1f).ok_or_else(err)?;
+        let function = parse_hex_field(function, 1, 0x7).ok_or_else(err)?;
+
+        Ok(PciAddr::new(
+            domain,
+            bus as u8,
+            device as u8,
+            function as u8,
+        ))
+    }
+}
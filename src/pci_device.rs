@@ -1,9 +1,13 @@
-use std::{collections::HashMap, sync::OnceLock};
+use std::sync::OnceLock;
 
 use regex::Regex;
 
-use crate::{LnkCap, LnkSta, PciAddr};
+use crate::{
+    AspmState, ExitLatency, LnkCap, LnkSta, NameResolver, PciAddr, PciDeviceBuilder,
+    machine_readable,
+};
 
+/// A PCI device, backed by its section of `lspci -nnvv` output.
 #[derive(Debug)]
 pub struct PciDevice {
     addr: PciAddr,
@@ -14,22 +18,49 @@ pub struct PciDevice {
 }
 
 impl PciDevice {
+    /// Parses a device section of `lspci -nnvv` output, returning `None` if
+    /// the section doesn't describe a PCI device.  The section's first line
+    /// may also be in the format that `lspci -mm -nn` prints, and may start
+    /// with the bridge path that `lspci -PP` prints.  Configuration space
+    /// hex dumps, as printed by `lspci -x` and up, are left out.
     pub fn parse(desc: &str) -> Option<PciDevice> {
         static PCI_DEVICE_RE: OnceLock<Regex> = OnceLock::new();
+        static HEX_DUMP_RE: OnceLock<Regex> = OnceLock::new();
+
+        let desc = machine_readable::rewrite_header(desc).unwrap_or_else(|| desc.to_string());
+
+        //
+        // The hex dump lines don't tell anything that the decoded lines
+        // don't, and they would make the section several times as long to
+        // match regexes against.
+        //
+        let hex_dump_re = HEX_DUMP_RE
+            .get_or_init(|| Regex::new(r"^[0-9a-f]{1,2}0:( [0-9a-f]{2}){1,16}$").unwrap());
+
+        let desc = if desc.lines().any(|line| hex_dump_re.is_match(line)) {
+            desc.split('\n')
+                .filter(|line| !hex_dump_re.is_match(line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            desc
+        };
 
         PCI_DEVICE_RE
             .get_or_init(|| {
                 Regex::new(concat!(
-                    r"^(?:([0-9a-f]{4}):)?([0-9a-f]{2}):([0-9a-f]{2})\.([0-7]).*",
+                    r"^(?:([0-9a-f]{4,8}):)?",
+                    r"(?:[0-9a-f]{2}:[0-9a-f]{2}\.[0-7]/)*",
+                    r"([0-9a-f]{2}):([0-9a-f]{2})\.([0-7]).*",
                     r"\[([0-9a-f]{4}):([0-9a-f]{4})\]"
                 ))
                 .unwrap()
             })
-            .captures(desc)
+            .captures(&desc)
             .map(|caps| {
                 let domain = caps
                     .get(1)
-                    .map_or(0, |m| u16::from_str_radix(m.as_str(), 16).unwrap());
+                    .map_or(0, |m| u32::from_str_radix(m.as_str(), 16).unwrap());
                 let bus = u8::from_str_radix(&caps[2], 16).unwrap();
                 let device = u8::from_str_radix(&caps[3], 16).unwrap();
                 let function = u8::from_str_radix(&caps[4], 16).unwrap();
@@ -45,6 +76,18 @@ impl PciDevice {
             })
     }
 
+    /// Returns a builder for a device with the given address and IDs,
+    /// for constructing a device without `lspci` output.
+    pub fn builder(addr: PciAddr, vendor_id: u16, device_id: u16) -> PciDeviceBuilder {
+        PciDeviceBuilder::new(addr, vendor_id, device_id)
+    }
+
+    /// Returns the section of `lspci` output that the device was parsed
+    /// from, or that was synthesized for it by `PciDeviceBuilder`.
+    pub fn desc(&self) -> &str {
+        &self.desc
+    }
+
     pub fn addr(&self) -> PciAddr {
         self.addr
     }
@@ -57,83 +100,167 @@ impl PciDevice {
         self.device_id
     }
 
+    /// Returns the device's class, e.g. `Ethernet controller`, as printed on
+    /// the first line of its section.
+    pub fn device_class(&self) -> Option<&str> {
+        static DEVICE_CLASS_RE: OnceLock<Regex> = OnceLock::new();
+
+        DEVICE_CLASS_RE
+            .get_or_init(|| Regex::new(r"^[^ ]+ ([^\n\[]+) \[[0-9a-f]{4}\]:").unwrap())
+            .captures(&self.desc)
+            .map(|caps| caps.get(1).unwrap().as_str())
+    }
+
+    /// Returns the device's class code, e.g. 0x0200 for an Ethernet
+    /// controller, as printed on the first line of its section by `lspci
+    /// -nn`.  Unlike the class name, this doesn't depend on the version of
+    /// `pci.ids` that lspci used.
+    pub fn class_code(&self) -> Option<u16> {
+        static CLASS_CODE_RE: OnceLock<Regex> = OnceLock::new();
+
+        CLASS_CODE_RE
+            .get_or_init(|| Regex::new(r"^[^ ]+ [^\n\[]+ \[([0-9a-f]{4})\]:").unwrap())
+            .captures(&self.desc)
+            .map(|caps| u16::from_str_radix(&caps[1], 16).unwrap())
+    }
+
+    /// Returns lspci's description of the device, e.g. `Intel Corporation
+    /// Ethernet Controller E810-C for QSFP`, as printed on the first line of
+    /// its section.  Devices that lspci has no name for are described as just
+    /// `Device`, which is not returned.
+    pub fn full_name(&self) -> Option<&str> {
+        static FULL_NAME_RE: OnceLock<Regex> = OnceLock::new();
+
+        FULL_NAME_RE
+            .get_or_init(|| {
+                Regex::new(r"^[^ ]+ [^\n\[]+ \[[0-9a-f]{4}\]: ([^\n]+) \[[0-9a-f]{4}:[0-9a-f]{4}\]")
+                    .unwrap()
+            })
+            .captures(&self.desc)
+            .map(|caps| caps.get(1).unwrap().as_str())
+            .filter(|full_name| *full_name != "Device")
+    }
+
+    /// Returns the device's revision, from the first line of its section.
+    /// Note that lspci shows a revision of ff for devices that have stopped
+    /// responding to configuration space reads.
+    pub fn revision(&self) -> Option<u8> {
+        static REVISION_RE: OnceLock<Regex> = OnceLock::new();
+
+        REVISION_RE
+            .get_or_init(|| Regex::new(r"^[^\n]* \(rev ([0-9a-f]{2})\)").unwrap())
+            .captures(&self.desc)
+            .map(|caps| u8::from_str_radix(&caps[1], 16).unwrap())
+    }
+
+    pub fn subsystem_id(&self) -> Option<(u16, u16)> {
+        static SUBSYSTEM_RE: OnceLock<Regex> = OnceLock::new();
+
+        SUBSYSTEM_RE
+            .get_or_init(|| {
+                Regex::new(r"\n\tSubsystem: [^\n]*\[([0-9a-f]{4}):([0-9a-f]{4})\]\n").unwrap()
+            })
+            .captures(&self.desc)
+            .map(|caps| {
+                (
+                    u16::from_str_radix(&caps[1], 16).unwrap(),
+                    u16::from_str_radix(&caps[2], 16).unwrap(),
+                )
+            })
+    }
+
+    /// Returns the device's name from the built-in short name table.
     pub fn short_name(&self) -> Option<&'static str> {
-        static SHORT_NAMES: [((u16, u16), &str); 46] = [
-            ((0x1000, 0x005d), "MegaRAID 3108"),
-            ((0x1000, 0x00b2), "switch mgmt"),
-            ((0x1000, 0x02b2), "placeholder"),
-            ((0x1000, 0xc010), "placeholder"),
-            ((0x1022, 0x1485), "AMD SPP"),
-            ((0x1022, 0x1486), "AMD PSPCPP"),
-            ((0x1022, 0x1487), "AMD HD Audio"),
-            ((0x1022, 0x148a), "dummy function"),
-            ((0x1022, 0x148c), "AMD XHCI"),
-            ((0x1022, 0x1498), "AMD PTDMA"),
-            ((0x1022, 0x149c), "AMD XHCI"),
-            ((0x1022, 0x7901), "AMD SATA"),
-            ((0x102b, 0x0522), "Matrox VGA"),
-            ((0x102b, 0x0534), "Matrox VGA"),
-            ((0x102b, 0x0536), "Matrox VGA"),
-            ((0x10de, 0x0e0f), "NVIDIA GK208 HDMP/DP Audio"),
-            ((0x10de, 0x128b), "NVIDIA GT 710"),
-            ((0x10de, 0x1af1), "A100 NVSwitch"),
-            ((0x10de, 0x20b0), "A100 SXM4 40GB"),
-            ((0x10de, 0x22a3), "H100 NVSwitch"),
-            ((0x10de, 0x2330), "H100 SXM5 80GB"),
-            ((0x10de, 0x2335), "H200 SXM5 141GB"),
-            ((0x10de, 0x2901), "B200 SXM6 192GB"),
-            ((0x10ec, 0x8125), "Realtek RTL8125 2.5GbE"),
-            ((0x1344, 0x51c3), "Micron NVMe"),
-            ((0x144d, 0xa808), "Samsung NVMe"),
-            ((0x144d, 0xa80a), "Samsung NVMe"),
-            ((0x144d, 0xa80c), "Samsung NVMe"),
-            ((0x144d, 0xa824), "Samsung NVMe"),
-            ((0x144d, 0xa825), "Samsung NVMe"),
-            ((0x14e4, 0x165f), "Broadcom BCM5720"),
-            ((0x15b3, 0x1019), "MT28800 ConnectX-5 Ex ETH"),
-            ((0x15b3, 0x101b), "MT28908 ConnectX-6 IB"),
-            ((0x15b3, 0x101d), "MT2892 ConnectX-6 Dx ETH"),
-            ((0x15b3, 0x101e), "ConnectX-7 IB VF"),
-            ((0x15b3, 0x1021), "MT2910 ConnectX-7 IB"),
-            ((0x15b3, 0xa2dc), "MT43244 BlueField-3"),
-            ((0x15b3, 0xc2d5), "MT43244 BlueField-3 mgmt"),
-            ((0x1912, 0x0014), "Renesas USB3"),
-            ((0x1a03, 0x2000), "ASPEED VGA"),
-            ((0x1a03, 0x2402), "ASPEED IPMI"),
-            ((0x1b4b, 0x2241), "Marvell NVMe"),
-            ((0x1b4b, 0x9485), "Marvell SAS/SATA"),
-            ((0x8086, 0x1563), "Intel X550"),
-            ((0x8086, 0x15f3), "Intel I225-V"),
-            ((0x8086, 0x2723), "Intel Wi-Fi 6 AX200"),
-        ];
-
-        static SHORT_NAMES_LOOKUP: OnceLock<HashMap<(u16, u16), &'static str>> = OnceLock::new();
-
-        SHORT_NAMES_LOOKUP
-            .get_or_init(|| HashMap::from(SHORT_NAMES))
-            .get(&(self.vendor_id, self.device_id))
-            .copied()
+        NameResolver::builtin().short_name(self)
     }
 
     pub fn is_root_port(&self) -> bool {
         static PCIE_ROOT_PORT_RE: OnceLock<Regex> = OnceLock::new();
 
         PCIE_ROOT_PORT_RE
-            .get_or_init(|| Regex::new(r" Express \(v2\) Root Port ").unwrap())
+            .get_or_init(|| Regex::new(r" Express \(v[0-9]+\) Root Port ").unwrap())
             .is_match(&self.desc)
     }
 
+    pub fn physical_slot(&self) -> Option<&str> {
+        static PHYSICAL_SLOT_RE: OnceLock<Regex> = OnceLock::new();
+
+        PHYSICAL_SLOT_RE
+            .get_or_init(|| Regex::new(r"\n\tPhysical Slot: ([^\n]*)").unwrap())
+            .captures(&self.desc)
+            .map(|caps| caps.get(1).unwrap().as_str())
+    }
+
+    /// Returns whether the port's slot supports hot-plugging, from SltCap.
+    /// Ports that don't implement a slot have no SltCap.
+    pub fn hotplug_capable(&self) -> Option<bool> {
+        static HOTPLUG_RE: OnceLock<Regex> = OnceLock::new();
+
+        HOTPLUG_RE
+            .get_or_init(|| Regex::new(r"SltCap:[ \t][^\n]* HotPlug([+-])").unwrap())
+            .captures(&self.desc)
+            .map(|caps| &caps[1] == "+")
+    }
+
+    /// Returns the power limit of the port's slot in watts, from SltCap.
+    /// lspci decodes the limit's value and scale into watts, and prints
+    /// e.g. `PowerLimit 75.000W`, or `PowerLimit 75W` in older versions.
+    pub fn slot_power_limit(&self) -> Option<f32> {
+        static SLOT_POWER_LIMIT_RE: OnceLock<Regex> = OnceLock::new();
+
+        SLOT_POWER_LIMIT_RE
+            .get_or_init(|| {
+                Regex::new(concat!(
+                    r"SltCap:[ \t][^\n]*\n[ \t]*Slot #[0-9]+, ",
+                    r"(?:PowerLimit|Slot Power Limit) >?([0-9]+(?:\.[0-9]+)?)W"
+                ))
+                .unwrap()
+            })
+            .captures(&self.desc)
+            .map(|caps| caps[1].parse::<f32>().unwrap())
+    }
+
     pub fn numa_node(&self) -> Option<usize> {
         static NUMA_NODE_RE: OnceLock<Regex> = OnceLock::new();
 
         NUMA_NODE_RE
             .get_or_init(|| Regex::new(r"NUMA node: ([0-9]*)\n").unwrap())
             .captures(&self.desc)
-            .map(|caps| usize::from_str_radix(&caps[1], 16).unwrap())
+            .map(|caps| caps[1].parse::<usize>().unwrap())
+    }
+
+    pub fn iommu_group(&self) -> Option<u32> {
+        static IOMMU_GROUP_RE: OnceLock<Regex> = OnceLock::new();
+
+        IOMMU_GROUP_RE
+            .get_or_init(|| Regex::new(r"\tIOMMU group: ([0-9]+)").unwrap())
+            .captures(&self.desc)
+            .map(|caps| caps[1].parse::<u32>().unwrap())
+    }
+
+    /// Returns the physical package ID of the CPU socket that the device is
+    /// attached to.  lspci doesn't show this, so this is only known for
+    /// devices read by `Machine::parse_sysfs`.
+    pub fn cpu_socket(&self) -> Option<usize> {
+        static CPU_SOCKET_RE: OnceLock<Regex> = OnceLock::new();
+
+        CPU_SOCKET_RE
+            .get_or_init(|| Regex::new(r"\tCPU socket: ([0-9]+)\n").unwrap())
+            .captures(&self.desc)
+            .map(|caps| caps[1].parse::<usize>().unwrap())
     }
 
+    /// Returns the name of the group that the device is drawn in: by NUMA
+    /// node if known, otherwise by CPU socket if known, otherwise just
+    /// "CPU".  Devices on bus 0 are taken to be part of the PCH.
     pub fn device_group_name(&self) -> String {
-        match self.numa_node() {
+        let location = match (self.numa_node(), self.cpu_socket()) {
+            (Some(numa_node), _) => Some(format!("NUMA node #{}", numa_node)),
+            (None, Some(cpu_socket)) => Some(format!("CPU socket {}", cpu_socket)),
+            (None, None) => None,
+        };
+
+        match location {
             None => {
                 if self.addr().bus() == 0 {
                     "PCH".to_string()
@@ -141,11 +268,11 @@ impl PciDevice {
                     "CPU".to_string()
                 }
             }
-            Some(numa_node) => {
+            Some(location) => {
                 if self.addr().bus() == 0 {
-                    format!("PCH (on NUMA node #{})", numa_node)
+                    format!("PCH (on {})", location)
                 } else {
-                    format!("NUMA node #{}", numa_node)
+                    location
                 }
             }
         }
@@ -157,7 +284,7 @@ impl PciDevice {
         LNK_CAP_RE
             .get_or_init(|| {
                 Regex::new(concat!(
-                    r"LnkCap:\tPort #[0-9]*, ",
+                    r"LnkCap:\t(?:Port #([0-9]*), )?",
                     r"Speed ([0-9.]*)GT/s, ",
                     r"Width x([0-9]*)"
                 ))
@@ -165,11 +292,204 @@ impl PciDevice {
             })
             .captures(&self.desc)
             .map(|caps| {
-                LnkCap::new(
-                    caps[1].parse::<f32>().unwrap(),
-                    caps[2].parse::<u8>().unwrap(),
-                )
+                let lnk_cap = LnkCap::new(
+                    caps[2].parse::<f32>().unwrap(),
+                    caps[3].parse::<u8>().unwrap(),
+                );
+
+                match caps.get(1).map(|port| port.as_str().parse::<u8>()) {
+                    Some(Ok(port)) => lnk_cap.with_port(port),
+                    _ => lnk_cap,
+                }
+            })
+    }
+
+    /// Returns the Target Link Speed in GT/s from LnkCtl2, which firmware
+    /// can lower to keep the link from training at its full speed.
+    pub fn target_link_speed(&self) -> Option<f32> {
+        static TARGET_LINK_SPEED_RE: OnceLock<Regex> = OnceLock::new();
+
+        TARGET_LINK_SPEED_RE
+            .get_or_init(|| Regex::new(r"LnkCtl2:[ \t]Target Link Speed: ([0-9.]+)GT/s").unwrap())
+            .captures(&self.desc)
+            .map(|caps| caps[1].parse::<f32>().unwrap())
+    }
+
+    /// Returns whether the link uses a reference clock common to both of its
+    /// ends, rather than a separate one for each end, from the Common Clock
+    /// Configuration bit (`CommClk`) in LnkCtl.
+    pub fn common_clock(&self) -> Option<bool> {
+        static COMMON_CLOCK_RE: OnceLock<Regex> = OnceLock::new();
+
+        COMMON_CLOCK_RE
+            .get_or_init(|| Regex::new(r"LnkCtl:[^\n]* CommClk([+-])").unwrap())
+            .captures(&self.desc)
+            .map(|caps| &caps[1] == "+")
+    }
+
+    /// Returns how many retimers were detected on the link, from the
+    /// Retimer Presence Detected bits in LnkSta2, or `None` if the device
+    /// doesn't report them.
+    pub fn retimer_count(&self) -> Option<u8> {
+        static LNK_STA2_RE: OnceLock<Regex> = OnceLock::new();
+        static RETIMER_RE: OnceLock<Regex> = OnceLock::new();
+
+        //
+        // LnkSta2 is wrapped over several lines, and the retimer bits are
+        // usually on the last one.
+        //
+        let lnk_sta2 = LNK_STA2_RE
+            .get_or_init(|| Regex::new(r"LnkSta2:[ \t]((?:[^\n]|\n\t\t\t)*)").unwrap())
+            .captures(&self.desc)?;
+
+        let caps = RETIMER_RE
+            .get_or_init(|| Regex::new(r"\bRetimer([+-])(?:\s+2Retimers([+-]))?").unwrap())
+            .captures(lnk_sta2.get(1).unwrap().as_str())?;
+
+        match (&caps[1], caps.get(2).map(|two| two.as_str())) {
+            (_, Some("+")) => Some(2),
+            ("+", _) => Some(1),
+            _ => Some(0),
+        }
+    }
+
+    /// Returns whether Access Control Services are enabled on the port, from
+    /// ACSCtl.  As with Linux's IOMMU grouping, ACS counts as enabled if
+    /// all of source validation, P2P request and completion redirection
+    /// and upstream forwarding that the port supports (per ACSCap) are
+    /// enabled.
+    pub fn acs_enabled(&self) -> Option<bool> {
+        static ACS_RE: OnceLock<Regex> = OnceLock::new();
+
+        let caps = ACS_RE
+            .get_or_init(|| {
+                Regex::new(r"ACSCap:[ \t]([^\n]*)\n[ \t]*ACSCtl:[ \t]([^\n]*)").unwrap()
+            })
+            .captures(&self.desc)?;
+
+        let is_set =
+            |flags: &str, name: &str| flags.split(' ').any(|flag| flag == format!("{}+", name));
+
+        Some(
+            ["SrcValid", "ReqRedir", "CmpltRedir", "UpstreamFwd"]
+                .iter()
+                .all(|name| !is_set(&caps[1], name) || is_set(&caps[2], name)),
+        )
+    }
+
+    /// Returns whether Advanced Error Reporting has logged any errors, i.e.
+    /// whether any bit is set in the Uncorrectable or Correctable Error
+    /// Status register (UESta or CESta).  `None` for devices without AER.
+    pub fn aer_has_errors(&self) -> Option<bool> {
+        static AER_STATUS_RE: OnceLock<Regex> = OnceLock::new();
+
+        //
+        // Newer versions of lspci wrap the status bits over several lines.
+        //
+        let statuses = AER_STATUS_RE
+            .get_or_init(|| Regex::new(r"\t(?:UESta|CESta):[ \t]((?:[^\n]|\n\t\t\t)*)").unwrap())
+            .captures_iter(&self.desc)
+            .map(|caps| caps.get(1).unwrap().as_str())
+            .collect::<Vec<_>>();
+
+        if statuses.is_empty() {
+            return None;
+        }
+
+        Some(
+            statuses
+                .iter()
+                .flat_map(|status| status.split_whitespace())
+                .any(|flag| flag.ends_with('+')),
+        )
+    }
+
+    /// Returns the largest payload size, in bytes, that the device supports,
+    /// from DevCap.
+    pub fn max_payload_supported(&self) -> Option<u16> {
+        static MAX_PAYLOAD_SUPPORTED_RE: OnceLock<Regex> = OnceLock::new();
+
+        MAX_PAYLOAD_SUPPORTED_RE
+            .get_or_init(|| Regex::new(r"DevCap:[ \t]MaxPayload ([0-9]+) bytes").unwrap())
+            .captures(&self.desc)
+            .and_then(|caps| caps[1].parse().ok())
+    }
+
+    /// Returns whether the device supports Function Level Reset, from
+    /// DevCap.
+    pub fn supports_flr(&self) -> Option<bool> {
+        static DEV_CAP_RE: OnceLock<Regex> = OnceLock::new();
+        static FLR_RE: OnceLock<Regex> = OnceLock::new();
+
+        //
+        // DevCap is wrapped over two lines, and DevCtl has an FLReset bit
+        // of its own, for initiating a reset.
+        //
+        let dev_cap = DEV_CAP_RE
+            .get_or_init(|| Regex::new(r"DevCap:[ \t]((?:[^\n]|\n\t\t\t)*)").unwrap())
+            .captures(&self.desc)?;
+
+        FLR_RE
+            .get_or_init(|| Regex::new(r"\bFLReset([+-])").unwrap())
+            .captures(dev_cap.get(1).unwrap().as_str())
+            .map(|caps| &caps[1] == "+")
+    }
+
+    /// Returns the payload size, in bytes, that the device is configured to
+    /// use, from DevCtl.
+    pub fn max_payload(&self) -> Option<u16> {
+        static MAX_PAYLOAD_RE: OnceLock<Regex> = OnceLock::new();
+
+        MAX_PAYLOAD_RE
+            .get_or_init(|| {
+                Regex::new(r"DevCtl:[ \t][^\n]*(?:\n\t\t\t[^\n]*)*?MaxPayload ([0-9]+) bytes")
+                    .unwrap()
+            })
+            .captures(&self.desc)
+            .and_then(|caps| caps[1].parse().ok())
+    }
+
+    /// Returns the ASPM states supported by the device's link, from LnkCap.
+    pub fn aspm_capable(&self) -> Option<AspmState> {
+        static ASPM_CAPABLE_RE: OnceLock<Regex> = OnceLock::new();
+
+        ASPM_CAPABLE_RE
+            .get_or_init(|| Regex::new(r"\tLnkCap:\t[^\n]*, ASPM ([^,\n]*),").unwrap())
+            .captures(&self.desc)
+            .and_then(|caps| AspmState::parse(&caps[1]))
+    }
+
+    /// Returns the time it takes the device's link to leave the L1 ASPM
+    /// state, from LnkCap.
+    pub fn l1_exit_latency(&self) -> Option<ExitLatency> {
+        static L1_EXIT_LATENCY_RE: OnceLock<Regex> = OnceLock::new();
+
+        L1_EXIT_LATENCY_RE
+            .get_or_init(|| {
+                Regex::new(r"\tLnkCap:\t[^\n]*, Exit Latency (?:[^\n]*, )?L1 ([^,\n]*)").unwrap()
             })
+            .captures(&self.desc)
+            .and_then(|caps| ExitLatency::parse(&caps[1]))
+    }
+
+    /// Returns the ASPM states enabled on the device's link, from LnkCtl.
+    pub fn aspm_enabled(&self) -> Option<AspmState> {
+        static ASPM_ENABLED_RE: OnceLock<Regex> = OnceLock::new();
+
+        ASPM_ENABLED_RE
+            .get_or_init(|| Regex::new(r"\tLnkCtl:\tASPM ([^;\n]*);").unwrap())
+            .captures(&self.desc)
+            .and_then(|caps| AspmState::parse(&caps[1]))
+    }
+
+    /// Returns whether the Slot Status register of this (downstream or
+    /// root) port reports that a card is present in its slot.
+    pub fn is_card_present(&self) -> bool {
+        static PRESENCE_DETECT_RE: OnceLock<Regex> = OnceLock::new();
+
+        PRESENCE_DETECT_RE
+            .get_or_init(|| Regex::new(r"\tSltSta:\tStatus:[^\n]* PresDet\+").unwrap())
+            .is_match(&self.desc)
     }
 
     pub fn secondary_bus(&self) -> Option<u8> {
@@ -181,11 +501,59 @@ impl PciDevice {
             .map(|caps| u8::from_str_radix(&caps[1], 16).unwrap())
     }
 
+    /// Returns the highest bus number behind the bridge.  The buses from
+    /// the secondary bus up to this one are all behind it.
+    pub fn subordinate_bus(&self) -> Option<u8> {
+        static SUBORDINATE_BUS_RE: OnceLock<Regex> = OnceLock::new();
+
+        SUBORDINATE_BUS_RE
+            .get_or_init(|| {
+                Regex::new(r", secondary=[0-9a-f]{2}, subordinate=([0-9a-f]{2})").unwrap()
+            })
+            .captures(&self.desc)
+            .map(|caps| u8::from_str_radix(&caps[1], 16).unwrap())
+    }
+
+    /// Returns the bridges that lead to the device, starting at the root
+    /// port, from the path that `lspci -PP` prints in place of the device's
+    /// address.  Empty if the section has no path.
+    pub fn bridge_path(&self) -> Vec<PciAddr> {
+        static BRIDGE_PATH_RE: OnceLock<Regex> = OnceLock::new();
+
+        BRIDGE_PATH_RE
+            .get_or_init(|| {
+                Regex::new(r"^(?:[0-9a-f]{4,8}:)?((?:[0-9a-f]{2}:[0-9a-f]{2}\.[0-7]/)+)").unwrap()
+            })
+            .captures(&self.desc)
+            .map_or_else(Vec::new, |caps| {
+                caps[1]
+                    .split_terminator('/')
+                    .filter_map(|bridge| bridge.parse::<PciAddr>().ok())
+                    .map(|bridge| {
+                        PciAddr::new(
+                            self.addr.domain(),
+                            bridge.bus(),
+                            bridge.device(),
+                            bridge.function(),
+                        )
+                    })
+                    .collect()
+            })
+    }
+
     pub fn is_upstream_port(&self) -> bool {
         static PCIE_UPSTREAM_PORT_RE: OnceLock<Regex> = OnceLock::new();
 
         PCIE_UPSTREAM_PORT_RE
-            .get_or_init(|| Regex::new(r" Express \(v2\) Upstream Port, ").unwrap())
+            .get_or_init(|| Regex::new(r" Express \(v[0-9]+\) Upstream Port, ").unwrap())
+            .is_match(&self.desc)
+    }
+
+    pub fn is_downstream_port(&self) -> bool {
+        static PCIE_DOWNSTREAM_PORT_RE: OnceLock<Regex> = OnceLock::new();
+
+        PCIE_DOWNSTREAM_PORT_RE
+            .get_or_init(|| Regex::new(r" Express \(v[0-9]+\) Downstream Port ").unwrap())
             .is_match(&self.desc)
     }
 
@@ -193,7 +561,17 @@ impl PciDevice {
         static PCIE_ENDPOINT_RE: OnceLock<Regex> = OnceLock::new();
 
         PCIE_ENDPOINT_RE
-            .get_or_init(|| Regex::new(r" Express \(v2\) (?:Legacy )?Endpoint, ").unwrap())
+            .get_or_init(|| Regex::new(r" Express \(v[0-9]+\) (?:Legacy )?Endpoint, ").unwrap())
+            .is_match(&self.desc)
+    }
+
+    /// Returns whether the device is a conventional PCI device, without a
+    /// PCI Express capability, such as a device behind a PCIe to PCI bridge.
+    pub fn is_legacy_pci_device(&self) -> bool {
+        static PCIE_CAPABILITY_RE: OnceLock<Regex> = OnceLock::new();
+
+        !PCIE_CAPABILITY_RE
+            .get_or_init(|| Regex::new(r" Express \(v[0-9]+\) ").unwrap())
             .is_match(&self.desc)
     }
 
@@ -202,7 +580,7 @@ impl PciDevice {
 
         PCIE_PCI_BRIDGE_RE
             .get_or_init(|| {
-                Regex::new(r" Express \(v2\) PCI-Express to PCI/PCI-X Bridge, ").unwrap()
+                Regex::new(r" Express \(v[0-9]+\) PCI-Express to PCI/PCI-X Bridge, ").unwrap()
             })
             .is_match(&self.desc)
     }
@@ -234,6 +612,15 @@ impl PciDevice {
             })
     }
 
+    pub fn kernel_driver(&self) -> Option<&str> {
+        static KERNEL_DRIVER_RE: OnceLock<Regex> = OnceLock::new();
+
+        KERNEL_DRIVER_RE
+            .get_or_init(|| Regex::new(r"\tKernel driver in use: ([^\n]*)").unwrap())
+            .captures(&self.desc)
+            .map(|caps| caps.get(1).unwrap().as_str())
+    }
+
     pub fn serial_number(&self) -> Option<u64> {
         static DEVICE_SERIAL_NUMBER_RE: OnceLock<Regex> = OnceLock::new();
 
@@ -266,4 +653,26 @@ impl PciDevice {
                 ])
             })
     }
+
+    /// Returns the VSEC ID and length of each of the device's Vendor-Specific
+    /// Extended Capabilities, in the order in which they were listed.
+    pub fn vsec_ids(&self) -> Vec<(u16, u16)> {
+        static VSEC_RE: OnceLock<Regex> = OnceLock::new();
+
+        VSEC_RE
+            .get_or_init(|| {
+                Regex::new(
+                    r"\] Vendor Specific Information: ID=([0-9a-f]{4}) Rev=[0-9]+ Len=([0-9a-f]{3})",
+                )
+                .unwrap()
+            })
+            .captures_iter(&self.desc)
+            .map(|caps| {
+                (
+                    u16::from_str_radix(&caps[1], 16).unwrap(),
+                    u16::from_str_radix(&caps[2], 16).unwrap(),
+                )
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,239 @@
+use std::fmt::Write as _;
+
+use crate::{LnkCap, LnkSta, PciAddr, PciDevice, sysfs};
+
+/// The role of a PCI Express device, as listed in its PCI Express
+/// capability.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PortType {
+    Endpoint,
+    LegacyEndpoint,
+    RootPort,
+    UpstreamPort,
+    DownstreamPort,
+    PciBridge,
+}
+
+impl PortType {
+    fn is_bridge(self) -> bool {
+        !matches!(self, PortType::Endpoint | PortType::LegacyEndpoint)
+    }
+}
+
+/// Builds a `PciDevice` from values set by the caller rather than from
+/// `lspci` output, e.g. for devices found by other means, or for tests.
+///
+/// Created by `PciDevice::builder`.
+#[derive(Debug)]
+pub struct PciDeviceBuilder {
+    addr: PciAddr,
+    vendor_id: u16,
+    device_id: u16,
+    class: Option<u16>,
+    port_type: Option<PortType>,
+    buses: Option<(u8, u8)>,
+    lnk_cap: Option<LnkCap>,
+    lnk_sta: Option<LnkSta>,
+    numa_node: Option<usize>,
+    physical_slot: Option<String>,
+    kernel_driver: Option<String>,
+    serial_number: Option<u64>,
+}
+
+impl PciDeviceBuilder {
+    pub(crate) fn new(addr: PciAddr, vendor_id: u16, device_id: u16) -> PciDeviceBuilder {
+        PciDeviceBuilder {
+            addr,
+            vendor_id,
+            device_id,
+            class: None,
+            port_type: None,
+            buses: None,
+            lnk_cap: None,
+            lnk_sta: None,
+            numa_node: None,
+            physical_slot: None,
+            kernel_driver: None,
+            serial_number: None,
+        }
+    }
+
+    /// Sets the device's base class and subclass, e.g. `0x0200` for an
+    /// Ethernet controller.  Bridges and ports default to `0x0604` (PCI
+    /// bridge), other devices to `0x0000`.
+    pub fn class(mut self, class: u16) -> PciDeviceBuilder {
+        self.class = Some(class);
+        self
+    }
+
+    /// Gives the device a PCI Express capability with the given role.
+    /// Devices without one are conventional PCI devices.
+    pub fn port_type(mut self, port_type: PortType) -> PciDeviceBuilder {
+        self.port_type = Some(port_type);
+        self
+    }
+
+    /// Sets the secondary and subordinate bus numbers of a bridge or port.
+    pub fn buses(mut self, secondary: u8, subordinate: u8) -> PciDeviceBuilder {
+        self.buses = Some((secondary, subordinate));
+        self
+    }
+
+    pub fn lnk_cap(mut self, lnk_cap: LnkCap) -> PciDeviceBuilder {
+        self.lnk_cap = Some(lnk_cap);
+        self
+    }
+
+    pub fn lnk_sta(mut self, lnk_sta: LnkSta) -> PciDeviceBuilder {
+        self.lnk_sta = Some(lnk_sta);
+        self
+    }
+
+    pub fn numa_node(mut self, numa_node: usize) -> PciDeviceBuilder {
+        self.numa_node = Some(numa_node);
+        self
+    }
+
+    pub fn physical_slot<T: Into<String>>(mut self, physical_slot: T) -> PciDeviceBuilder {
+        self.physical_slot = Some(physical_slot.into());
+        self
+    }
+
+    pub fn kernel_driver<T: Into<String>>(mut self, kernel_driver: T) -> PciDeviceBuilder {
+        self.kernel_driver = Some(kernel_driver.into());
+        self
+    }
+
+    pub fn serial_number(mut self, serial_number: u64) -> PciDeviceBuilder {
+        self.serial_number = Some(serial_number);
+        self
+    }
+
+    /// Builds the device.  Its `desc` is an `lspci -nnvv` section holding
+    /// the values that were set, in the same way as for devices read by
+    /// `Machine::parse_sysfs`.
+    pub fn build(self) -> PciDevice {
+        PciDevice::parse(&self.section()).unwrap()
+    }
+
+    fn section(&self) -> String {
+        let class = self
+            .class
+            .unwrap_or(if self.port_type.is_some_and(PortType::is_bridge) {
+                0x0604
+            } else {
+                0x0000
+            });
+
+        let mut desc = String::new();
+
+        writeln!(
+            desc,
+            "{} {} [{:04x}]: Device [{:04x}:{:04x}]",
+            self.addr,
+            sysfs::class_name(class),
+            class,
+            self.vendor_id,
+            self.device_id
+        )
+        .unwrap();
+
+        if let Some(physical_slot) = &self.physical_slot {
+            writeln!(desc, "\tPhysical Slot: {}", physical_slot).unwrap();
+        }
+
+        if let Some(numa_node) = self.numa_node {
+            writeln!(desc, "\tNUMA node: {}", numa_node).unwrap();
+        }
+
+        if let Some((secondary, subordinate)) = self.buses {
+            writeln!(
+                desc,
+                "\tBus: primary={:02x}, secondary={:02x}, subordinate={:02x}, sec-latency=0",
+                self.addr.bus(),
+                secondary,
+                subordinate
+            )
+            .unwrap();
+        }
+
+        if let Some(port_type) = self.port_type {
+            let slot = if self.physical_slot.is_some() {
+                '+'
+            } else {
+                '-'
+            };
+
+            let port_type = match port_type {
+                PortType::Endpoint => "Endpoint".to_string(),
+                PortType::LegacyEndpoint => "Legacy Endpoint".to_string(),
+                PortType::RootPort => format!("Root Port (Slot{})", slot),
+                PortType::UpstreamPort => "Upstream Port".to_string(),
+                PortType::DownstreamPort => format!("Downstream Port (Slot{})", slot),
+                PortType::PciBridge => "PCI-Express to PCI/PCI-X Bridge".to_string(),
+            };
+
+            writeln!(
+                desc,
+                "\tCapabilities: [40] Express (v2) {}, MSI 00",
+                port_type
+            )
+            .unwrap();
+
+            if let Some(lnk_cap) = &self.lnk_cap {
+                //
+                // A LnkCap without a port number is written without the
+                // Port field, so that it parses back without one rather
+                // than as port 0.
+                //
+                let port = lnk_cap
+                    .port()
+                    .map_or(String::new(), |port| format!("Port #{}, ", port));
+
+                writeln!(
+                    desc,
+                    "\t\tLnkCap:\t{}Speed {}GT/s, Width x{}, ASPM not supported",
+                    port,
+                    lnk_cap.speed(),
+                    lnk_cap.width()
+                )
+                .unwrap();
+            }
+
+            if let Some(lnk_sta) = &self.lnk_sta {
+                let downgraded = if lnk_sta.is_downgraded() {
+                    " (downgraded)"
+                } else {
+                    ""
+                };
+
+                writeln!(
+                    desc,
+                    "\t\tLnkSta:\tSpeed {}GT/s{}, Width x{}",
+                    lnk_sta.speed(),
+                    downgraded,
+                    lnk_sta.width()
+                )
+                .unwrap();
+            }
+        }
+
+        if let Some(serial_number) = self.serial_number {
+            writeln!(
+                desc,
+                "\tCapabilities: [100 v1] Device Serial Number {}",
+                serial_number
+                    .to_be_bytes()
+                    .map(|byte| format!("{:02x}", byte))
+                    .join("-")
+            )
+            .unwrap();
+        }
+
+        if let Some(kernel_driver) = &self.kernel_driver {
+            writeln!(desc, "\tKernel driver in use: {}", kernel_driver).unwrap();
+        }
+
+        desc
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Error, Read},
+    path::Path,
+};
+
+/// Device names from a `pci.ids` database, as shipped by hwdata.
+#[derive(Clone, Debug, Default)]
+pub struct PciIds {
+    names: HashMap<(u16, u16), String>,
+}
+
+impl PciIds {
+    /// Reads a `pci.ids` database from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<PciIds, Error> {
+        PciIds::parse(&mut File::open(path)?)
+    }
+
+    /// Reads a `pci.ids` database from `src`.
+    pub fn parse<T: Read>(src: &mut T) -> Result<PciIds, Error> {
+        let mut data = String::new();
+
+        src.read_to_string(&mut data)?;
+
+        let mut names = HashMap::new();
+        let mut vendor_id = None;
+
+        //
+        // Vendor lines start at column 0 and device lines are indented by
+        // a single tab, both with the ID and name separated by two spaces.
+        // Subsystem lines are indented by two tabs and are skipped, as are
+        // the device class lines ("C xx  ...") that follow the vendor list.
+        //
+        for line in data.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(device_line) = line.strip_prefix('\t') {
+                if device_line.starts_with('\t') {
+                    continue;
+                }
+
+                if let Some(vendor_id) = vendor_id
+                    && let Some((device_id, name)) = parse_id_line(device_line)
+                {
+                    names.insert((vendor_id, device_id), name.to_string());
+                }
+            } else {
+                vendor_id = parse_id_line(line).map(|(vendor_id, _)| vendor_id);
+            }
+        }
+
+        Ok(PciIds { names })
+    }
+
+    pub fn device_name(&self, vendor_id: u16, device_id: u16) -> Option<&str> {
+        self.names
+            .get(&(vendor_id, device_id))
+            .map(|name| name.as_str())
+    }
+}
+
+fn parse_id_line(line: &str) -> Option<(u16, &str)> {
+    let (id, name) = line.split_once("  ")?;
+
+    if id.len() != 4 {
+        return None;
+    }
+
+    u16::from_str_radix(id, 16).ok().map(|id| (id, name))
+}
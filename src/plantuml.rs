@@ -0,0 +1,275 @@
+use std::{
+    collections::HashMap,
+    io::{Error, Write},
+};
+
+use crate::{
+    GraphOptions, RankDir,
+    graph_writer::{Attrs, GraphWriter},
+};
+
+struct PlantUmlNode {
+    id: String,
+    label: Option<String>,
+    shape: Option<&'static str>,
+    color: Option<&'static str>,
+    fillcolor: Option<&'static str>,
+    parent: Option<u32>,
+}
+
+struct PlantUmlCluster {
+    index: u32,
+    label: Option<String>,
+    parent: Option<u32>,
+}
+
+struct PlantUmlEdge {
+    from: String,
+    to: String,
+    label: Option<String>,
+    color: Option<&'static str>,
+}
+
+//
+// Like GraphML, PlantUML wants the elements in a package to be declared
+// inside that package's block, so the whole diagram is buffered and
+// written out by end_graph.
+//
+pub struct PlantUmlWriter<'a, T: Write> {
+    w: &'a mut T,
+    options: &'a GraphOptions,
+    nodes: Vec<PlantUmlNode>,
+    node_indices: HashMap<String, usize>,
+    clusters: Vec<PlantUmlCluster>,
+    open_clusters: Vec<u32>,
+    edges: Vec<PlantUmlEdge>,
+}
+
+impl<'a, T: Write> PlantUmlWriter<'a, T> {
+    pub fn new(w: &'a mut T, options: &'a GraphOptions) -> PlantUmlWriter<'a, T> {
+        PlantUmlWriter {
+            w,
+            options,
+            nodes: Vec::new(),
+            node_indices: HashMap::new(),
+            clusters: Vec::new(),
+            open_clusters: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    fn node_mut(&mut self, id: &str) -> &mut PlantUmlNode {
+        let index = *self.node_indices.entry(id.to_string()).or_insert_with(|| {
+            self.nodes.push(PlantUmlNode {
+                id: id.to_string(),
+                label: None,
+                shape: None,
+                color: None,
+                fillcolor: None,
+                parent: self.open_clusters.last().copied(),
+            });
+
+            self.nodes.len() - 1
+        });
+
+        &mut self.nodes[index]
+    }
+
+    fn write_package_contents(&mut self, parent: Option<u32>, depth: usize) -> Result<(), Error> {
+        let indent = "  ".repeat(depth);
+
+        let clusters = self
+            .clusters
+            .iter()
+            .filter(|cluster| cluster.parent == parent)
+            .map(|cluster| (cluster.index, cluster.label.clone()))
+            .collect::<Vec<_>>();
+
+        for (index, label) in clusters {
+            writeln!(
+                self.w,
+                "{}package \"{}\" as cluster{} {{",
+                indent,
+                label.as_deref().map_or(" ".to_string(), escape),
+                index
+            )?;
+
+            self.write_package_contents(Some(index), depth + 1)?;
+
+            writeln!(self.w, "{}}}", indent)?;
+        }
+
+        for node in self.nodes.iter().filter(|node| node.parent == parent) {
+            let element = match node.shape {
+                Some("rectangle") => "rectangle",
+                Some("hexagon") => "node",
+                _ => "component",
+            };
+
+            let colors = match (node.fillcolor, node.color) {
+                (Some(fillcolor), Some(color)) => format!(" #{};line:{}", fillcolor, color),
+                (Some(fillcolor), None) => format!(" #{}", fillcolor),
+                (None, Some(color)) => format!(" #line:{}", color),
+                (None, None) => "".to_string(),
+            };
+
+            writeln!(
+                self.w,
+                "{}{} \"{}\" as {}{}",
+                indent,
+                element,
+                escape(node.label.as_deref().unwrap_or(&node.id)),
+                element_id(&node.id),
+                colors
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+//
+// PlantUML element aliases can't contain most punctuation.
+//
+fn element_id(id: &str) -> String {
+    let mangled = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+
+    format!("n{}", mangled)
+}
+
+//
+// PlantUML has no way to escape a double quote inside a quoted name, but
+// it does understand Unicode character references.
+//
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "<U+0022>")
+        .replace('\n', "\\n")
+}
+
+impl<T: Write> GraphWriter for PlantUmlWriter<'_, T> {
+    fn begin_graph(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn end_graph(&mut self) -> Result<(), Error> {
+        writeln!(self.w, "@startuml")?;
+
+        //
+        // PlantUML can only lay diagrams out left to right or top to
+        // bottom.
+        //
+        match self.options.rankdir {
+            RankDir::LeftRight | RankDir::RightLeft => writeln!(self.w, "left to right direction")?,
+            RankDir::TopBottom | RankDir::BottomTop => writeln!(self.w, "top to bottom direction")?,
+        }
+
+        writeln!(self.w)?;
+
+        self.write_package_contents(None, 0)?;
+
+        if !self.edges.is_empty() {
+            writeln!(self.w)?;
+        }
+
+        let head = if self.options.directed { ">" } else { "" };
+
+        for edge in &self.edges {
+            let arrow = match edge.color {
+                Some(color) => format!("-[#{}]-{}", color, head),
+                None => format!("--{}", head),
+            };
+
+            write!(
+                self.w,
+                "{} {} {}",
+                element_id(&edge.from),
+                arrow,
+                element_id(&edge.to)
+            )?;
+
+            match &edge.label {
+                Some(label) => writeln!(self.w, " : {}", escape(label))?,
+                None => writeln!(self.w)?,
+            }
+        }
+
+        writeln!(self.w, "@enduml")
+    }
+
+    fn blank(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn section(&mut self, _title: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn comment(&mut self, _text: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn node(&mut self, id: &str, attrs: &Attrs) -> Result<(), Error> {
+        let node = self.node_mut(id);
+
+        if attrs.label.is_some() {
+            node.label = attrs.label.clone();
+        }
+
+        node.shape = attrs.shape.or(node.shape);
+        node.color = attrs.color.or(node.color);
+        node.fillcolor = attrs.fillcolor.or(node.fillcolor);
+
+        Ok(())
+    }
+
+    fn edge(&mut self, from: &str, to: &str, attrs: &Attrs) -> Result<(), Error> {
+        //
+        // PlantUML would take undeclared aliases to be classes, and turn
+        // the whole diagram into a class diagram.
+        //
+        self.node_mut(from);
+        self.node_mut(to);
+
+        self.edges.push(PlantUmlEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: attrs.label.clone(),
+            color: attrs.color,
+        });
+
+        Ok(())
+    }
+
+    fn begin_cluster(&mut self, index: u32, label: Option<&str>) -> Result<(), Error> {
+        if !self.clusters.iter().any(|cluster| cluster.index == index) {
+            self.clusters.push(PlantUmlCluster {
+                index,
+                label: label.map(|label| label.to_string()),
+                parent: self.open_clusters.last().copied(),
+            });
+        }
+
+        self.open_clusters.push(index);
+
+        Ok(())
+    }
+
+    fn cluster_member(&mut self, id: &str) -> Result<(), Error> {
+        let cluster = self.open_clusters.last().copied();
+
+        self.node_mut(id).parent = cluster;
+
+        Ok(())
+    }
+
+    fn end_cluster(&mut self) -> Result<(), Error> {
+        self.open_clusters.pop();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::{PciAddr, PciDevice};
+
+/// What a section of the input to `Machine::parse` was recognized as.
+#[derive(Debug)]
+pub enum Section {
+    /// A DMI System Slot record (`dmidecode` type 9) with a bus address.
+    /// The slot's address and designation are included.
+    DmiSlot(PciAddr, String),
+    /// A PCI device.
+    PciDevice(PciDevice),
+    /// Anything else, such as the other records in `dmidecode` output, or
+    /// a PCI device section that can't be parsed.
+    Unknown,
+}
+
+impl Section {
+    /// Classifies a section of `lspci -nnvv` or `dmidecode` output, as
+    /// split up by `Machine::parse`.
+    pub fn parse(section: &str) -> Section {
+        static DMI_SLOT_RE: OnceLock<Regex> = OnceLock::new();
+
+        let dmi_slot_re = DMI_SLOT_RE.get_or_init(|| {
+            Regex::new(concat!(
+                r"(?s), DMI type 9, .*",
+                r"Designation: ([^\n]*)\n.*",
+                r"Bus Address: ([0-9a-f]{4,8}):([0-9a-f]{2}):([0-9a-f]{2})\.([0-7])"
+            ))
+            .unwrap()
+        });
+
+        if let Some(caps) = dmi_slot_re.captures(section) {
+            let domain = u32::from_str_radix(&caps[2], 16).unwrap();
+            let bus = u8::from_str_radix(&caps[3], 16).unwrap();
+            let device = u8::from_str_radix(&caps[4], 16).unwrap();
+            let function = u8::from_str_radix(&caps[5], 16).unwrap();
+
+            return Section::DmiSlot(
+                PciAddr::new(domain, bus, device, function),
+                caps[1].to_string(),
+            );
+        }
+
+        match PciDevice::parse(section) {
+            Some(pci_device) => Section::PciDevice(pci_device),
+            None => Section::Unknown,
+        }
+    }
+}
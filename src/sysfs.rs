@@ -0,0 +1,431 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use crate::{Machine, ParseError, PciDevice};
+
+//
+// lspci's names for the device classes that pcigraph cares about, keyed by
+// base class and subclass.  Other classes are named like lspci names
+// classes it doesn't know about.
+//
+static CLASS_NAMES: [(u16, &str); 22] = [
+    (0x0100, "SCSI storage controller"),
+    (0x0101, "IDE interface"),
+    (0x0104, "RAID bus controller"),
+    (0x0106, "SATA controller"),
+    (0x0107, "Serial Attached SCSI controller"),
+    (0x0108, "Non-Volatile memory controller"),
+    (0x0180, "Mass storage controller"),
+    (0x0200, "Ethernet controller"),
+    (0x0207, "Infiniband controller"),
+    (0x0280, "Network controller"),
+    (0x0300, "VGA compatible controller"),
+    (0x0302, "3D controller"),
+    (0x0380, "Display controller"),
+    (0x0403, "Audio device"),
+    (0x0600, "Host bridge"),
+    (0x0601, "ISA bridge"),
+    (0x0604, "PCI bridge"),
+    (0x0880, "System peripheral"),
+    (0x0c03, "USB controller"),
+    (0x0c05, "SMBus"),
+    (0x1200, "Processing accelerators"),
+    (0x1300, "Non-Essential Instrumentation"),
+];
+
+//
+// Returns lspci's name for a device class, given its base class and
+// subclass.
+//
+pub(crate) fn class_name(class: u16) -> String {
+    CLASS_NAMES.iter().find(|(id, _)| *id == class).map_or_else(
+        || format!("Class {:04x}", class),
+        |(_, name)| name.to_string(),
+    )
+}
+
+impl Machine {
+    /// Reads the PCI devices and slots found under a sysfs PCI bus
+    /// directory, normally `/sys/bus/pci`, and adds them to the machine.
+    ///
+    /// All but the first 64 bytes of each device's configuration space are
+    /// only readable by root, so without root privileges the PCI Express
+    /// capability can't be read, and no PCIe topology will be found.
+    pub fn parse_sysfs<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ParseError> {
+        let path = path.as_ref();
+
+        let cpu_path = path.join("../../devices/system/cpu");
+
+        let mut slots = Vec::new();
+
+        //
+        // Each slot directory has an "address" attribute containing the
+        // domain, bus and device number of the device in the slot.
+        //
+        if let Ok(entries) = fs::read_dir(path.join("slots")) {
+            for entry in entries {
+                let entry = entry?;
+
+                if let Ok(address) = fs::read_to_string(entry.path().join("address")) {
+                    slots.push((
+                        address.trim().to_string(),
+                        entry.file_name().to_string_lossy().into_owned(),
+                    ));
+                }
+            }
+        }
+
+        for entry in fs::read_dir(path.join("devices"))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            self.sections += 1;
+
+            let physical_slot = slots
+                .iter()
+                .find(|(address, _)| name.starts_with(&format!("{}.", address)))
+                .map(|(_, slot)| slot.as_str());
+
+            let desc = device_section(&entry.path(), &name, physical_slot, &cpu_path)?;
+
+            let pci_device = PciDevice::parse(&desc).ok_or_else(|| {
+                ParseError::MalformedSection(desc.lines().next().unwrap().to_string())
+            })?;
+
+            self.insert_device(pci_device);
+        }
+
+        Ok(())
+    }
+}
+
+fn read_attr(dir: &Path, attr: &str) -> Option<String> {
+    fs::read_to_string(dir.join(attr))
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+fn read_hex_attr(dir: &Path, attr: &str) -> Result<u32, Error> {
+    let value = fs::read_to_string(dir.join(attr))?;
+
+    value
+        .trim()
+        .strip_prefix("0x")
+        .and_then(|value| u32::from_str_radix(value, 16).ok())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: invalid value {:?}", dir.join(attr).display(), value),
+            )
+        })
+}
+
+fn read_link_attr(dir: &Path, attr: &str) -> Option<String> {
+    fs::read_link(dir.join(attr)).ok().and_then(|target| {
+        target
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    })
+}
+
+//
+// Link speeds are shown as e.g. "16.0 GT/s PCIe", or as "Unknown" for
+// devices that don't have a link.
+//
+fn read_link_speed(dir: &Path, attr: &str) -> Option<f32> {
+    read_attr(dir, attr)?
+        .split(' ')
+        .next()
+        .and_then(|speed| speed.parse::<f32>().ok())
+}
+
+//
+// PCI configuration space, as much of it as we were allowed to read.
+//
+struct Config(Vec<u8>);
+
+impl Config {
+    fn u8(&self, offset: usize) -> Option<u8> {
+        self.0.get(offset).copied()
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        Some(u16::from_le_bytes([self.u8(offset)?, self.u8(offset + 1)?]))
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        Some(u32::from_le_bytes([
+            self.u8(offset)?,
+            self.u8(offset + 1)?,
+            self.u8(offset + 2)?,
+            self.u8(offset + 3)?,
+        ]))
+    }
+
+    fn capability(&self, id: u8) -> Option<usize> {
+        //
+        // Bit 4 of the Status register indicates that there is a
+        // capability list.
+        //
+        if self.u16(0x06)? & 0x0010 == 0 {
+            return None;
+        }
+
+        let mut offset = self.u8(0x34)? as usize & !3;
+
+        //
+        // Guard against malformed lists that loop.
+        //
+        for _ in 0..48 {
+            if offset < 0x40 {
+                return None;
+            }
+
+            if self.u8(offset)? == id {
+                return Some(offset);
+            }
+
+            offset = self.u8(offset + 1)? as usize & !3;
+        }
+
+        None
+    }
+
+    fn extended_capability(&self, id: u16) -> Option<usize> {
+        let mut offset = 0x100;
+
+        for _ in 0..960 {
+            let header = self.u32(offset)?;
+
+            if header == 0 || header == 0xffffffff {
+                return None;
+            }
+
+            if header as u16 == id {
+                return Some(offset);
+            }
+
+            offset = (header >> 20) as usize & !3;
+
+            if offset < 0x100 {
+                return None;
+            }
+        }
+
+        None
+    }
+}
+
+fn aspm_states(bits: u32) -> &'static str {
+    match bits & 3 {
+        1 => "L0s",
+        2 => "L1",
+        3 => "L0s L1",
+        _ => "",
+    }
+}
+
+//
+// Synthesizes the `lspci -nnvv` section for the device in `dir`, limited
+// to the fields that PciDevice looks at.
+//
+fn device_section(
+    dir: &Path,
+    name: &str,
+    physical_slot: Option<&str>,
+    cpu_path: &Path,
+) -> Result<String, Error> {
+    let vendor_id = read_hex_attr(dir, "vendor")?;
+    let device_id = read_hex_attr(dir, "device")?;
+    let class = (read_hex_attr(dir, "class")? >> 8) as u16;
+
+    let config = Config(fs::read(dir.join("config")).unwrap_or_default());
+
+    let mut desc = String::new();
+
+    writeln!(
+        desc,
+        "{} {} [{:04x}]: Device [{:04x}:{:04x}]",
+        name,
+        class_name(class),
+        class,
+        vendor_id,
+        device_id
+    )
+    .unwrap();
+
+    if let (Ok(subsystem_vendor_id), Ok(subsystem_device_id)) = (
+        read_hex_attr(dir, "subsystem_vendor"),
+        read_hex_attr(dir, "subsystem_device"),
+    ) && subsystem_vendor_id != 0
+    {
+        writeln!(
+            desc,
+            "\tSubsystem: Device [{:04x}:{:04x}]",
+            subsystem_vendor_id, subsystem_device_id
+        )
+        .unwrap();
+    }
+
+    if let Some(physical_slot) = physical_slot {
+        writeln!(desc, "\tPhysical Slot: {}", physical_slot).unwrap();
+    }
+
+    if let Some(numa_node) = read_attr(dir, "numa_node")
+        && let Ok(numa_node) = numa_node.parse::<usize>()
+    {
+        writeln!(desc, "\tNUMA node: {}", numa_node).unwrap();
+    }
+
+    //
+    // lspci doesn't know which CPU socket a device is attached to, so this
+    // line is pcigraph's own.  The socket is that of the first of the
+    // device's local CPUs, e.g. CPU 0 for a local_cpulist of "0-15,32-47".
+    //
+    if let Some(local_cpulist) = read_attr(dir, "local_cpulist")
+        && let Some(cpu) = local_cpulist
+            .split([',', '-'])
+            .next()
+            .and_then(|cpu| cpu.parse::<usize>().ok())
+        && let Some(cpu_socket) = read_attr(
+            &cpu_path.join(format!("cpu{}/topology", cpu)),
+            "physical_package_id",
+        )
+        && let Ok(cpu_socket) = cpu_socket.parse::<usize>()
+    {
+        writeln!(desc, "\tCPU socket: {}", cpu_socket).unwrap();
+    }
+
+    if let Some(iommu_group) = read_link_attr(dir, "iommu_group") {
+        writeln!(desc, "\tIOMMU group: {}", iommu_group).unwrap();
+    }
+
+    //
+    // Header type 1 is a PCI-to-PCI bridge, which has bus numbers.
+    //
+    if config.u8(0x0e).map(|header_type| header_type & 0x7f) == Some(1)
+        && let (Some(primary), Some(secondary), Some(subordinate), Some(sec_latency)) = (
+            config.u8(0x18),
+            config.u8(0x19),
+            config.u8(0x1a),
+            config.u8(0x1b),
+        )
+    {
+        writeln!(
+            desc,
+            "\tBus: primary={:02x}, secondary={:02x}, subordinate={:02x}, sec-latency={}",
+            primary, secondary, subordinate, sec_latency
+        )
+        .unwrap();
+    }
+
+    if let Some(offset) = config.capability(0x10)
+        && let Some(flags) = config.u16(offset + 2)
+    {
+        let slot = if flags & 0x0100 != 0 { '+' } else { '-' };
+
+        let port_type = match (flags >> 4) & 0xf {
+            0x0 => "Endpoint".to_string(),
+            0x1 => "Legacy Endpoint".to_string(),
+            0x4 => format!("Root Port (Slot{})", slot),
+            0x5 => "Upstream Port".to_string(),
+            0x6 => format!("Downstream Port (Slot{})", slot),
+            0x7 => "PCI-Express to PCI/PCI-X Bridge".to_string(),
+            0x8 => "PCI/PCI-X to PCI-Express Bridge".to_string(),
+            0x9 => "Root Complex Integrated Endpoint".to_string(),
+            0xa => "Root Complex Event Collector".to_string(),
+            port_type => format!("Unknown type {}", port_type),
+        };
+
+        writeln!(
+            desc,
+            "\tCapabilities: [{:02x}] Express (v{}) {}, MSI {:02x}",
+            offset,
+            flags & 0xf,
+            port_type,
+            (flags >> 9) & 0x1f
+        )
+        .unwrap();
+
+        let lnk_cap = config.u32(offset + 0x0c).unwrap_or(0);
+
+        if let Some(speed) = read_link_speed(dir, "max_link_speed")
+            && let Some(width) = read_attr(dir, "max_link_width")
+        {
+            write!(
+                desc,
+                "\t\tLnkCap:\tPort #{}, Speed {}GT/s, Width x{}, ASPM ",
+                lnk_cap >> 24,
+                speed,
+                width
+            )
+            .unwrap();
+
+            match aspm_states(lnk_cap >> 10) {
+                "" => writeln!(desc, "not supported,").unwrap(),
+                states => writeln!(desc, "{},", states).unwrap(),
+            }
+        }
+
+        if let Some(lnk_ctl) = config.u16(offset + 0x10) {
+            match aspm_states(lnk_ctl as u32) {
+                "" => writeln!(desc, "\t\tLnkCtl:\tASPM Disabled;").unwrap(),
+                states => writeln!(desc, "\t\tLnkCtl:\tASPM {} Enabled;", states).unwrap(),
+            }
+        }
+
+        if let Some(speed) = read_link_speed(dir, "current_link_speed")
+            && let Some(width) = read_attr(dir, "current_link_width")
+        {
+            let max_speed = read_link_speed(dir, "max_link_speed").unwrap_or(speed);
+            let max_width = read_attr(dir, "max_link_width")
+                .and_then(|max_width| max_width.parse::<u8>().ok())
+                .unwrap_or(0);
+
+            writeln!(
+                desc,
+                "\t\tLnkSta:\tSpeed {}GT/s{}, Width x{}{}",
+                speed,
+                if speed < max_speed {
+                    " (downgraded)"
+                } else {
+                    ""
+                },
+                width,
+                if width.parse::<u8>().unwrap_or(0) < max_width {
+                    " (downgraded)"
+                } else {
+                    ""
+                }
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(offset) = config.extended_capability(0x0003)
+        && let (Some(low), Some(high)) = (config.u32(offset + 4), config.u32(offset + 8))
+    {
+        let serial_number = ((high as u64) << 32) | low as u64;
+
+        writeln!(
+            desc,
+            "\tCapabilities: [{:03x} v1] Device Serial Number {}",
+            offset,
+            serial_number
+                .to_be_bytes()
+                .map(|byte| format!("{:02x}", byte))
+                .join("-")
+        )
+        .unwrap();
+    }
+
+    if let Some(driver) = read_link_attr(dir, "driver") {
+        writeln!(desc, "\tKernel driver in use: {}", driver).unwrap();
+    }
+
+    Ok(desc)
+}
@@ -0,0 +1,100 @@
+use std::{
+    collections::BTreeSet,
+    io::{Error, Write},
+};
+
+use crate::{Machine, PciDevice};
+
+impl Machine {
+    /// Writes the machine's PCI topology to `w` as an indented text tree,
+    /// with one tree per root port.
+    pub fn write_tree<T: Write>(&self, w: &mut T) -> Result<(), Error> {
+        //
+        // As in the graph, root ports without a type 1 configuration space
+        // header have nothing behind them, and are left out.
+        //
+        for root_port in self
+            .root_ports()
+            .filter(|root_port| self.secondary_bus(root_port).is_some())
+        {
+            writeln!(w, "{}", self.tree_line(root_port))?;
+
+            self.write_subtree(w, root_port, "", &mut BTreeSet::new())?;
+        }
+
+        Ok(())
+    }
+
+    fn tree_line(&self, dev: &PciDevice) -> String {
+        let mut line = format!("{} {}", dev.addr(), self.device_name(dev));
+
+        if let Some(kernel_driver) = dev.kernel_driver() {
+            line.push_str(&format!(" ({})", kernel_driver));
+        }
+
+        line
+    }
+
+    fn write_subtree<T: Write>(
+        &self,
+        w: &mut T,
+        parent_dev: &PciDevice,
+        indent: &str,
+        buses: &mut BTreeSet<u8>,
+    ) -> Result<(), Error> {
+        let Some(bus) = self.secondary_bus(parent_dev) else {
+            return Ok(());
+        };
+
+        //
+        // Guard against bus ranges that overlap or loop, which would
+        // otherwise make this recurse forever.
+        //
+        if !buses.insert(bus) {
+            return Ok(());
+        }
+
+        let bus_devices = self.child_devices(parent_dev);
+
+        for (index, dev_addr) in bus_devices.iter().enumerate() {
+            let dev = self.pci_devices.get(dev_addr).unwrap();
+
+            let last = index == bus_devices.len() - 1;
+
+            let mut line = self.tree_line(dev);
+
+            //
+            // LnkSta shows downgraded links on two lines, as in the graph.
+            //
+            if let Some(lnk_sta) = dev.lnk_sta() {
+                line.push_str(&format!(" {}", lnk_sta.to_string().replace('\n', " ")));
+            }
+
+            writeln!(
+                w,
+                "{}{}{}",
+                indent,
+                if last { "`-- " } else { "|-- " },
+                line
+            )?;
+
+            //
+            // Guard against bridges that claim their own bus as their
+            // secondary bus.
+            //
+            if self
+                .secondary_bus(dev)
+                .is_some_and(|bus| bus != dev_addr.bus())
+            {
+                self.write_subtree(
+                    w,
+                    dev,
+                    &format!("{}{}", indent, if last { "    " } else { "|   " }),
+                    buses,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
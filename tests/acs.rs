@@ -0,0 +1,68 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn root_port(acs_ctl: &str) -> String {
+    format!(
+        concat!(
+            "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 8GT/s, Width x4, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x4\n",
+            "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+            "\tCapabilities: [220 v1] Access Control Services\n",
+            "\t\tACSCap:\tSrcValid+ TransBlk+ ReqRedir+ CmpltRedir+ UpstreamFwd+ ",
+            "EgressCtrl- DirectTrans-\n",
+            "\t\tACSCtl:\t{}\n",
+            "\n",
+            "01:00.0 Ethernet controller [0200]: Intel Corporation Device [8086:1593]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 8GT/s, Width x4, ASPM L1, Exit Latency L1 <64us\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x4\n",
+        ),
+        acs_ctl
+    )
+}
+
+fn acs_enabled(machine: &Machine) -> Option<bool> {
+    machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0, 1, 0))
+        .next()
+        .unwrap()
+        .acs_enabled()
+}
+
+#[test]
+fn acs_capability() {
+    //
+    // TransBlk isn't needed for isolation, and EgressCtrl and DirectTrans
+    // aren't supported here.
+    //
+    let enabled = common::machine(&root_port(
+        "SrcValid+ TransBlk- ReqRedir+ CmpltRedir+ UpstreamFwd+ EgressCtrl- DirectTrans-",
+    ));
+
+    assert_eq!(acs_enabled(&enabled), Some(true));
+
+    let disabled = common::machine(&root_port(
+        "SrcValid- TransBlk- ReqRedir- CmpltRedir- UpstreamFwd- EgressCtrl- DirectTrans-",
+    ));
+
+    assert_eq!(acs_enabled(&disabled), Some(false));
+
+    assert!(
+        common::graph(&disabled, &GraphOptions::default())
+            .contains("label=\"Root port\\n0000:00:01.0\\nACS disabled\"")
+    );
+}
+
+#[test]
+fn no_acs_capability() {
+    let machine = common::machine(concat!(
+        "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+        "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+        "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+    ));
+
+    assert_eq!(acs_enabled(&machine), None);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+fn render(include: &[&str], exclude: &[&str]) -> String {
+    let machine = common::fixture("dual-socket");
+
+    let options = GraphOptions {
+        include: include.iter().map(|prefix| prefix.to_string()).collect(),
+        exclude: exclude.iter().map(|prefix| prefix.to_string()).collect(),
+        show_orphans: true,
+        ..GraphOptions::default()
+    };
+
+    common::graph(&machine, &options)
+}
+
+#[test]
+fn include() {
+    let output = render(&["0000:97:"], &[]);
+
+    assert!(output.contains("# root port 0000:97:02.0"));
+    assert!(output.contains("# root port 0000:97:04.0"));
+    assert!(!output.contains("# root port 0000:00:1c.0"));
+    assert!(!output.contains("# root port 0000:17:02.0"));
+    assert!(output.contains("label=\"NUMA node #1\";"));
+    assert!(!output.contains("unreachable"));
+}
+
+#[test]
+fn include_below_root_port() {
+    let output = render(&["0000:18:00.0"], &[]);
+
+    assert!(output.contains("# root port 0000:17:02.0"));
+    assert!(output.contains("\"0000:17:02.0_18\" -- \"0000:18:00.0\""));
+    assert!(!output.contains("# root port 0000:97:"));
+}
+
+#[test]
+fn exclude() {
+    let output = render(&[], &["0000:97:04.0", "0000:00:"]);
+
+    assert!(output.contains("# root port 0000:17:02.0"));
+    assert!(output.contains("# root port 0000:97:02.0"));
+    assert!(!output.contains("# root port 0000:97:04.0"));
+    assert!(!output.contains("\"0000:00:1c.0\""));
+    assert!(!output.contains("unreachable"));
+}
+
+#[test]
+fn include_and_exclude() {
+    let output = render(&["0000:97:"], &["0000:97:04.0"]);
+
+    assert!(output.contains("# root port 0000:97:02.0"));
+    assert!(!output.contains("# root port 0000:97:04.0"));
+    assert!(!output.contains("# root port 0000:17:02.0"));
+}
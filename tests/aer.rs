@@ -0,0 +1,88 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn capture(unsupported_request: char) -> String {
+    format!(
+        concat!(
+            "0000:00:01.1 PCI bridge [0604]: Advanced Micro Devices, Inc. [AMD] Starship/Matisse GPP Bridge [1022:1483] (prog-if 00 [Normal decode])\n",
+            "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+            "\tCapabilities: [58] Express (v2) Root Port (Slot+), MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x4, ASPM L1, Exit Latency L1 <64us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x4\n",
+            "\n",
+            "0000:01:00.0 Non-Volatile memory controller [0108]: Samsung Electronics Co Ltd NVMe SSD Controller PM173X [144d:a824]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x4, ASPM not supported\n",
+            "\t\tLnkSta:\tSpeed 16GT/s (ok), Width x4 (ok)\n",
+            "\tCapabilities: [100 v2] Advanced Error Reporting\n",
+            "\t\tUESta:\tDLP- SDES- TLP- FCP- CmpltTO- CmpltAbrt- UnxCmplt- RxOF- MalfTLP- ECRC- UnsupReq{} ACSViol-\n",
+            "\t\tUEMsk:\tDLP- SDES- TLP- FCP- CmpltTO- CmpltAbrt- UnxCmplt- RxOF- MalfTLP- ECRC- UnsupReq- ACSViol-\n",
+            "\t\tUESvrt:\tDLP+ SDES+ TLP- FCP+ CmpltTO- CmpltAbrt- UnxCmplt- RxOF+ MalfTLP+ ECRC- UnsupReq- ACSViol-\n",
+            "\t\tCESta:\tRxErr- BadTLP- BadDLLP- Rollover- Timeout- AdvNonFatalErr-\n",
+            "\t\tCEMsk:\tRxErr- BadTLP- BadDLLP- Rollover- Timeout- AdvNonFatalErr+\n",
+            "\t\tAERCap:\tFirst Error Pointer: 00, ECRCGenCap+ ECRCGenEn- ECRCChkCap+ ECRCChkEn-\n",
+        ),
+        unsupported_request
+    )
+}
+
+fn aer_has_errors(machine: &Machine, addr: PciAddr) -> Option<bool> {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+        .aer_has_errors()
+}
+
+#[test]
+fn uncorrectable_error() {
+    let machine = common::machine(&capture('+'));
+
+    assert_eq!(
+        aer_has_errors(&machine, PciAddr::new(0, 1, 0, 0)),
+        Some(true)
+    );
+
+    let output = common::graph(&machine, &GraphOptions::default());
+
+    assert!(output.contains(
+        "\"0000:00:01.1\" -- \"0000:01:00.0\" [ label=\"16GT/s x4\\n(AER errors)\" color=red ];"
+    ));
+    assert!(output.contains("\\n0000:01:00.0\\nAER errors\""));
+}
+
+#[test]
+fn all_clear() {
+    let machine = common::machine(&capture('-'));
+
+    assert_eq!(
+        aer_has_errors(&machine, PciAddr::new(0, 1, 0, 0)),
+        Some(false)
+    );
+
+    let output = common::graph(&machine, &GraphOptions::default());
+
+    assert!(output.contains("\"0000:00:01.1\" -- \"0000:01:00.0\" [ label=\"16GT/s x4\" ];"));
+    assert!(!output.contains("AER errors"));
+}
+
+#[test]
+fn without_aer() {
+    let machine = common::machine(&capture('-'));
+
+    assert_eq!(aer_has_errors(&machine, PciAddr::new(0, 0, 1, 1)), None);
+}
+
+#[test]
+fn wrapped_status() {
+    let input = capture('-').replace(
+        "\t\tCESta:\tRxErr- BadTLP- BadDLLP- Rollover- Timeout- AdvNonFatalErr-\n",
+        "\t\tCESta:\tRxErr- BadTLP- BadDLLP- Rollover- Timeout-\n\t\t\tAdvNonFatalErr+\n",
+    );
+
+    assert_eq!(
+        aer_has_errors(&common::machine(&input), PciAddr::new(0, 1, 0, 0)),
+        Some(true)
+    );
+}
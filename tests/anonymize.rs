@@ -0,0 +1,118 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine};
+
+fn serial_numbers(machine: &Machine) -> Vec<(String, Option<u64>)> {
+    machine
+        .devices_where(|_| true)
+        .map(|dev| (dev.addr().to_string(), dev.serial_number()))
+        .collect()
+}
+
+#[test]
+fn shared_serial_numbers_share_a_pseudonym() {
+    let mut machine = common::fixture("pcie-switch");
+
+    assert!(
+        serial_numbers(&machine)
+            .iter()
+            .any(|(_, serial_number)| *serial_number == Some(0x00805e1000000001))
+    );
+
+    machine.anonymize_serial_numbers();
+
+    assert_eq!(
+        serial_numbers(&machine),
+        [
+            ("0000:40:01.1".to_string(), None),
+            ("0000:41:00.0".to_string(), Some(1)),
+            ("0000:42:00.0".to_string(), Some(1)),
+            ("0000:42:01.0".to_string(), Some(1)),
+            ("0000:43:00.0".to_string(), None),
+            ("0000:44:00.0".to_string(), None),
+        ]
+    );
+
+    //
+    // The switch is still drawn as a single cluster.
+    //
+    assert_eq!(
+        common::graph(&machine, &GraphOptions::default())
+            .matches("label=\"PCIe switch\"")
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn distinct_serial_numbers_get_distinct_pseudonyms() {
+    let endpoint = |bus: u8, serial_number: &str| {
+        format!(
+            concat!(
+                "{:02x}:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+                "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+                "\tCapabilities: [100 v1] Device Serial Number {}\n",
+                "\tCapabilities: [e0] Vital Product Data\n",
+                "\t\tProduct Name: NVMe SSD\n",
+                "\t\tRead-only fields:\n",
+                "\t\t\t[SN] Serial number: S4EWNX0R123456\n",
+                "\n",
+            ),
+            bus, serial_number
+        )
+    };
+
+    let input = [
+        endpoint(1, "38-4b-5b-12-34-56-78-9a"),
+        endpoint(2, "ab-cd-ef-01-23-45-67-89"),
+        endpoint(3, "38-4b-5b-12-34-56-78-9a"),
+    ]
+    .concat();
+
+    let mut machine = common::machine(&input);
+    machine.anonymize_serial_numbers();
+
+    assert_eq!(
+        serial_numbers(&machine)
+            .into_iter()
+            .map(|(_, serial_number)| serial_number)
+            .collect::<Vec<_>>(),
+        [Some(1), Some(2), Some(1)]
+    );
+
+    let mut output = Vec::new();
+
+    machine.write_json(&mut output).unwrap();
+
+    assert!(!String::from_utf8(output).unwrap().contains("384b5b"));
+}
+
+#[test]
+fn addresses() {
+    let original = common::fixture("dual-socket");
+
+    let mut machine = common::fixture("dual-socket");
+
+    machine.anonymize_addresses();
+
+    let addrs = machine
+        .devices_where(|_| true)
+        .map(|dev| dev.addr().to_string())
+        .collect::<Vec<_>>();
+
+    assert!(addrs.iter().all(|addr| !addr.contains(":97:")));
+    assert_eq!(addrs.len(), original.devices_where(|_| true).count());
+
+    assert!(machine.check_graph(&GraphOptions::default()).is_empty());
+    assert!(machine.unreachable_devices().is_empty());
+
+    assert_eq!(
+        common::graph(&machine, &GraphOptions::default())
+            .matches(" -- ")
+            .count(),
+        common::graph(&original, &GraphOptions::default())
+            .matches(" -- ")
+            .count()
+    );
+    assert_eq!(machine.slots().count(), original.slots().count());
+}
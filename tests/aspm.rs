@@ -0,0 +1,89 @@
+mod common;
+
+use pcigraph::{AspmState, GraphOptions, Machine, PciAddr};
+
+fn endpoint(bus: u8, aspm_capable: &str, aspm_enabled: &str) -> String {
+    format!(
+        concat!(
+            "00:{bus:02x}.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tBus: primary=00, secondary={bus:02x}, subordinate={bus:02x}, sec-latency=0\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 8GT/s, Width x4, ASPM L0s L1, ",
+            "Exit Latency L0s <1us, L1 <8us\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x4\n",
+            "\n",
+            "{bus:02x}:00.0 Non-Volatile memory controller [0108]: Acme Device [1234:5678]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 8GT/s, Width x4, ASPM {aspm_capable}, ",
+            "Exit Latency L0s <1us, L1 <8us\n",
+            "\t\tLnkCtl:\tASPM {aspm_enabled}; RCB 64 bytes, Disabled- CommClk+\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x4\n",
+            "\n",
+        ),
+        bus = bus,
+        aspm_capable = aspm_capable,
+        aspm_enabled = aspm_enabled
+    )
+}
+
+fn machine() -> Machine {
+    let input = [
+        endpoint(1, "not supported", "Disabled"),
+        endpoint(2, "L0s L1", "L0s Enabled"),
+        endpoint(3, "L1", "L1 Enabled"),
+        endpoint(4, "L0s L1", "L0s L1 Enabled"),
+    ]
+    .concat();
+
+    common::machine(&input)
+}
+
+#[test]
+fn aspm_states() {
+    let machine = machine();
+
+    let aspm = |bus| {
+        let dev = machine
+            .devices_where(|dev| dev.addr() == PciAddr::new(0, bus, 0, 0))
+            .next()
+            .unwrap();
+
+        (dev.aspm_capable(), dev.aspm_enabled())
+    };
+
+    assert_eq!(aspm(1), (Some(AspmState::None), Some(AspmState::None)));
+    assert_eq!(aspm(2), (Some(AspmState::L0sL1), Some(AspmState::L0s)));
+    assert_eq!(aspm(3), (Some(AspmState::L1), Some(AspmState::L1)));
+    assert_eq!(aspm(4), (Some(AspmState::L0sL1), Some(AspmState::L0sL1)));
+
+    //
+    // Root ports without LnkCtl have no enabled states.
+    //
+    let port = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0, 1, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(port.aspm_capable(), Some(AspmState::L0sL1));
+    assert_eq!(port.aspm_enabled(), None);
+}
+
+#[test]
+fn show_aspm() {
+    let render = |show_aspm| {
+        let options = GraphOptions {
+            show_aspm,
+            ..GraphOptions::default()
+        };
+
+        common::graph(&machine(), &options)
+    };
+
+    let output = render(true);
+
+    assert!(output.contains("[ label=\"8GT/s x4\\nASPM off\" ];\n"));
+    assert!(output.contains("[ label=\"8GT/s x4\\nASPM L0s (cap L0s L1)\" ];\n"));
+    assert!(output.contains("[ label=\"8GT/s x4\\nASPM L1\\nL1 exit <8us\" ];\n"));
+
+    assert!(!render(false).contains("ASPM"));
+}
@@ -0,0 +1,31 @@
+use pcigraph::{LnkCap, LnkSta};
+
+fn assert_gbps(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 0.0001,
+        "{} GB/s, expected {} GB/s",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn encoding_8b_10b() {
+    assert_gbps(LnkCap::new(2.5, 1).bandwidth_gbps(), 0.25);
+    assert_gbps(LnkCap::new(5.0, 4).bandwidth_gbps(), 2.0);
+    assert_gbps(LnkSta::new(2.5, 1, false).bandwidth_gbps(), 0.25);
+}
+
+#[test]
+fn encoding_128b_130b() {
+    assert_gbps(LnkCap::new(8.0, 16).bandwidth_gbps(), 15.753846);
+    assert_gbps(LnkCap::new(16.0, 4).bandwidth_gbps(), 7.876923);
+    assert_gbps(LnkCap::new(16.0, 16).bandwidth_gbps(), 31.507692);
+    assert_gbps(LnkSta::new(8.0, 16, false).bandwidth_gbps(), 15.753846);
+    assert_gbps(LnkSta::new(16.0, 4, false).bandwidth_gbps(), 7.876923);
+}
+
+#[test]
+fn link_down() {
+    assert_gbps(LnkSta::new(2.5, 0, false).bandwidth_gbps(), 0.0);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use pcigraph::{GraphOptions, LnkCap, LnkSta, Machine, PciAddr, PciDevice, PortType};
+
+fn nvme(device: u8, width: u8) -> PciDevice {
+    PciDevice::builder(PciAddr::new(0, 1, device, 0), 0x1234, 0x5678)
+        .class(0x0108)
+        .physical_slot("3")
+        .port_type(PortType::Endpoint)
+        .lnk_cap(LnkCap::new(16.0, width))
+        .lnk_sta(LnkSta::new(16.0, width, false))
+        .build()
+}
+
+//
+// Renders a x16 slot with drives of the given link widths behind it.
+//
+fn render(widths: &[u8]) -> String {
+    let mut machine = Machine::default();
+
+    machine.add_device(
+        PciDevice::builder(PciAddr::new(0, 0, 1, 0), 0x8086, 0x1901)
+            .port_type(PortType::RootPort)
+            .buses(1, 1)
+            .lnk_cap(LnkCap::new(16.0, 16))
+            .lnk_sta(LnkSta::new(16.0, 4, false))
+            .build(),
+    );
+
+    for (device, width) in widths.iter().enumerate() {
+        machine.add_device(nvme(device as u8, *width));
+    }
+
+    common::graph(&machine, &GraphOptions::default())
+}
+
+fn slot_label(output: &str) -> &str {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("\t\"0000:00:01.0_01\" [ label=\""))
+        .and_then(|rest| rest.split_once("\" shape=rectangle"))
+        .map(|(label, _)| label)
+        .unwrap()
+}
+
+#[test]
+fn evenly_split() {
+    assert_eq!(
+        slot_label(&render(&[4, 4, 4, 4])),
+        "Slot 3\\nbifurcated 4×4"
+    );
+}
+
+#[test]
+fn unevenly_split() {
+    assert_eq!(slot_label(&render(&[8, 4, 4])), "Slot 3\\nbifurcated 8+4+4");
+}
+
+#[test]
+fn not_bifurcated() {
+    //
+    // Widths that don't add up to the slot's width, e.g. because a
+    // device's link trained narrower than it should have, or a single
+    // device, don't make the slot count as bifurcated.
+    //
+    assert_eq!(slot_label(&render(&[4, 4])), "Slot 3");
+    assert_eq!(slot_label(&render(&[16])), "Slot 3");
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+const INPUT: &str = concat!(
+    "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:1901]\n",
+    "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+    "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+    "\t\tLnkCap:\tPort #1, Speed 8GT/s, Width x4, ASPM not supported\n",
+    "\n",
+    "\t\tLnkSta:\tSpeed 8GT/s, Width x4\n",
+    "\tKernel driver in use: pcieport\n",
+    "\n",
+    "01:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+    "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+    "\n",
+    "\n",
+    "\t\tLnkSta:\tSpeed 8GT/s, Width x4\n",
+    "\n",
+    "\tKernel driver in use: nvme\n",
+    "\n",
+);
+
+fn machine() -> Machine {
+    common::machine(INPUT)
+}
+
+#[test]
+fn continuation_chunks() {
+    let machine = machine();
+
+    assert_eq!(machine.devices_where(|_| true).count(), 2);
+
+    let port = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0, 1, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(port.lnk_sta().map(|lnk_sta| lnk_sta.width()), Some(4));
+    assert_eq!(port.kernel_driver(), Some("pcieport"));
+
+    let nvme = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 1, 0, 0))
+        .next()
+        .unwrap();
+
+    assert!(nvme.is_endpoint());
+    assert_eq!(nvme.lnk_sta().map(|lnk_sta| lnk_sta.speed()), Some(8.0));
+    assert_eq!(nvme.kernel_driver(), Some("nvme"));
+}
+
+#[test]
+fn render() {
+    let output = common::graph(&machine(), &GraphOptions::default());
+
+    assert!(output.contains("\t\"0000:00:01.0\" -- \"0000:01:00.0\" [ label=\"8GT/s x4\" ];\n"));
+    assert!(
+        output.contains("\t\"0000:01:00.0\" [ label=\"Acme NVMe SSD\\nnvme\\n0000:01:00.0\" ];\n")
+    );
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn machine(input: &str, bridge_paths: bool) -> Machine {
+    let mut machine = Machine::default();
+
+    machine.set_bridge_paths(bridge_paths);
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    machine
+}
+
+//
+// Leaves out the bridges' bus numbers, so that the topology can only be
+// found from the paths.
+//
+fn without_bus_lines(input: &str) -> String {
+    input
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with("\tBus: "))
+        .collect()
+}
+
+#[test]
+fn bridge_path() {
+    let machine = machine(
+        &read_to_string("tests/fixtures/bridge-paths.txt").unwrap(),
+        false,
+    );
+
+    let dev = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0x43, 0, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(
+        dev.bridge_path(),
+        [
+            PciAddr::new(0, 0x40, 1, 1),
+            PciAddr::new(0, 0x41, 0, 0),
+            PciAddr::new(0, 0x42, 0, 0),
+        ]
+    );
+
+    let root_port = machine.root_ports().next().unwrap();
+
+    assert_eq!(root_port.addr(), PciAddr::new(0, 0x40, 1, 1));
+    assert!(root_port.bridge_path().is_empty());
+}
+
+#[test]
+fn paths_match_bus_numbers() {
+    let expected = common::graph(
+        &machine(
+            &read_to_string("tests/fixtures/pcie-switch.txt").unwrap(),
+            false,
+        ),
+        &GraphOptions::default(),
+    );
+
+    let input = read_to_string("tests/fixtures/bridge-paths.txt").unwrap();
+
+    assert_eq!(
+        common::graph(&machine(&input, false), &GraphOptions::default()),
+        expected
+    );
+    assert_eq!(
+        common::graph(&machine(&input, true), &GraphOptions::default()),
+        expected
+    );
+}
+
+#[test]
+fn paths_without_bus_numbers() {
+    let expected = common::graph(
+        &machine(
+            &read_to_string("tests/fixtures/pcie-switch.txt").unwrap(),
+            false,
+        ),
+        &GraphOptions::default(),
+    );
+
+    let input = without_bus_lines(&read_to_string("tests/fixtures/bridge-paths.txt").unwrap());
+
+    assert_eq!(
+        common::graph(&machine(&input, true), &GraphOptions::default()),
+        expected
+    );
+    assert_ne!(
+        common::graph(&machine(&input, false), &GraphOptions::default()),
+        expected
+    );
+
+    //
+    // The devices are described as they were parsed.
+    //
+    let machine = machine(&input, true);
+    let root_port = machine.root_ports().next().unwrap();
+
+    assert_eq!(root_port.secondary_bus(), None);
+    assert!(!root_port.desc().contains("\tBus: "));
+}
+
+#[test]
+fn paths_override_bus_numbers() {
+    let expected = common::graph(
+        &machine(
+            &read_to_string("tests/fixtures/pcie-switch.txt").unwrap(),
+            false,
+        ),
+        &GraphOptions::default(),
+    );
+
+    let input = read_to_string("tests/fixtures/bridge-paths.txt")
+        .unwrap()
+        .replace(
+            "secondary=43, subordinate=43",
+            "secondary=44, subordinate=44",
+        );
+
+    let machine = machine(&input, true);
+
+    assert_eq!(common::graph(&machine, &GraphOptions::default()), expected);
+
+    let port = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0x42, 0, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(port.secondary_bus(), Some(0x44));
+    assert!(
+        port.desc()
+            .contains("\tBus: primary=42, secondary=44, subordinate=44, sec-latency=0\n")
+    );
+}
+
+#[test]
+fn paths_survive_anonymized_addresses() {
+    let input = without_bus_lines(&read_to_string("tests/fixtures/bridge-paths.txt").unwrap());
+
+    let original = machine(&input, true);
+
+    let mut machine = machine(&input, true);
+
+    machine.anonymize_addresses();
+
+    assert!(machine.unreachable_devices().is_empty());
+    assert_eq!(
+        common::graph(&machine, &GraphOptions::default())
+            .matches(" -- ")
+            .count(),
+        common::graph(&original, &GraphOptions::default())
+            .matches(" -- ")
+            .count()
+    );
+}
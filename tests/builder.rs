@@ -0,0 +1,97 @@
+mod common;
+
+use pcigraph::{GraphOptions, LnkCap, LnkSta, Machine, PciAddr, PciDevice, PortType};
+
+fn root_port() -> PciDevice {
+    PciDevice::builder(PciAddr::new(0, 0, 1, 0), 0x8086, 0x1901)
+        .port_type(PortType::RootPort)
+        .buses(1, 1)
+        .lnk_cap(LnkCap::new(16.0, 16).with_port(1))
+        .lnk_sta(LnkSta::new(8.0, 16, true))
+        .numa_node(1)
+        .kernel_driver("pcieport")
+        .build()
+}
+
+fn endpoint() -> PciDevice {
+    PciDevice::builder(PciAddr::new(0, 1, 0, 0), 0x15b3, 0x101b)
+        .class(0x0207)
+        .port_type(PortType::Endpoint)
+        .lnk_cap(LnkCap::new(16.0, 16))
+        .lnk_sta(LnkSta::new(8.0, 16, true))
+        .numa_node(1)
+        .serial_number(0x0011223344556677)
+        .kernel_driver("mlx5_core")
+        .build()
+}
+
+#[test]
+fn accessors() {
+    let port = root_port();
+
+    assert_eq!(port.addr(), PciAddr::new(0, 0, 1, 0));
+    assert_eq!(port.vendor_id(), 0x8086);
+    assert_eq!(port.device_id(), 0x1901);
+    assert_eq!(port.class_code(), Some(0x0604));
+    assert!(port.is_root_port());
+    assert_eq!(port.secondary_bus(), Some(1));
+    assert_eq!(port.subordinate_bus(), Some(1));
+    assert_eq!(port.lnk_cap().unwrap().port(), Some(1));
+    assert_eq!(port.lnk_cap().unwrap().speed(), 16.0);
+    assert!(port.lnk_sta().unwrap().is_downgraded());
+    assert_eq!(port.numa_node(), Some(1));
+    assert_eq!(port.kernel_driver(), Some("pcieport"));
+
+    let dev = endpoint();
+
+    assert!(dev.is_endpoint());
+    assert_eq!(dev.device_class(), Some("Infiniband controller"));
+    assert_eq!(dev.serial_number(), Some(0x0011223344556677));
+    assert_eq!(dev.secondary_bus(), None);
+}
+
+#[test]
+fn lnk_cap_without_port() {
+    let lnk_cap = endpoint().lnk_cap().unwrap();
+
+    assert_eq!(lnk_cap.port(), None);
+    assert_eq!(lnk_cap.speed(), 16.0);
+    assert_eq!(lnk_cap.width(), 16);
+}
+
+#[test]
+fn conventional_pci_device() {
+    let dev = PciDevice::builder(PciAddr::new(0, 2, 3, 0), 0x1234, 0x5678).build();
+
+    assert!(dev.is_legacy_pci_device());
+    assert_eq!(dev.class_code(), Some(0x0000));
+    assert_eq!(dev.lnk_cap().map(|lnk_cap| lnk_cap.width()), None);
+}
+
+#[test]
+fn render() {
+    let mut machine = Machine::default();
+
+    machine.add_device(root_port());
+    machine.add_device(endpoint());
+
+    assert!(machine.duplicate_devices().is_empty());
+
+    let output = common::graph(&machine, &GraphOptions::default());
+
+    assert!(output.contains(concat!(
+        "\t\"0000:00:01.0\" -- \"0000:01:00.0\" ",
+        "[ label=\"8GT/s x16\\n(downgraded) (cap 16GT/s x16)\" color=red ];\n"
+    )));
+    assert!(output.contains("label=\"PCH (on NUMA node #1)\";"));
+}
+
+#[test]
+fn add_device_replaces_duplicates() {
+    let mut machine = Machine::default();
+
+    machine.add_device(endpoint());
+    machine.add_device(endpoint());
+
+    assert_eq!(machine.duplicate_devices(), [PciAddr::new(0, 1, 0, 0)]);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+fn function(function: u8) -> String {
+    format!(
+        concat!(
+            "01:00.{} Ethernet controller [0200]: Intel Corporation Ethernet Controller X710 [8086:1572]\n",
+            "\tCapabilities: [a0] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 8GT/s, Width x8, ASPM L1, Exit Latency L1 <16us\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x8\n",
+            "\n",
+        ),
+        function
+    )
+}
+
+const ROOT_PORT: &str = concat!(
+    "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+    "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+    "\t\tLnkCap:\tPort #1, Speed 8GT/s, Width x8, ASPM L1, Exit Latency L1 <4us\n",
+    "\t\tLnkSta:\tSpeed 8GT/s, Width x8\n",
+    "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+    "\n",
+);
+
+fn edges(input: &str) -> Vec<String> {
+    let machine = common::machine(input);
+
+    common::graph(&machine, &GraphOptions::default())
+        .lines()
+        .filter(|line| line.contains(" -- "))
+        .map(|line| {
+            line.trim()
+                .trim_end_matches(';')
+                .split(" [")
+                .next()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn functions_are_chained_by_devfn() {
+    let expected = [
+        "\"0000:00:01.0\" -- \"0000:01:00.0\"",
+        "\"0000:01:00.0\" -- \"0000:01:00.1\"",
+        "\"0000:01:00.1\" -- \"0000:01:00.4\"",
+    ];
+
+    let in_order = [ROOT_PORT.to_string(), function(0), function(1), function(4)].concat();
+
+    assert_eq!(edges(&in_order), expected);
+
+    //
+    // The endpoints can come before their parent port, and out of order.
+    //
+    let shuffled = [function(4), function(0), ROOT_PORT.to_string(), function(1)].concat();
+
+    assert_eq!(edges(&shuffled), expected);
+}
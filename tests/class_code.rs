@@ -0,0 +1,85 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn endpoint(bus: u8, class: &str, class_code: &str) -> String {
+    format!(
+        concat!(
+            "00:{bus:02x}.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 8GT/s, Width x4, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x4\n",
+            "\tBus: primary=00, secondary={bus:02x}, subordinate={bus:02x}, sec-latency=0\n",
+            "\n",
+            "{bus:02x}:00.0 {class} [{class_code}]: Acme Device [1234:5678]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x4\n",
+            "\n",
+        ),
+        bus = bus,
+        class = class,
+        class_code = class_code
+    )
+}
+
+fn machine() -> Machine {
+    //
+    // Class names as a localized or outdated pci.ids could spell them.
+    //
+    let input = [
+        endpoint(1, "Contrôleur NVMe", "0108"),
+        endpoint(2, "Ethernet-Controller", "0200"),
+        endpoint(3, "3D-Controller", "0302"),
+        endpoint(4, "Processing accelerators", "1200"),
+        endpoint(5, "Signal processing controller", "1180"),
+    ]
+    .concat();
+
+    common::machine(&input)
+}
+
+#[test]
+fn class_codes() {
+    let machine = machine();
+
+    let class_code = |bus| {
+        machine
+            .devices_where(|dev| dev.addr() == PciAddr::new(0, bus, 0, 0))
+            .next()
+            .unwrap()
+            .class_code()
+    };
+
+    assert_eq!(class_code(1), Some(0x0108));
+    assert_eq!(class_code(2), Some(0x0200));
+    assert_eq!(class_code(3), Some(0x0302));
+    assert_eq!(class_code(4), Some(0x1200));
+    assert_eq!(class_code(5), Some(0x1180));
+}
+
+#[test]
+fn colors_by_class_code() {
+    let options = GraphOptions {
+        color_by_class: true,
+        ..GraphOptions::default()
+    };
+
+    let output = common::graph(&machine(), &options);
+
+    let fillcolor = |bus: u8| {
+        let node = format!("\t\"0000:{:02x}:00.0\" [ ", bus);
+
+        output
+            .lines()
+            .find(|line| line.starts_with(&node))
+            .and_then(|line| line.split("fillcolor=").nth(1))
+            .map(|color| color.trim_end_matches(" ];").to_string())
+            .unwrap()
+    };
+
+    assert_eq!(fillcolor(1), "lightblue");
+    assert_eq!(fillcolor(2), "palegreen");
+    assert_eq!(fillcolor(3), "orange");
+    assert_eq!(fillcolor(4), "orange");
+    assert_eq!(fillcolor(5), "white");
+}
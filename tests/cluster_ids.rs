@@ -0,0 +1,53 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+fn render(options: &GraphOptions) -> String {
+    common::graph(&common::fixture("dual-socket"), options)
+}
+
+//
+// Returns the name of the cluster labeled `label`.
+//
+fn cluster(output: &str, label: &str) -> String {
+    let lines = output.lines().collect::<Vec<_>>();
+
+    lines
+        .windows(2)
+        .find(|pair| pair[1].trim() == format!("label=\"{}\";", label))
+        .and_then(|pair| pair[0].trim().strip_prefix("subgraph "))
+        .map(|name| name.trim_end_matches(" {").to_string())
+        .unwrap()
+}
+
+#[test]
+fn deterministic() {
+    assert_eq!(
+        render(&GraphOptions::default()),
+        render(&GraphOptions::default())
+    );
+}
+
+#[test]
+fn stable_across_captures() {
+    let full = render(&GraphOptions::default());
+
+    //
+    // Leaving out the PCH and the devices on NUMA node 0 doesn't
+    // renumber the NUMA node 1 cluster.
+    //
+    let partial = render(&GraphOptions {
+        exclude: vec!["0000:00:".to_string(), "0000:17:".to_string()],
+        ..GraphOptions::default()
+    });
+
+    assert!(!partial.contains("NUMA node #0"));
+    assert_eq!(
+        cluster(&full, "NUMA node #1"),
+        cluster(&partial, "NUMA node #1")
+    );
+    assert_ne!(
+        cluster(&full, "NUMA node #0"),
+        cluster(&full, "NUMA node #1")
+    );
+}
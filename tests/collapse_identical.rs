@@ -0,0 +1,103 @@
+mod common;
+
+use pcigraph::{GraphOptions, LnkCap, LnkSta, Machine, PciAddr, PciDevice, PortType};
+
+fn port(addr: PciAddr, port_type: PortType, secondary: u8, subordinate: u8) -> PciDevice {
+    PciDevice::builder(addr, 0x10b5, 0x9781)
+        .port_type(port_type)
+        .buses(secondary, subordinate)
+        .lnk_cap(LnkCap::new(16.0, 4))
+        .lnk_sta(LnkSta::new(16.0, 4, false))
+        .build()
+}
+
+fn nvme(bus: u8, lnk_sta: LnkSta) -> PciDevice {
+    PciDevice::builder(PciAddr::new(0, bus, 0, 0), 0x144d, 0xa80a)
+        .class(0x0108)
+        .port_type(PortType::Endpoint)
+        .lnk_cap(LnkCap::new(16.0, 4))
+        .lnk_sta(lnk_sta)
+        .kernel_driver("nvme")
+        .build()
+}
+
+//
+// A switch with four drives behind it, the last of which is running at a
+// lower speed than the others.
+//
+fn machine() -> Machine {
+    let mut machine = Machine::default();
+
+    machine.add_device(port(PciAddr::new(0, 0, 1, 0), PortType::RootPort, 1, 6));
+    machine.add_device(port(PciAddr::new(0, 1, 0, 0), PortType::UpstreamPort, 2, 6));
+
+    for index in 0..4 {
+        let bus = 3 + index;
+
+        machine.add_device(port(
+            PciAddr::new(0, 2, index, 0),
+            PortType::DownstreamPort,
+            bus,
+            bus,
+        ));
+
+        machine.add_device(nvme(
+            bus,
+            if index < 3 {
+                LnkSta::new(16.0, 4, false)
+            } else {
+                LnkSta::new(8.0, 4, true)
+            },
+        ));
+    }
+
+    machine
+}
+
+fn render(collapse_identical: bool) -> String {
+    let options = GraphOptions {
+        collapse_identical,
+        ..GraphOptions::default()
+    };
+
+    common::graph(&machine(), &options)
+}
+
+#[test]
+fn collapses_identical_drives() {
+    let output = render(true);
+
+    assert!(output.contains("\t\"0000:02:00.0\" [ label=\"3× 0000:02:00.0\" ];\n"));
+    assert!(
+        output
+            .contains("\t\"0000:03:00.0\" [ label=\"3× Samsung NVMe\\nnvme\\n0000:03:00.0\" ];\n")
+    );
+
+    for addr in [
+        "0000:04:00.0",
+        "0000:05:00.0",
+        "0000:02:01.0",
+        "0000:02:02.0",
+    ] {
+        assert!(!output.contains(addr), "{} wasn't collapsed", addr);
+    }
+
+    //
+    // The drive whose link trained differently is drawn on its own.
+    //
+    assert!(
+        output.contains("\t\"0000:06:00.0\" [ label=\"Samsung NVMe\\nnvme\\n0000:06:00.0\" ];\n")
+    );
+    assert!(output.contains("\"0000:02:03.0\""));
+}
+
+#[test]
+fn unset() {
+    let output = render(false);
+
+    assert!(!output.contains('×'));
+
+    for bus in 3..=6 {
+        assert!(output.contains(&format!("\"0000:{:02x}:00.0\" [ label=\"Samsung NVMe", bus)));
+    }
+}
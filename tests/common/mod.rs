@@ -0,0 +1,38 @@
+//
+// Helpers shared by the integration tests.  Each test file only uses some
+// of them.
+//
+#![allow(dead_code)]
+
+use std::fs::File;
+
+use pcigraph::{GraphOptions, Machine};
+
+/// Parses the capture in tests/fixtures/`name`.txt.
+pub fn fixture(name: &str) -> Machine {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(&mut File::open(format!("tests/fixtures/{}.txt", name)).unwrap())
+        .unwrap();
+
+    machine
+}
+
+/// Parses a capture given inline.
+pub fn machine(input: &str) -> Machine {
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    machine
+}
+
+/// Renders `machine` as a graphviz graph.
+pub fn graph(machine: &Machine, options: &GraphOptions) -> String {
+    let mut output = Vec::new();
+
+    machine.write_graph(&mut output, options).unwrap();
+
+    String::from_utf8(output).unwrap()
+}
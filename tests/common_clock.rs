@@ -0,0 +1,42 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, PciAddr};
+
+#[test]
+fn common_clock() {
+    let machine = common::fixture("single-nvme");
+
+    let common_clock = |addr| {
+        machine
+            .devices_where(|dev| dev.addr() == addr)
+            .next()
+            .unwrap()
+            .common_clock()
+    };
+
+    assert_eq!(common_clock(PciAddr::new(0, 1, 0, 0)), Some(true));
+    assert_eq!(common_clock(PciAddr::new(0, 0, 0, 0)), None);
+
+    assert!(!common::graph(&machine, &GraphOptions::default()).contains("sep-clk"));
+}
+
+#[test]
+fn separate_clock() {
+    let input = read_to_string("tests/fixtures/single-nvme.txt").unwrap();
+
+    //
+    // Have the NVMe drive use a separate reference clock.
+    //
+    let at = input.rfind("CommClk+").unwrap();
+    let input = format!("{}CommClk-{}", &input[..at], &input[at + 8..]);
+
+    let machine = common::machine(&input);
+
+    assert!(
+        common::graph(&machine, &GraphOptions::default()).contains(
+            "\t\"0000:00:01.2\" -- \"0000:01:00.0\" [ label=\"16GT/s x4\\n(sep-clk)\" ];\n"
+        )
+    );
+}
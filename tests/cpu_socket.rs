@@ -0,0 +1,108 @@
+mod common;
+
+use std::{env, fs};
+
+use pcigraph::{GraphOptions, Machine, PciDevice};
+
+fn root_port(header: &str, extra: &str) -> PciDevice {
+    PciDevice::parse(&format!(
+        concat!(
+            "{} PCI bridge [0604]: Intel Corporation Device [8086:347a]\n",
+            "{}",
+            "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+        ),
+        header, extra
+    ))
+    .unwrap()
+}
+
+#[test]
+fn device_group_names() {
+    let cases = [
+        ("0000:17:02.0", "\tCPU socket: 1\n", "CPU socket 1"),
+        ("0000:00:1c.0", "\tCPU socket: 0\n", "PCH (on CPU socket 0)"),
+        (
+            "0000:17:02.0",
+            "\tNUMA node: 2\n\tCPU socket: 1\n",
+            "NUMA node #2",
+        ),
+        ("0000:17:02.0", "", "CPU"),
+        ("0000:00:1c.0", "", "PCH"),
+    ];
+
+    for (header, extra, group_name) in cases {
+        let dev = root_port(header, extra);
+
+        assert_eq!(dev.device_group_name(), group_name, "{:?}", extra);
+    }
+
+    assert_eq!(
+        root_port("0000:17:02.0", "\tCPU socket: 1\n").cpu_socket(),
+        Some(1)
+    );
+    assert_eq!(root_port("0000:17:02.0", "").cpu_socket(), None);
+}
+
+#[test]
+fn from_sysfs() {
+    let root = env::temp_dir().join(format!("pcigraph-cpu-socket-{}", std::process::id()));
+
+    let _ = fs::remove_dir_all(&root);
+
+    let dev = root.join("bus/pci/devices/0000:17:02.0");
+
+    fs::create_dir_all(&dev).unwrap();
+
+    for (attr, value) in [
+        ("vendor", "0x8086"),
+        ("device", "0x347a"),
+        ("class", "0x060400"),
+        ("numa_node", "-1"),
+        ("local_cpulist", "16-31,48-63"),
+    ] {
+        fs::write(dev.join(attr), format!("{}\n", value)).unwrap();
+    }
+
+    let mut config = vec![0; 0x40];
+
+    config[0x0e] = 0x01;
+    config[0x18..0x1b].copy_from_slice(&[0x17, 0x18, 0x18]);
+
+    fs::write(dev.join("config"), config).unwrap();
+
+    let topology = root.join("devices/system/cpu/cpu16/topology");
+
+    fs::create_dir_all(&topology).unwrap();
+    fs::write(topology.join("physical_package_id"), "1\n").unwrap();
+
+    let mut machine = Machine::default();
+
+    machine.parse_sysfs(root.join("bus/pci")).unwrap();
+
+    fs::remove_dir_all(&root).unwrap();
+
+    let dev = machine.devices_where(|_| true).next().unwrap();
+
+    assert_eq!(dev.numa_node(), None);
+    assert_eq!(dev.cpu_socket(), Some(1));
+}
+
+#[test]
+fn cluster_label() {
+    let input = concat!(
+        "0000:17:02.0 PCI bridge [0604]: Intel Corporation Device [8086:347a]\n",
+        "\tCPU socket: 1\n",
+        "\tBus: primary=17, secondary=18, subordinate=18, sec-latency=0\n",
+        "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+        "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x16, ASPM not supported\n",
+        "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+        "\n",
+    );
+
+    let machine = common::machine(input);
+
+    assert!(
+        common::graph(&machine, &GraphOptions::default()).contains("\t\tlabel=\"CPU socket 1\";\n")
+    );
+}
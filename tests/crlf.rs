@@ -0,0 +1,32 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn graph(input: &str) -> (Machine, String) {
+    let machine = common::machine(input);
+
+    let output = common::graph(&machine, &GraphOptions::default());
+
+    (machine, output)
+}
+
+#[test]
+fn crlf_line_endings() {
+    let input = read_to_string("tests/fixtures/single-nvme.txt").unwrap();
+
+    let (expected_machine, expected) = graph(&input);
+    let (machine, output) = graph(&input.replace('\n', "\r\n"));
+
+    assert_eq!(
+        machine
+            .endpoints()
+            .map(|dev| dev.addr())
+            .collect::<Vec<_>>(),
+        [PciAddr::new(0, 1, 0, 0)]
+    );
+    assert_eq!(machine.root_ports().count(), 1);
+    assert_eq!(machine.sections_seen(), expected_machine.sections_seen());
+    assert_eq!(output, expected);
+}
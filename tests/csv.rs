@@ -0,0 +1,36 @@
+mod common;
+
+use pcigraph::Machine;
+
+fn csv(machine: &Machine) -> String {
+    let mut output = Vec::new();
+
+    machine.write_csv(&mut output).unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn header_and_rows() {
+    assert_eq!(
+        csv(&common::fixture("dual-socket")),
+        concat!(
+            "address,vendor_id,device_id,short_name,numa_node,speed,width,capable_speed,capable_width,serial_number\n",
+            "0000:18:00.0,15b3,1021,MT2910 ConnectX-7 IB,0,16,16,16,16,b8cef603009a1c0e\n",
+            "0000:98:00.0,15b3,1021,MT2910 ConnectX-7 IB,1,16,16,16,16,b8cef603009a1c2e\n",
+            "0000:99:00.0,1344,51c3,Micron NVMe,1,8,4,16,4,\n",
+        )
+    );
+}
+
+#[test]
+fn quoting() {
+    let mut machine = common::fixture("single-nvme");
+
+    machine.add_name(0x144d, 0xa80a, "Samsung \"980\" PRO, 1TB");
+
+    assert!(
+        csv(&machine)
+            .ends_with("\n0000:01:00.0,144d,a80a,\"Samsung \"\"980\"\" PRO, 1TB\",,16,4,16,4,\n")
+    );
+}
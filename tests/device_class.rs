@@ -0,0 +1,60 @@
+mod common;
+
+use pcigraph::{GraphOptions, PciAddr};
+
+#[test]
+fn device_classes() {
+    let machine = common::fixture("dual-socket");
+
+    let device_class = |bus| {
+        machine
+            .devices_where(|dev| dev.addr() == PciAddr::new(0, bus, 0, 0))
+            .next()
+            .unwrap()
+            .device_class()
+    };
+
+    assert_eq!(device_class(0x01), Some("PCI bridge"));
+    assert_eq!(device_class(0x02), Some("VGA compatible controller"));
+    assert_eq!(device_class(0x18), Some("Infiniband controller"));
+    assert_eq!(device_class(0x99), Some("Non-Volatile memory controller"));
+}
+
+#[test]
+fn color_by_class() {
+    let options = GraphOptions {
+        color_by_class: true,
+        ..GraphOptions::default()
+    };
+
+    let output = common::graph(&common::fixture("dual-socket"), &options);
+
+    assert!(output.contains(concat!(
+        "\t\"0000:02:00.0\" [ label=\"ASPEED VGA\\nast\\n0000:02:00.0\" ",
+        "shape=hexagon style=filled fillcolor=orange ];\n"
+    )));
+    assert!(output.contains("style=filled fillcolor=palegreen ];\n"));
+    assert!(output.contains("style=filled fillcolor=lightblue ];\n"));
+
+    let output = common::graph(&common::fixture("dual-socket"), &GraphOptions::default());
+
+    assert!(!output.contains("fillcolor="));
+}
+
+#[test]
+fn mermaid_fill() {
+    let options = GraphOptions {
+        color_by_class: true,
+        ..GraphOptions::default()
+    };
+
+    let mut output = Vec::new();
+
+    common::fixture("single-nvme")
+        .write_mermaid(&mut output, &options)
+        .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("    style n0000_01_00_0 fill:lightblue\n"));
+}
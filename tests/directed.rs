@@ -0,0 +1,41 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+fn graph(directed: bool) -> String {
+    let options = GraphOptions {
+        directed,
+        ..GraphOptions::default()
+    };
+
+    common::graph(&common::fixture("single-nvme"), &options)
+}
+
+#[test]
+fn undirected() {
+    let output = graph(false);
+
+    assert!(output.starts_with("graph pci {\n"));
+    assert!(output.contains("\t\"0000:00:01.2\" -- \"0000:01:00.0\" [ label=\"16GT/s x4\" ];\n"));
+    assert!(!output.contains("->"));
+}
+
+#[test]
+fn directed() {
+    let output = graph(true);
+
+    assert!(output.starts_with("digraph pci {\n"));
+    assert!(output.contains("\t\"0000:00:01.2\" -> \"0000:01:00.0\" [ label=\"16GT/s x4\" ];\n"));
+    assert!(!output.contains(" -- "));
+
+    //
+    // Apart from the graph keyword and the edge operator, the output is
+    // the same.
+    //
+    assert_eq!(
+        output
+            .replacen("digraph", "graph", 1)
+            .replace(" -> ", " -- "),
+        graph(false)
+    );
+}
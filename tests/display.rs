@@ -0,0 +1,52 @@
+mod common;
+
+use std::fs;
+
+use pcigraph::{GraphOptions, Machine};
+
+#[test]
+fn summary() {
+    assert_eq!(
+        common::fixture("pcie-switch").to_string(),
+        "6 PCI devices, 1 root port, 1 DMI slot, 2 clusters"
+    );
+    assert_eq!(
+        Machine::default().to_string(),
+        "0 PCI devices, 0 root ports, 0 DMI slots, 0 clusters"
+    );
+}
+
+//
+// The summary counts clusters without drawing the graph, so check it
+// against the clusters that the graph is actually drawn with.
+//
+#[test]
+fn cluster_count_matches_graph() {
+    for entry in fs::read_dir("tests/fixtures").unwrap() {
+        let path = entry.unwrap().path();
+
+        let machine = common::machine(&fs::read_to_string(&path).unwrap());
+
+        let mut output = Vec::new();
+
+        machine
+            .explain_clusters(&mut output, &GraphOptions::default())
+            .unwrap();
+
+        let clusters = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .filter(|line| line.starts_with("cluster"))
+            .count();
+
+        assert!(
+            machine.to_string().ends_with(&format!(
+                " {} cluster{}",
+                clusters,
+                if clusters == 1 { "" } else { "s" }
+            )),
+            "{}",
+            path.display()
+        );
+    }
+}
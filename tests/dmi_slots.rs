@@ -0,0 +1,85 @@
+mod common;
+
+use pcigraph::{Machine, PciAddr, PciDevice};
+
+fn device(machine: &Machine, addr: PciAddr) -> &PciDevice {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+}
+
+#[test]
+fn slots() {
+    let machine = common::fixture("pcie-switch");
+
+    assert_eq!(
+        machine.slots().collect::<Vec<_>>(),
+        [(&PciAddr::new(0, 0x40, 1, 1), "PCIE1")]
+    );
+
+    assert_eq!(
+        machine.dmi_slot_for(PciAddr::new(0, 0x40, 1, 1)),
+        Some("PCIE1")
+    );
+    assert_eq!(machine.dmi_slot_for(PciAddr::new(0, 0x41, 0, 0)), None);
+}
+
+#[test]
+fn slot_names() {
+    let machine = common::fixture("pcie-switch");
+
+    let slot_name = |addr| machine.slot_name(device(&machine, addr));
+
+    //
+    // From the DMI record for the root port, and from the Physical Slot
+    // of the devices behind the switch's downstream ports.
+    //
+    assert_eq!(
+        slot_name(PciAddr::new(0, 0x40, 1, 1)),
+        Some("PCIE1".to_string())
+    );
+    assert_eq!(
+        slot_name(PciAddr::new(0, 0x42, 0, 0)),
+        Some("Slot 8".to_string())
+    );
+    assert_eq!(
+        slot_name(PciAddr::new(0, 0x42, 1, 0)),
+        Some("Slot 9".to_string())
+    );
+
+    //
+    // Endpoints have nothing behind them.
+    //
+    assert_eq!(slot_name(PciAddr::new(0, 0x44, 0, 0)), None);
+}
+
+#[test]
+fn downstream_record_first() {
+    let machine = common::machine(concat!(
+        "Handle 0x0901, DMI type 9, 17 bytes\n",
+        "System Slot Information\n",
+        "\tDesignation: PORT\n",
+        "\tBus Address: 0000:00:01.0\n",
+        "\n",
+        "Handle 0x0902, DMI type 9, 17 bytes\n",
+        "System Slot Information\n",
+        "\tDesignation: DEVICE\n",
+        "\tBus Address: 0000:01:00.0\n",
+        "\n",
+        "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+        "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+        "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+        "\n",
+        "01:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+        "\tPhysical Slot: 7\n",
+        "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+        "\n",
+    ));
+
+    assert_eq!(machine.slots().count(), 2);
+    assert_eq!(
+        machine.slot_name(device(&machine, PciAddr::new(0, 0, 1, 0))),
+        Some("DEVICE".to_string())
+    );
+}
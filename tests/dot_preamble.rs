@@ -0,0 +1,26 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+#[test]
+fn graph_and_node_attrs() {
+    let machine = common::fixture("single-nvme");
+
+    let options = GraphOptions {
+        dpi: Some(150),
+        graph_attrs: Some("splines=ortho bgcolor=white".to_string()),
+        node_attrs: Some("fontname=\"Helvetica\"".to_string()),
+        ..GraphOptions::default()
+    };
+
+    let output = common::graph(&machine, &options);
+
+    assert!(output.starts_with(concat!(
+        "graph pci {\n",
+        "\trankdir=LR;\n",
+        "\tcompound=true;\n",
+        "\tgraph [dpi=150];\n",
+        "\tgraph [splines=ortho bgcolor=white];\n",
+        "\tnode [fontname=\"Helvetica\"];\n",
+    )));
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use pcigraph::{GraphOptions, PciAddr};
+
+#[test]
+fn two_endpoints_under_root_port() {
+    let machine = common::fixture("pcie-switch");
+
+    let root_port = machine
+        .root_ports()
+        .find(|dev| dev.addr() == PciAddr::new(0, 0x40, 1, 1))
+        .unwrap();
+
+    //
+    // An x16 and an x4 link at 16GT/s.
+    //
+    let bandwidth_gbps = machine.downstream_bandwidth_gbps(root_port);
+
+    assert!((bandwidth_gbps - 20.0 * 16.0 * 128.0 / 130.0 / 8.0).abs() < 0.01);
+
+    let options = GraphOptions {
+        show_bandwidth: true,
+        ..GraphOptions::default()
+    };
+
+    assert!(
+        common::graph(&machine, &options)
+            .contains("label=\"Root port\\n0000:40:01.1\\nΣ 39.4 GB/s\"")
+    );
+}
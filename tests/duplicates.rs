@@ -0,0 +1,87 @@
+mod common;
+
+use pcigraph::{Machine, ParseError, PciAddr};
+
+const NVME: &str = concat!(
+    "01:00.0 Non-Volatile memory controller [0108]: ",
+    "Samsung Electronics Co Ltd NVMe SSD Controller PM173X [144d:a824]\n",
+    "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+);
+
+#[test]
+fn duplicate_section() {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(&mut format!("{}\n{}", NVME, NVME).as_bytes())
+        .unwrap();
+
+    assert_eq!(machine.duplicate_devices(), [PciAddr::new(0, 1, 0, 0)]);
+    assert_eq!(machine.devices_where(|_| true).count(), 1);
+}
+
+#[test]
+fn duplicate_section_strict() {
+    let mut machine = Machine::default();
+
+    machine.set_strict(true);
+
+    let err = machine
+        .parse(&mut format!("{}\n{}", NVME, NVME).as_bytes())
+        .unwrap_err();
+
+    assert!(matches!(err, ParseError::DuplicateDevice(addr) if addr == PciAddr::new(0, 1, 0, 0)));
+}
+
+#[test]
+fn no_duplicates() {
+    let machine = common::machine(NVME);
+
+    assert!(machine.duplicate_devices().is_empty());
+}
+
+#[test]
+fn duplicate_fragment_strict() {
+    const GPU: &str = concat!(
+        "02:00.0 VGA compatible controller [0300]: ",
+        "NVIDIA Corporation GA102 [GeForce RTX 3090] [10de:2204] (rev a1)\n",
+        "\tCapabilities: [68] Express (v2) Legacy Endpoint, MSI 00\n",
+    );
+
+    let (gpu_header, gpu_rest) = GPU.split_at(GPU.find('\t').unwrap());
+
+    let mut machine = Machine::default();
+
+    machine.set_strict(true);
+
+    machine
+        .parse(&mut format!("{}\n{}", NVME, gpu_header).as_bytes())
+        .unwrap();
+
+    //
+    // A fragment that fails to parse leaves the machine as it was, so the
+    // next fragment picks up where the last good one left off.
+    //
+    let err = machine
+        .parse(&mut format!("{}\n{}", gpu_rest, NVME).as_bytes())
+        .unwrap_err();
+
+    assert!(matches!(err, ParseError::DuplicateDevice(addr) if addr == PciAddr::new(0, 1, 0, 0)));
+    assert_eq!(machine.sections_seen(), 2);
+
+    machine
+        .parse(&mut format!("{}\n", gpu_rest).as_bytes())
+        .unwrap();
+
+    assert_eq!(machine.sections_seen(), 2);
+    assert_eq!(machine.devices_where(|_| true).count(), 2);
+    assert!(machine.duplicate_devices().is_empty());
+    assert!(
+        machine
+            .devices_where(|dev| dev.addr() == PciAddr::new(0, 2, 0, 0))
+            .next()
+            .unwrap()
+            .desc()
+            .contains("Legacy Endpoint")
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::GraphOptions;
+
+fn graph(input: &str) -> String {
+    let machine = common::machine(input);
+
+    common::graph(&machine, &GraphOptions::default())
+}
+
+#[test]
+fn pci_to_pci_bridge_with_empty_bus() {
+    let output = graph(&read_to_string("tests/fixtures/empty-bus.txt").unwrap());
+
+    assert!(output.contains("\t\"0000:0f:00.0\" -- \"0000:10:01.0\";\n"));
+    assert!(output.contains("\t\"0000:10:01.0\" -- \"bus 0000:11\";\n"));
+    assert!(output.contains("\t\"bus 0000:11\" [ shape=rectangle ];\n"));
+}
+
+#[test]
+fn pcie_to_pci_bridge_with_empty_bus() {
+    let input = read_to_string("tests/fixtures/empty-bus.txt").unwrap();
+
+    //
+    // Leave out the PCI-to-PCI bridge, so that there is nothing at all
+    // behind the PCIe to PCI bridge.
+    //
+    let input = &input[..input.find("0000:10:01.0 ").unwrap()];
+
+    let output = graph(input);
+
+    assert!(output.contains("\t\"0000:0f:00.0\" -- \"bus 0000:10\";\n"));
+    assert!(output.contains("\t\"bus 0000:10\" [ shape=rectangle ];\n"));
+}
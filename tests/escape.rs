@@ -0,0 +1,51 @@
+mod common;
+
+use std::fs;
+
+use pcigraph::GraphOptions;
+
+#[test]
+fn quoted_slot_name() {
+    let machine = common::machine(concat!(
+        "Handle 0x0901, DMI type 9, 17 bytes\n",
+        "System Slot Information\n",
+        "\tDesignation: CPU1 SLOT2 \"PCIe 4.0 X16\" \\ riser\n",
+        "\tBus Address: 0000:00:01.0\n",
+        "\n",
+        "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+        "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+        "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+        "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+        "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+        "\n",
+        "01:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+        "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+        "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+        "\n",
+    ));
+
+    let output = common::graph(&machine, &GraphOptions::default());
+
+    assert!(output.contains(concat!(
+        "\t\"0000:00:01.0_01\" ",
+        "[ label=\"CPU1 SLOT2 \\\"PCIe 4.0 X16\\\" \\\\ riser\" shape=rectangle ];\n"
+    )));
+
+    assert!(machine.check_graph(&GraphOptions::default()).is_empty());
+}
+
+#[test]
+fn fixtures_check_clean() {
+    for entry in fs::read_dir("tests/fixtures").unwrap() {
+        let path = entry.unwrap().path();
+
+        let machine = common::machine(&fs::read_to_string(&path).unwrap());
+
+        assert_eq!(
+            machine.check_graph(&GraphOptions::default()),
+            Vec::<String>::new(),
+            "{}",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use pcigraph::{ExitLatency, PciDevice};
+
+fn l1_exit_latency(exit_latency: &str) -> Option<ExitLatency> {
+    PciDevice::parse(&format!(
+        concat!(
+            "01:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 8GT/s, Width x4, ASPM L0s L1, Exit Latency {}\n",
+        ),
+        exit_latency
+    ))
+    .unwrap()
+    .l1_exit_latency()
+}
+
+#[test]
+fn parse() {
+    assert_eq!(l1_exit_latency("L1 <64ns"), Some(ExitLatency::Below(64)));
+    assert_eq!(
+        l1_exit_latency("L0s <512ns, L1 <32us"),
+        Some(ExitLatency::Below(32000))
+    );
+    assert_eq!(
+        l1_exit_latency("L0s <1us, L1 <1ms"),
+        Some(ExitLatency::Below(1000000))
+    );
+    assert_eq!(
+        l1_exit_latency("L0s unlimited, L1 unlimited"),
+        Some(ExitLatency::Unlimited)
+    );
+
+    //
+    // As older versions of lspci show the largest latency.
+    //
+    assert_eq!(
+        l1_exit_latency("L0s >4us, L1 >64us"),
+        Some(ExitLatency::Unlimited)
+    );
+
+    assert_eq!(l1_exit_latency("L0s <512ns"), None);
+    assert_eq!(l1_exit_latency("L1 <64ps"), None);
+}
+
+#[test]
+fn display() {
+    let display = |exit_latency| l1_exit_latency(exit_latency).unwrap().to_string();
+
+    assert_eq!(display("L1 <64ns"), "<64ns");
+    assert_eq!(display("L1 <2us"), "<2us");
+    assert_eq!(display("L1 <1ms"), "<1000us");
+    assert_eq!(display("L1 unlimited"), "unlimited");
+}
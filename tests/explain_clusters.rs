@@ -0,0 +1,62 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::GraphOptions;
+
+fn explain(input: &str) -> String {
+    let machine = common::machine(input);
+
+    let mut output = Vec::new();
+
+    machine
+        .explain_clusters(&mut output, &GraphOptions::default())
+        .unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn switch() {
+    assert_eq!(
+        explain(&read_to_string("tests/fixtures/pcie-switch.txt").unwrap()),
+        concat!(
+            "cluster1009062057 (CPU)\n",
+            "  identifier: CPU\n",
+            "  reason: bus number, as the NUMA node isn't known\n",
+            "  member: 0000:40:01.1\n",
+            "\n",
+            "cluster571214201 (PCIe switch)\n",
+            "  identifier: 00805e1000000001\n",
+            "  reason: Device Serial Number\n",
+            "  member: 0000:41:00.0\n",
+            "  member: 0000:42:00.0\n",
+            "  member: 0000:42:01.0\n",
+        )
+    );
+}
+
+#[test]
+fn switch_with_mismatched_serial_numbers() {
+    let input = read_to_string("tests/fixtures/pcie-switch.txt").unwrap();
+
+    //
+    // Give the last downstream port a serial number of its own.
+    //
+    let serial = "Device Serial Number 00-80-5e-10-00-00-00-01";
+    let at = input.rfind(serial).unwrap();
+
+    let input = format!(
+        "{}Device Serial Number 00-80-5e-10-00-00-00-02{}",
+        &input[..at],
+        &input[at + serial.len()..]
+    );
+
+    let output = explain(&input);
+
+    assert!(output.contains(concat!(
+        "  identifier: 0000:41:00.0\n",
+        "  reason: address, as a downstream port has a different serial number\n",
+        "  member: 0000:41:00.0\n",
+    )));
+}
@@ -0,0 +1,34 @@
+use pcigraph::PciDevice;
+
+fn device(version: u8, port_type: &str) -> PciDevice {
+    PciDevice::parse(&format!(
+        concat!(
+            "0000:00:01.1 PCI bridge [0604]: Advanced Micro Devices, Inc. [AMD] Device [1022:14ab]\n",
+            "\tCapabilities: [58] Express (v{}) {}, MSI 00\n",
+        ),
+        version, port_type
+    ))
+    .unwrap()
+}
+
+#[test]
+fn any_capability_version() {
+    for version in [1, 2, 3, 4] {
+        assert!(device(version, "Root Port (Slot+)").is_root_port());
+        assert!(device(version, "Upstream Port").is_upstream_port());
+        assert!(device(version, "Downstream Port (Slot-)").is_downstream_port());
+        assert!(device(version, "Endpoint").is_endpoint());
+        assert!(device(version, "PCI-Express to PCI/PCI-X Bridge").is_pci_bridge());
+        assert!(!device(version, "Endpoint").is_legacy_pci_device());
+    }
+}
+
+#[test]
+fn roles_are_distinct() {
+    let dev = device(3, "Endpoint");
+
+    assert!(!dev.is_root_port());
+    assert!(!dev.is_upstream_port());
+    assert!(!dev.is_downstream_port());
+    assert!(!dev.is_pci_bridge());
+}
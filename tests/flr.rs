@@ -0,0 +1,42 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn supports_flr(machine: &Machine, addr: PciAddr) -> Option<bool> {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+        .supports_flr()
+}
+
+#[test]
+fn flr_reset_bits() {
+    let nvme = common::fixture("single-nvme");
+
+    assert_eq!(supports_flr(&nvme, PciAddr::new(0, 1, 0, 0)), Some(true));
+    assert_eq!(supports_flr(&nvme, PciAddr::new(0, 0, 0, 0)), None);
+
+    let virtio = common::fixture("no-lnk-cap");
+
+    assert_eq!(supports_flr(&virtio, PciAddr::new(0, 1, 0, 0)), Some(false));
+
+    //
+    // The root port's DevCap doesn't mention FLR at all.
+    //
+    assert_eq!(supports_flr(&virtio, PciAddr::new(0, 0, 2, 0)), None);
+}
+
+#[test]
+fn show_flr() {
+    let options = GraphOptions {
+        show_flr: true,
+        ..GraphOptions::default()
+    };
+
+    assert!(
+        common::graph(&common::fixture("no-lnk-cap"), &options)
+            .contains("\\n0000:01:00.0\\nno FLR\"")
+    );
+    assert!(!common::graph(&common::fixture("single-nvme"), &options).contains("no FLR"));
+}
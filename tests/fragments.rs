@@ -0,0 +1,81 @@
+use std::{fs, path::PathBuf};
+
+use pcigraph::Machine;
+
+fn fixture(name: &str) -> String {
+    let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+
+    fs::read_to_string(fixture_path).unwrap()
+}
+
+fn json(machine: &Machine) -> String {
+    let mut output = Vec::new();
+
+    machine.write_json(&mut output).unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn parse_in_pieces() {
+    let input = fixture("pcie-switch.txt");
+
+    let mut whole = Machine::default();
+
+    whole.parse(&mut input.as_bytes()).unwrap();
+
+    //
+    // Cut the input at every line boundary and in the middle of every
+    // line, which covers cuts at section boundaries, within sections and
+    // within device header lines.
+    //
+    let mut cuts = Vec::new();
+
+    for (index, _) in input.match_indices('\n') {
+        cuts.push(index + 1);
+        cuts.push(index + 4);
+    }
+
+    for cut in cuts.into_iter().filter(|cut| *cut < input.len()) {
+        let (first, second) = input.split_at(cut);
+
+        let mut pieces = Machine::default();
+
+        pieces.parse(&mut first.as_bytes()).unwrap();
+        pieces.parse(&mut second.as_bytes()).unwrap();
+
+        assert_eq!(json(&pieces), json(&whole), "cut at {}", cut);
+        assert_eq!(pieces.sections_seen(), whole.sections_seen());
+        assert!(pieces.duplicate_devices().is_empty());
+    }
+}
+
+#[test]
+fn merge() {
+    let input = fixture("pcie-switch.txt");
+
+    let cut = input[..input.len() / 2].rfind("\n\n").unwrap() + 2;
+    let (first, second) = input.split_at(cut);
+
+    let mut whole = Machine::default();
+
+    whole.parse(&mut input.as_bytes()).unwrap();
+
+    let mut machine = Machine::default();
+
+    machine.parse(&mut first.as_bytes()).unwrap();
+
+    let mut other = Machine::default();
+
+    other.parse(&mut second.as_bytes()).unwrap();
+
+    assert!(machine.devices_where(|_| true).count() < whole.devices_where(|_| true).count());
+
+    machine.merge(other);
+
+    assert_eq!(json(&machine), json(&whole));
+    assert!(machine.duplicate_devices().is_empty());
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciDevice};
+
+const E810: &str = concat!(
+    "0000:b1:00.0 Ethernet controller [0200]: Intel Corporation Ethernet Controller ",
+    "E810-C for QSFP [8086:1592] (rev 02)\n",
+    "\tSubsystem: Intel Corporation Ethernet Network Adapter E810-C-Q2 [8086:0002]\n",
+    "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+    "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+    "\tKernel driver in use: ice\n",
+);
+
+#[test]
+fn full_names() {
+    let dev = PciDevice::parse(E810).unwrap();
+
+    assert_eq!(
+        dev.full_name(),
+        Some("Intel Corporation Ethernet Controller E810-C for QSFP")
+    );
+
+    //
+    // Devices that lspci has no name for aren't given one.
+    //
+    let dev = PciDevice::parse("0000:00:01.0 PCI bridge [0604]: Device [1d94:1453]\n").unwrap();
+
+    assert_eq!(dev.full_name(), None);
+
+    //
+    // IDs in the description don't end up in the name.
+    //
+    let dev = PciDevice::parse(concat!(
+        "0000:18:00.0 Infiniband controller [0207]: Mellanox Technologies ",
+        "MT2910 Family [ConnectX-7] [15b3:1021]\n"
+    ))
+    .unwrap();
+
+    assert_eq!(
+        dev.full_name(),
+        Some("Mellanox Technologies MT2910 Family [ConnectX-7]")
+    );
+}
+
+#[test]
+fn label_fallback() {
+    let input = concat!(
+        "0000:b0:02.0 PCI bridge [0604]: Intel Corporation Device [8086:347a]\n",
+        "\tBus: primary=b0, secondary=b1, subordinate=b1, sec-latency=0\n",
+        "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+        "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x16, ASPM not supported\n",
+        "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+        "\n",
+    )
+    .to_string()
+        + E810;
+
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    let output = common::graph(&machine, &GraphOptions::default());
+
+    assert!(output.contains(
+        "\t\"0000:b1:00.0\" [ label=\"Intel Corporation Ethernet Controller E810-C for QSFP\\nice\\n0000:b1:00.0\" ];\n"
+    ));
+}
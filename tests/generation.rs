@@ -0,0 +1,33 @@
+use pcigraph::{LnkCap, LnkSta};
+
+#[test]
+fn known_speeds() {
+    for (speed, generation) in [
+        (2.5, 1),
+        (5.0, 2),
+        (8.0, 3),
+        (16.0, 4),
+        (32.0, 5),
+        (64.0, 6),
+    ] {
+        assert_eq!(LnkCap::new(speed, 4).generation(), Some(generation));
+        assert_eq!(LnkSta::new(speed, 4, false).generation(), Some(generation));
+    }
+}
+
+#[test]
+fn unknown_speed() {
+    assert_eq!(LnkCap::new(12.0, 4).generation(), None);
+    assert_eq!(LnkSta::new(12.0, 4, false).generation(), None);
+}
+
+#[test]
+fn alternate_display() {
+    assert_eq!(format!("{}", LnkCap::new(16.0, 4)), "16GT/s x4");
+    assert_eq!(format!("{:#}", LnkCap::new(16.0, 4)), "16GT/s (Gen4) x4");
+    assert_eq!(
+        format!("{:#}", LnkSta::new(32.0, 16, false)),
+        "32GT/s (Gen5) x16"
+    );
+    assert_eq!(format!("{:#}", LnkCap::new(12.0, 4)), "12GT/s x4");
+}
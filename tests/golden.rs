@@ -0,0 +1,96 @@
+//
+// Renders each capture in tests/fixtures and compares the result against
+// the corresponding file in tests/golden.  Run with UPDATE_GOLDEN=1 set to
+// regenerate the golden files after an intentional output change.
+//
+
+use std::{env, fs, path::PathBuf};
+
+use pcigraph::{GraphOptions, Machine};
+
+fn check_golden(name: &str) {
+    let tests_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+
+    let fixture_path = tests_dir.join("fixtures").join(format!("{}.txt", name));
+    let golden_path = tests_dir.join("golden").join(format!("{}.dot", name));
+
+    let mut machine = Machine::default();
+
+    machine
+        .parse(&mut fs::File::open(&fixture_path).unwrap())
+        .unwrap();
+
+    let mut output = Vec::new();
+
+    machine
+        .write_graph(&mut output, &GraphOptions::default())
+        .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&golden_path, &output).unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(&golden_path)
+        .unwrap_or_else(|err| panic!("can't read {}: {}", golden_path.display(), err));
+
+    assert!(
+        output == golden,
+        "output for {} differs from {}; rerun with UPDATE_GOLDEN=1 if this is intended\n{}",
+        fixture_path.display(),
+        golden_path.display(),
+        output
+    );
+}
+
+#[test]
+fn single_nvme() {
+    check_golden("single-nvme");
+}
+
+#[test]
+fn pcie_switch() {
+    check_golden("pcie-switch");
+}
+
+#[test]
+fn dual_socket() {
+    check_golden("dual-socket");
+}
+
+#[test]
+fn legacy_pci() {
+    check_golden("legacy-pci");
+}
+
+#[test]
+fn no_lnk_cap() {
+    check_golden("no-lnk-cap");
+}
+
+#[test]
+fn pci_bridge_range() {
+    check_golden("pci-bridge-range");
+}
+
+#[test]
+fn broken_switch() {
+    check_golden("broken-switch");
+}
+
+#[test]
+fn empty_bus() {
+    check_golden("empty-bus");
+}
+
+#[test]
+fn bridge_paths() {
+    check_golden("bridge-paths");
+}
+
+#[test]
+fn quad_nic() {
+    check_golden("quad-nic");
+}
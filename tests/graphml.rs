@@ -0,0 +1,135 @@
+use std::{collections::BTreeSet, fs};
+
+use pcigraph::{GraphOptions, Machine};
+
+fn graphml(input: &str) -> String {
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    let mut output = Vec::new();
+
+    machine
+        .write_graphml(&mut output, &GraphOptions::default())
+        .unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+fn attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = tag.split_once(&format!(" {}=\"", name))?;
+
+    rest.split_once('"').map(|(value, _)| value)
+}
+
+//
+// Checks that the tags in `xml` are balanced, that text and attribute
+// values contain no unescaped markup, and that every edge connects
+// declared nodes.
+//
+fn check_well_formed(xml: &str) {
+    let body = xml
+        .strip_prefix("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")
+        .unwrap();
+
+    let mut stack = Vec::new();
+    let mut nodes = BTreeSet::new();
+    let mut edges = Vec::new();
+
+    let mut rest = body;
+
+    while let Some(start) = rest.find('<') {
+        let text = &rest[..start];
+
+        assert!(!text.contains('>'), "unescaped > in {:?}", text);
+
+        for (index, _) in text.match_indices('&') {
+            let entity = &text[index..];
+
+            assert!(
+                ["&amp;", "&lt;", "&gt;", "&quot;", "&apos;", "&#10;"]
+                    .iter()
+                    .any(|name| entity.starts_with(name)),
+                "unescaped & in {:?}",
+                text
+            );
+        }
+
+        let end = rest[start..].find('>').unwrap() + start;
+        let tag = &rest[start + 1..end];
+
+        assert!(!tag.contains('<'), "unescaped < in {:?}", tag);
+
+        let name = tag
+            .trim_start_matches('/')
+            .split([' ', '/'])
+            .next()
+            .unwrap();
+
+        if tag.starts_with('/') {
+            assert_eq!(stack.pop(), Some(name), "unbalanced </{}>", name);
+        } else if !tag.ends_with('/') {
+            stack.push(name);
+        }
+
+        if name == "node" && !tag.starts_with('/') {
+            nodes.insert(attr(tag, "id").unwrap().to_string());
+        } else if name == "edge" && !tag.starts_with('/') {
+            edges.push((
+                attr(tag, "source").unwrap().to_string(),
+                attr(tag, "target").unwrap().to_string(),
+            ));
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    assert!(stack.is_empty(), "unclosed {:?}", stack);
+    assert_eq!(rest, "\n");
+
+    for (source, target) in edges {
+        assert!(nodes.contains(&source), "undeclared node {}", source);
+        assert!(nodes.contains(&target), "undeclared node {}", target);
+    }
+}
+
+#[test]
+fn fixtures_well_formed() {
+    for entry in fs::read_dir("tests/fixtures").unwrap() {
+        let path = entry.unwrap().path();
+
+        check_well_formed(&graphml(&fs::read_to_string(&path).unwrap()));
+    }
+}
+
+#[test]
+fn escaping() {
+    let output = graphml(concat!(
+        "Handle 0x0901, DMI type 9, 17 bytes\n",
+        "System Slot Information\n",
+        "\tDesignation: <Riser & \"Slot\" 'A'>\n",
+        "\tBus Address: 0000:00:01.0\n",
+        "\n",
+        "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+        "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+        "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+        "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+        "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+        "\n",
+        "01:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+        "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+        "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+        "\n",
+    ));
+
+    check_well_formed(&output);
+
+    assert!(output.contains(concat!(
+        "<data key=\"label\">",
+        "&lt;Riser &amp; &quot;Slot&quot; &apos;A&apos;&gt;",
+        "</data>"
+    )));
+    assert!(output.contains("<data key=\"label\">Acme NVMe SSD&#10;0000:01:00.0</data>"));
+    assert!(output.contains("<data key=\"vendor\">1234</data>"));
+    assert!(output.contains("<data key=\"width\">16</data>"));
+}
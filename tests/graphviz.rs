@@ -0,0 +1,89 @@
+#![cfg(unix)]
+
+use std::{
+    env, fs,
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+//
+// Runs pcigraph with only `dir` on the PATH, so that a stub can stand in
+// for graphviz's dot.
+//
+fn run_with_path(dir: &PathBuf, format: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pcigraph"))
+        .args(["--format", format, "tests/fixtures/single-nvme.txt"])
+        .env("PATH", dir)
+        .output()
+        .unwrap()
+}
+
+fn stub_dir(name: &str, script: Option<&str>) -> PathBuf {
+    let dir = env::temp_dir().join(format!("pcigraph-{}-{}", name, std::process::id()));
+
+    fs::create_dir_all(&dir).unwrap();
+
+    if let Some(script) = script {
+        let path = dir.join("dot");
+
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    dir
+}
+
+#[test]
+fn pipes_dot_output_through_dot() {
+    let dir = stub_dir("svg", Some("#!/bin/sh\necho \"args: $*\"\nexec /bin/cat\n"));
+
+    for format in ["svg", "png"] {
+        let output = run_with_path(&dir, format);
+
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(stdout.starts_with(&format!("args: -T{}\n", format)));
+        assert!(stdout.contains("\ngraph pci {\n"));
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dot_not_found() {
+    let dir = stub_dir("no-dot", None);
+
+    let output = run_with_path(&dir, "svg");
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("dot not found; install graphviz")
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dot_fails() {
+    let dir = stub_dir(
+        "dot-fails",
+        Some("#!/bin/sh\n/bin/cat > /dev/null\necho \"syntax error in line 1\" >&2\nexit 1\n"),
+    );
+
+    let output = run_with_path(&dir, "svg");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("dot failed (exit status: 1): syntax error in line 1")
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
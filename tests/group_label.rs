@@ -0,0 +1,56 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use pcigraph::GraphOptions;
+
+fn cluster_labels(group_label: Option<&str>) -> BTreeSet<String> {
+    let machine = common::fixture("dual-socket");
+
+    let options = GraphOptions {
+        group_label: group_label.map(|group_label| group_label.to_string()),
+        ..GraphOptions::default()
+    };
+
+    common::graph(&machine, &options)
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("label=\""))
+        .map(|label| label.trim_end_matches("\";").to_string())
+        .filter(|label| !label.starts_with("PCI"))
+        .collect()
+}
+
+#[test]
+fn default_labels() {
+    assert_eq!(
+        cluster_labels(None),
+        BTreeSet::from([
+            "NUMA node #0".to_string(),
+            "NUMA node #1".to_string(),
+            "PCH (on NUMA node #0)".to_string(),
+        ])
+    );
+}
+
+#[test]
+fn templates() {
+    assert_eq!(
+        cluster_labels(Some("NUMA {numa} Bus {bus}")),
+        BTreeSet::from([
+            "NUMA 0 Bus 00".to_string(),
+            "NUMA 0 Bus 17".to_string(),
+            "NUMA 1 Bus 97".to_string(),
+        ])
+    );
+
+    //
+    // lspci doesn't show CPU sockets.
+    //
+    assert_eq!(
+        cluster_labels(Some("Node {numa} / Socket {socket}")),
+        BTreeSet::from([
+            "Node 0 / Socket ?".to_string(),
+            "Node 1 / Socket ?".to_string()
+        ])
+    );
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use std::{fs, io::Write, path::PathBuf};
+
+use flate2::{Compression, write::GzEncoder};
+use pcigraph::{GraphOptions, Machine};
+
+fn render(input: &[u8]) -> String {
+    let mut machine = Machine::default();
+
+    machine.parse(&mut &input[..]).unwrap();
+
+    common::graph(&machine, &GraphOptions::default())
+}
+
+#[test]
+fn gzip_input() {
+    let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("pcie-switch.txt");
+
+    let plain = fs::read(fixture_path).unwrap();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&plain).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    assert_ne!(plain, compressed);
+    assert_eq!(render(&compressed), render(&plain));
+}
@@ -0,0 +1,35 @@
+use std::fs::read_to_string;
+
+use pcigraph::{Machine, PciAddr};
+
+const HEX_DUMP: &str = concat!(
+    "00: 4d 14 0a a8 06 04 10 00 00 02 08 01 10 00 00 00\n",
+    "10: 04 00 c0 f6 00 00 00 00 00 00 00 00 00 00 00 00\n",
+    "f0: 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00\n",
+    "100: 01 00 02 15 00 00 00 00 00 00 00 00 00 00 00 00\n",
+);
+
+#[test]
+fn hex_dump() {
+    let input = read_to_string("tests/fixtures/single-nvme.txt").unwrap();
+
+    //
+    // lspci prints the hex dump of each device at the end of its section,
+    // and the NVMe drive's section is the last one.
+    //
+    let input = format!("{}{}", input, HEX_DUMP);
+
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    let nvme = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 1, 0, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(nvme.lnk_sta().unwrap().to_string(), "16GT/s x4");
+    assert!(!nvme.desc().contains("4d 14 0a a8"));
+    assert!(!nvme.desc().contains("100: "));
+    assert!(nvme.desc().ends_with("\tKernel modules: nvme\n"));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+fn render(hide_pch: bool, show_orphans: bool) -> String {
+    let options = GraphOptions {
+        hide_pch,
+        show_orphans,
+        ..GraphOptions::default()
+    };
+
+    common::graph(&common::fixture("dual-socket"), &options)
+}
+
+#[test]
+fn hide_pch() {
+    let output = render(true, false);
+
+    //
+    // The BMC hangs off a root port on bus 0.
+    //
+    for addr in ["0000:00:1c.0", "0000:01:00.0", "0000:02:00.0"] {
+        assert!(!output.contains(addr), "{} wasn't left out", addr);
+    }
+
+    assert!(!output.contains("PCH"));
+
+    for addr in ["0000:17:02.0", "0000:97:02.0", "0000:97:04.0"] {
+        assert!(
+            output.contains(&format!(
+                "\t\"{}\" [ label=\"Root port\\n{}\" shape=rectangle ];\n",
+                addr, addr
+            )),
+            "{} was left out",
+            addr
+        );
+    }
+}
+
+#[test]
+fn not_orphans() {
+    let output = render(true, true);
+
+    assert!(!output.contains("0000:02:00.0"));
+    assert!(!output.contains("unreachable"));
+}
+
+#[test]
+fn unset() {
+    let output = render(false, false);
+
+    assert!(output.contains("label=\"PCH (on NUMA node #0)\";"));
+    assert!(output.contains("\"0000:02:00.0\""));
+}
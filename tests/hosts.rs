@@ -0,0 +1,50 @@
+use std::fs;
+
+use pcigraph::{Machine, ParseError};
+
+fn capture(marker: &str) -> String {
+    let mut capture = String::new();
+
+    for (host, name) in [("db1", "pcie-switch"), ("web1", "single-nvme")] {
+        capture.push_str(&marker.replace("{host}", host));
+        capture.push('\n');
+        capture.push_str(&fs::read_to_string(format!("tests/fixtures/{}.txt", name)).unwrap());
+    }
+
+    capture
+}
+
+#[test]
+fn two_hosts() {
+    let machines = Machine::default()
+        .parse_hosts(&mut capture("### {host} ###").as_bytes(), "### {host} ###")
+        .unwrap();
+
+    assert_eq!(machines.keys().collect::<Vec<_>>(), ["db1", "web1"]);
+    assert_eq!(machines["db1"].endpoints().count(), 2);
+    assert_eq!(machines["web1"].endpoints().count(), 1);
+}
+
+#[test]
+fn custom_marker() {
+    let machines = Machine::default()
+        .parse_hosts(
+            &mut capture("==> {host}.log <==").as_bytes(),
+            "==> {host}.log <==",
+        )
+        .unwrap();
+
+    assert_eq!(machines.keys().collect::<Vec<_>>(), ["db1", "web1"]);
+
+    //
+    // Marker lines in another format don't start a new host.
+    //
+    let err = Machine::default()
+        .parse_hosts(
+            &mut capture("### {host} ###").as_bytes(),
+            "==> {host}.log <==",
+        )
+        .unwrap_err();
+
+    assert!(matches!(err, ParseError::MissingHostMarker(line) if line == "### db1 ###"));
+}
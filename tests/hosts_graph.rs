@@ -0,0 +1,51 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use pcigraph::{GraphOptions, Machine};
+
+#[test]
+fn two_hosts() {
+    let db1 = common::fixture("single-nvme");
+    let web1 = common::fixture("single-nvme");
+
+    let mut output = Vec::new();
+
+    Machine::write_hosts_graph(
+        &mut output,
+        &[("db1", &db1), ("web1", &web1)],
+        &GraphOptions::default(),
+    )
+    .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.starts_with("graph pci {\n"));
+    assert_eq!(output.matches("graph pci {").count(), 1);
+
+    assert!(output.contains("\t\tlabel=\"db1\";\n"));
+    assert!(output.contains("\t\tlabel=\"web1\";\n"));
+
+    //
+    // The same device on both hosts gives two distinct nodes.
+    //
+    for host in ["host0", "host1"] {
+        assert!(output.contains(&format!(
+            "\t\"{host}:0000:00:01.2\" -- \"{host}:0000:01:00.0\" [ label=\"16GT/s x4\" ];\n",
+            host = host
+        )));
+    }
+
+    assert!(!output.contains("\"0000:01:00.0\""));
+
+    //
+    // Cluster names don't collide across hosts.
+    //
+    let clusters = output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("subgraph "))
+        .collect::<Vec<_>>();
+
+    assert_eq!(clusters.len(), 4);
+    assert_eq!(clusters.iter().collect::<BTreeSet<_>>().len(), 4);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+const SWITCH: &str = concat!(
+    "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+    "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+    "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+    "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+    "\tBus: primary=00, secondary=01, subordinate=03, sec-latency=0\n",
+    "\n",
+    "01:00.0 PCI bridge [0604]: Broadcom / LSI PEX88096 PCIe Gen 4 Switch [1000:c030]\n",
+    "\tBus: primary=01, secondary=02, subordinate=03, sec-latency=0\n",
+    "\tCapabilities: [68] Express (v2) Upstream Port, MSI 00\n",
+    "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+    "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+    "\n",
+    "02:00.0 PCI bridge [0604]: Broadcom / LSI PEX88096 PCIe Gen 4 Switch [1000:c030]\n",
+    "\tBus: primary=02, secondary=03, subordinate=03, sec-latency=0\n",
+    "\tCapabilities: [68] Express (v2) Downstream Port (Slot+), MSI 00\n",
+    "\t\tLnkCap:\tPort #8, Speed 16GT/s, Width x4, ASPM L1, Exit Latency L1 <4us\n",
+    "\t\tLnkSta:\tSpeed 16GT/s, Width x4\n",
+    "\t\tSltCap:\tAttnBtn- PwrCtrl+ MRL- AttnInd- PwrInd- HotPlug+ Surprise+\n",
+    "\t\t\tSlot #8, PowerLimit 25W; Interlock- NoCompl-\n",
+    "\n",
+    "03:00.0 Non-Volatile memory controller [0108]: ",
+    "Samsung Electronics Co Ltd NVMe SSD Controller PM173X [144d:a824]\n",
+    "\tPhysical Slot: 8\n",
+    "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+    "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x4, ASPM L1, Exit Latency L1 <64us\n",
+    "\t\tLnkSta:\tSpeed 16GT/s, Width x4\n",
+);
+
+#[test]
+fn hotplug_slot() {
+    let mut machine = Machine::default();
+
+    machine.parse(&mut SWITCH.as_bytes()).unwrap();
+
+    let hotplug_capable = |addr| {
+        machine
+            .devices_where(|dev| dev.addr() == addr)
+            .next()
+            .unwrap()
+            .hotplug_capable()
+    };
+
+    assert_eq!(hotplug_capable(PciAddr::new(0, 2, 0, 0)), Some(true));
+    assert_eq!(hotplug_capable(PciAddr::new(0, 1, 0, 0)), None);
+
+    assert!(
+        common::graph(&machine, &GraphOptions::default())
+            .contains("\"0000:02:00.0_03\" [ label=\"Slot 8 (hotplug)\\n25W\" shape=rectangle ];")
+    );
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use pcigraph::{GraphOptions, GroupBy, Machine, PciAddr};
+
+fn iommu_group(machine: &Machine, addr: PciAddr) -> Option<u32> {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+        .iommu_group()
+}
+
+#[test]
+fn iommu_group_extraction() {
+    let machine = common::fixture("single-nvme");
+
+    assert_eq!(iommu_group(&machine, PciAddr::new(0, 0, 1, 2)), Some(2));
+    assert_eq!(iommu_group(&machine, PciAddr::new(0, 1, 0, 0)), Some(14));
+    assert_eq!(iommu_group(&machine, PciAddr::new(0, 0, 0, 0)), None);
+}
+
+#[test]
+fn group_by_iommu() {
+    let output = common::graph(
+        &common::fixture("single-nvme"),
+        &GraphOptions {
+            group_by: GroupBy::Iommu,
+            ..GraphOptions::default()
+        },
+    );
+
+    assert!(output.contains("label=\"IOMMU group 2\";\n\t\t\"0000:00:01.2\";"));
+    assert!(output.contains("label=\"IOMMU group 14\";\n\t\t\"0000:01:00.0\";"));
+    assert!(!output.contains("label=\"PCH\""));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use pcigraph::Machine;
+use serde_json::Value;
+
+fn json(machine: &Machine) -> Value {
+    let mut output = Vec::new();
+
+    machine.write_json(&mut output).unwrap();
+
+    serde_json::from_slice(&output).unwrap()
+}
+
+fn short_name<'a>(json: &'a Value, addr: &str) -> &'a Value {
+    json["pci_devices"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|dev| dev["addr"] == addr)
+        .map(|dev| &dev["short_name"])
+        .unwrap()
+}
+
+#[test]
+fn builtin_names() {
+    let json = json(&common::fixture("single-nvme"));
+
+    assert_eq!(short_name(&json, "0000:01:00.0"), "Samsung 980 PRO");
+    assert_eq!(short_name(&json, "0000:00:01.2"), &Value::Null);
+}
+
+#[test]
+fn names_override() {
+    let mut machine = common::fixture("single-nvme");
+
+    machine.add_name(0x144d, 0xa80a, "Boot drive");
+    machine.add_name(0x1022, 0x1483, "Zen 2 GPP bridge");
+
+    let json = json(&machine);
+
+    assert_eq!(short_name(&json, "0000:01:00.0"), "Boot drive");
+    assert_eq!(short_name(&json, "0000:00:01.2"), "Zen 2 GPP bridge");
+}
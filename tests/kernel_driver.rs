@@ -0,0 +1,47 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn kernel_driver(machine: &Machine, addr: PciAddr) -> Option<String> {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+        .kernel_driver()
+        .map(str::to_string)
+}
+
+#[test]
+fn driver_in_use() {
+    let machine = common::fixture("single-nvme");
+
+    assert_eq!(
+        kernel_driver(&machine, PciAddr::new(0, 1, 0, 0)).as_deref(),
+        Some("nvme")
+    );
+    assert_eq!(
+        kernel_driver(&machine, PciAddr::new(0, 0, 1, 2)).as_deref(),
+        Some("pcieport")
+    );
+    assert!(
+        common::graph(&machine, &GraphOptions::default())
+            .contains("label=\"Samsung 980 PRO\\nnvme\\n0000:01:00.0\"")
+    );
+}
+
+#[test]
+fn no_driver() {
+    let input = read_to_string("tests/fixtures/single-nvme.txt")
+        .unwrap()
+        .replace("\tKernel driver in use: nvme\n", "");
+
+    let machine = common::machine(&input);
+
+    assert_eq!(kernel_driver(&machine, PciAddr::new(0, 1, 0, 0)), None);
+    assert!(
+        common::graph(&machine, &GraphOptions::default())
+            .contains("label=\"Samsung 980 PRO\\n0000:01:00.0\"")
+    );
+}
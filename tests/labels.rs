@@ -0,0 +1,28 @@
+mod common;
+
+use pcigraph::{GraphOptions, Labels};
+
+fn graph(name: &str, options: &GraphOptions) -> String {
+    common::graph(&common::fixture(name), options)
+}
+
+#[test]
+fn labels() {
+    let options = GraphOptions {
+        labels: Labels {
+            pcie_switch: "Commutateur PCIe".to_string(),
+            pci_bridge: "Pont PCI".to_string(),
+            root_port: "Port racine".to_string(),
+        },
+        ..GraphOptions::default()
+    };
+
+    let output = graph("pcie-switch", &options);
+
+    assert!(output.contains("\t\tlabel=\"Commutateur PCIe\";\n"));
+    assert!(output.contains("[ label=\"Port racine\\n0000:40:01.1\" shape=rectangle ]"));
+    assert!(!output.contains("PCIe switch"));
+    assert!(!output.contains("Root port"));
+
+    assert!(graph("pci-bridge-range", &options).contains("\t\tlabel=\"Pont PCI\";\n"));
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+fn graph(options: &GraphOptions) -> String {
+    common::graph(&common::fixture("single-nvme"), options)
+}
+
+#[test]
+fn legend() {
+    let output = graph(&GraphOptions {
+        legend: true,
+        ..GraphOptions::default()
+    });
+
+    assert!(output.contains("\t\tlabel=\"Legend\";\n\t\t\"legend:port\";\n"));
+    assert!(output.contains(
+        "\t\"legend:port\" -- \"legend:degraded\" [ label=\"Degraded link\" color=red ];\n"
+    ));
+    assert!(!output.contains("\"legend:storage\""));
+
+    assert!(!graph(&GraphOptions::default()).contains("legend"));
+}
+
+#[test]
+fn legend_with_class_colors() {
+    let output = graph(&GraphOptions {
+        legend: true,
+        color_by_class: true,
+        ..GraphOptions::default()
+    });
+
+    assert!(output.contains(
+        "\t\"legend:storage\" [ label=\"Storage controller\" style=filled fillcolor=lightblue ];\n"
+    ));
+}
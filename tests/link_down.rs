@@ -0,0 +1,38 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine};
+
+fn render(presence: char) -> String {
+    let input = format!(
+        concat!(
+            "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:1901]\n",
+            "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 8GT/s, Width x16, ASPM not supported\n",
+            "\t\tLnkSta:\tSpeed 2.5GT/s, Width x0\n",
+            "\t\tSltCap:\tAttnBtn- PwrCtrl- MRL- AttnInd- PwrInd- HotPlug- Surprise-\n",
+            "\t\tSltSta:\tStatus: AttnBtn- PowerFlt- MRL- CmdCplt- PresDet{} Interlock-\n",
+            "\n",
+        ),
+        presence
+    );
+
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    common::graph(&machine, &GraphOptions::default())
+}
+
+#[test]
+fn card_present() {
+    let output = render('+');
+
+    assert!(output.contains("[ label=\"LINK DOWN\" color=red ];\n"));
+    assert!(output.contains("\t\"bus 0000:01\" [ shape=rectangle color=red ];\n"));
+}
+
+#[test]
+fn empty_slot() {
+    assert!(!render('-').contains("LINK DOWN"));
+}
@@ -0,0 +1,74 @@
+use pcigraph::{Machine, PciAddr, PciDevice};
+
+fn device(machine: &Machine, addr: PciAddr) -> &PciDevice {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+}
+
+#[test]
+fn device_lines() {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(
+            &mut concat!(
+                "00:1f.3 \"Audio device [0403]\" \"Intel Corporation [8086]\" ",
+                "\"Cannon Lake PCH cAVS [a348]\" -r10 \"Dell [1028]\" \"Device [0872]\"\n",
+                "0000:01:00.0 \"Non-Volatile memory controller [0108]\" ",
+                "\"Samsung Electronics Co Ltd [144d]\" \"NVMe SSD \\\"PM9A1\\\" [a80a]\" ",
+                "-p02 \"\" \"\"\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+    assert_eq!(machine.devices_where(|_| true).count(), 2);
+
+    let audio = device(&machine, PciAddr::new(0, 0, 0x1f, 3));
+
+    assert_eq!((audio.vendor_id(), audio.device_id()), (0x8086, 0xa348));
+    assert_eq!(audio.device_class(), Some("Audio device"));
+    assert_eq!(
+        audio.full_name(),
+        Some("Intel Corporation Cannon Lake PCH cAVS")
+    );
+    assert_eq!(audio.revision(), Some(0x10));
+    assert_eq!(audio.subsystem_id(), Some((0x1028, 0x0872)));
+
+    let nvme = device(&machine, PciAddr::new(0, 1, 0, 0));
+
+    assert_eq!((nvme.vendor_id(), nvme.device_id()), (0x144d, 0xa80a));
+    assert_eq!(
+        nvme.full_name(),
+        Some("Samsung Electronics Co Ltd NVMe SSD \"PM9A1\"")
+    );
+    assert_eq!(nvme.revision(), None);
+    assert_eq!(nvme.subsystem_id(), None);
+}
+
+#[test]
+fn device_line_with_capabilities() {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(
+            &mut concat!(
+                "01:00.0 \"Ethernet controller [0200]\" \"Intel Corporation [8086]\" ",
+                "\"Ethernet Controller E810-C for QSFP [1592]\" -r02 ",
+                "\"Intel Corporation [8086]\" \"Ethernet Network Adapter E810-C-Q2 [0002]\"\n",
+                "\tCapabilities: [a0] Express (v2) Endpoint, MSI 00\n",
+                "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+    let dev = device(&machine, PciAddr::new(0, 1, 0, 0));
+
+    assert_eq!((dev.vendor_id(), dev.device_id()), (0x8086, 0x1592));
+    assert_eq!(dev.subsystem_id(), Some((0x8086, 0x0002)));
+    assert!(dev.is_endpoint());
+    assert_eq!(dev.lnk_sta().unwrap().to_string(), "16GT/s x16");
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn machine(port_max_payload: u16) -> Machine {
+    let input = format!(
+        concat!(
+            "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+            "\t\tDevCap:\tMaxPayload {} bytes, PhantFunc 0\n",
+            "\t\tDevCtl:\tCorrErr- NonFatalErr- FatalErr- UnsupReq-\n",
+            "\t\t\tRlxdOrd- ExtTag- PhantFunc- AuxPwr- NoSnoop-\n",
+            "\t\t\tMaxPayload 128 bytes, MaxReadReq 128 bytes\n",
+            "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x4, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x4\n",
+            "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+            "\n",
+            "01:00.0 Non-Volatile memory controller [0108]: ",
+            "Samsung Electronics Co Ltd NVMe SSD Controller PM173X [144d:a824]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tDevCap:\tMaxPayload 512 bytes, PhantFunc 0, Latency L0s unlimited, L1 unlimited\n",
+            "\t\tDevCtl:\tCorrErr+ NonFatalErr+ FatalErr+ UnsupReq+\n",
+            "\t\t\tRlxdOrd+ ExtTag+ PhantFunc- AuxPwr- NoSnoop+ FLReset-\n",
+            "\t\t\tMaxPayload 128 bytes, MaxReadReq 512 bytes\n",
+            "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x4, ASPM L1, Exit Latency L1 <64us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x4\n",
+        ),
+        port_max_payload
+    );
+
+    common::machine(&input)
+}
+
+#[test]
+fn capable_above_configured() {
+    let machine = machine(256);
+
+    let dev = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 1, 0, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(dev.max_payload_supported(), Some(512));
+    assert_eq!(dev.max_payload(), Some(128));
+
+    assert!(
+        common::graph(&machine, &GraphOptions::default())
+            .contains("0000:01:00.0\\nMPS suboptimal\"")
+    );
+}
+
+#[test]
+fn limited_by_port() {
+    assert!(!common::graph(&machine(128), &GraphOptions::default()).contains("MPS suboptimal"));
+}
@@ -0,0 +1,67 @@
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, Machine};
+
+fn mermaid(name: &str) -> String {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(
+            &mut read_to_string(format!("tests/fixtures/{}.txt", name))
+                .unwrap()
+                .as_bytes(),
+        )
+        .unwrap();
+
+    let mut output = Vec::new();
+
+    machine
+        .write_mermaid(&mut output, &GraphOptions::default())
+        .unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn single_nvme() {
+    assert_eq!(
+        mermaid("single-nvme"),
+        concat!(
+            "graph LR\n",
+            "\n",
+            "    %% ####################################################################\n",
+            "    %% root port 0000:00:01.2\n",
+            "\n",
+            "    n0000_00_01_2[\"Root port<br>0000:00:01.2\"]\n",
+            "\n",
+            "    subgraph cluster3590780420 [\"PCH\"]\n",
+            "        n0000_00_01_2\n",
+            "    end\n",
+            "\n",
+            "    %% domain 0000 bus 01\n",
+            "\n",
+            "    n0000_00_01_2 ---|\"16GT/s x4\"| n0000_01_00_0([\"0000:01:00.0\"])\n",
+            "\n",
+            "    n0000_01_00_0([\"Samsung 980 PRO<br>nvme<br>0000:01:00.0\"])\n",
+        )
+    );
+}
+
+#[test]
+fn switch_cluster() {
+    let output = mermaid("pcie-switch");
+
+    assert!(output.starts_with("graph LR\n"));
+    assert!(output.contains(concat!(
+        "    subgraph cluster571214201 [\"PCIe switch\"]\n",
+        "        n0000_41_00_0\n",
+        "        n0000_42_00_0([\"0000:42:00.0\"])\n",
+        "        n0000_42_01_0([\"0000:42:01.0\"])\n",
+        "    end\n",
+    )));
+    assert_eq!(
+        output.matches("subgraph ").count(),
+        output.matches("    end\n").count()
+    );
+    assert!(!output.contains(" -- "));
+}
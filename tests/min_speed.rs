@@ -0,0 +1,57 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+fn render(options: &GraphOptions) -> String {
+    common::graph(&common::fixture("dual-socket"), options)
+}
+
+#[test]
+fn drops_slow_links() {
+    let output = render(&GraphOptions {
+        min_speed: Some(8.0),
+        ..GraphOptions::default()
+    });
+
+    //
+    // The root port to the BMC's bridge is capable of 8GT/s, but the
+    // bridge behind it only of 2.5GT/s.
+    //
+    for addr in ["0000:00:1c.0", "0000:01:00.0", "0000:02:00.0"] {
+        assert!(!output.contains(addr), "{} wasn't left out", addr);
+    }
+
+    //
+    // A link that is running slower than --min-speed but capable of it
+    // is kept.
+    //
+    for addr in [
+        "0000:17:02.0",
+        "0000:18:00.0",
+        "0000:98:00.0",
+        "0000:99:00.0",
+    ] {
+        assert!(
+            output.contains(&format!("\"{}\"", addr)),
+            "{} was left out",
+            addr
+        );
+    }
+}
+
+#[test]
+fn not_orphans() {
+    let output = render(&GraphOptions {
+        min_speed: Some(8.0),
+        show_orphans: true,
+        ..GraphOptions::default()
+    });
+
+    assert!(!output.contains("0000:02:00.0"));
+    assert!(!output.contains("devices not reachable from any root port"));
+}
+
+#[test]
+fn unset() {
+    assert!(render(&GraphOptions::default()).contains("\"0000:02:00.0\""));
+}
@@ -0,0 +1,37 @@
+use pcigraph::{Machine, ParseError};
+
+fn parse(input: &str) -> Result<(), ParseError> {
+    Machine::default().parse(&mut input.as_bytes())
+}
+
+#[test]
+fn without_nn() {
+    let input = concat!(
+        "00:01.0 PCI bridge: Intel Corporation 6th-10th Gen Core Processor PCIe Controller (x16)\n",
+        "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+        "\n",
+    );
+
+    let err = parse(input).unwrap_err();
+
+    assert!(matches!(
+        &err,
+        ParseError::MissingIds(line) if line.starts_with("00:01.0 PCI bridge: Intel")
+    ));
+    assert!(err.to_string().ends_with("(was lspci run without -nn?)"));
+}
+
+#[test]
+fn after_valid_sections() {
+    let input = concat!(
+        "00:00.0 Host bridge [0600]: Intel Corporation Device [8086:3e30]\n",
+        "\n",
+        "00:02.0 VGA compatible controller: Intel Corporation UHD Graphics 630\n",
+        "\n",
+    );
+
+    assert!(matches!(
+        parse(input),
+        Err(ParseError::MissingIds(line)) if line.starts_with("00:02.0 ")
+    ));
+}
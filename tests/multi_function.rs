@@ -0,0 +1,69 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, Machine};
+
+fn graph(input: &str) -> String {
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    common::graph(&machine, &GraphOptions::default())
+}
+
+//
+// Returns the cluster id from the lhead attribute of the edge from `from`
+// to `to`.
+//
+fn lhead<'a>(output: &'a str, from: &str, to: &str) -> Option<&'a str> {
+    let edge = format!("\t\"{}\" -- \"{}\" [", from, to);
+
+    let line = output.lines().find(|line| line.starts_with(&edge))?;
+
+    Some(
+        line.split_once(" lhead=")?
+            .1
+            .trim_end_matches(" ];")
+            .split(' ')
+            .next()
+            .unwrap(),
+    )
+}
+
+#[test]
+fn functions_share_a_cluster() {
+    let output = graph(&read_to_string("tests/fixtures/quad-nic.txt").unwrap());
+
+    let cluster = lhead(&output, "0000:00:03.0", "0000:03:00.0").unwrap();
+
+    assert!(output.contains(&format!(
+        concat!(
+            "\tsubgraph {} {{\n",
+            "\t\t\"0000:03:00.0\";\n",
+            "\t\t\"0000:03:00.1\";\n",
+            "\t\t\"0000:03:00.2\";\n",
+            "\t\t\"0000:03:00.3\";\n",
+            "\t}}\n",
+        ),
+        cluster
+    )));
+
+    //
+    // Only function 0 is linked to the root port.
+    //
+    for function in 1..4 {
+        assert!(!output.contains(&format!("\"0000:00:03.0\" -- \"0000:03:00.{}\"", function)));
+    }
+}
+
+#[test]
+fn single_function() {
+    let input = read_to_string("tests/fixtures/quad-nic.txt").unwrap();
+    let input = &input[..input.find("0000:03:00.1 ").unwrap()];
+
+    let output = graph(input);
+
+    assert_eq!(lhead(&output, "0000:00:03.0", "0000:03:00.0"), None);
+    assert!(output.contains("\t\"0000:00:03.0\" -- \"0000:03:00.0\" [ label=\"8GT/s x8\" ];\n"));
+}
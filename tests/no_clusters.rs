@@ -0,0 +1,79 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine};
+
+//
+// The graph's edges, without the lhead attributes that point edges at
+// clusters.
+//
+fn edges(output: &[u8]) -> Vec<String> {
+    String::from_utf8(output.to_vec())
+        .unwrap()
+        .lines()
+        .filter(|line| line.contains(" -- "))
+        .map(|line| {
+            let (edge, attrs) = line.split_once(" [ ").unwrap_or((line, ""));
+            let attrs = attrs
+                .split(' ')
+                .filter(|attr| !attr.starts_with("lhead="))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            format!("{} [ {}", edge, attrs)
+        })
+        .collect()
+}
+
+#[test]
+fn flat_graph() {
+    let flat = GraphOptions {
+        no_clusters: true,
+        ..GraphOptions::default()
+    };
+
+    for name in ["dual-socket", "legacy-pci", "pcie-switch", "single-nvme"] {
+        let machine = common::fixture(name);
+
+        let mut clustered_output = Vec::new();
+        let mut flat_output = Vec::new();
+
+        machine
+            .write_graph(&mut clustered_output, &GraphOptions::default())
+            .unwrap();
+        machine.write_graph(&mut flat_output, &flat).unwrap();
+
+        assert!(!String::from_utf8_lossy(&flat_output).contains("subgraph"));
+        assert!(!String::from_utf8_lossy(&flat_output).contains("lhead="));
+        assert!(machine.check_graph(&flat).is_empty());
+
+        assert_eq!(edges(&flat_output), edges(&clustered_output), "{}", name);
+    }
+}
+
+#[test]
+fn flat_hosts_graph() {
+    let options = GraphOptions {
+        no_clusters: true,
+        ..GraphOptions::default()
+    };
+
+    let switch = common::fixture("pcie-switch");
+    let nvme = common::fixture("single-nvme");
+
+    let mut output = Vec::new();
+
+    Machine::write_hosts_graph(
+        &mut output,
+        &[("switch", &switch), ("nvme", &nvme)],
+        &options,
+    )
+    .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(!output.contains("subgraph"));
+    assert_eq!(
+        output.lines().filter(|line| line.contains(" -- ")).count(),
+        9
+    );
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::process::Command;
+
+use pcigraph::GraphOptions;
+
+fn render(options: &GraphOptions) -> String {
+    common::graph(&common::fixture("dual-socket"), options)
+}
+
+#[test]
+fn no_color() {
+    assert!(render(&GraphOptions::default()).contains(" color=red"));
+
+    let output = render(&GraphOptions {
+        no_color: true,
+        ..GraphOptions::default()
+    });
+
+    assert!(!output.contains("color="));
+    assert!(output.contains("[ label=\"8GT/s x4\\n(downgraded) (cap 16GT/s x4)\" ];\n"));
+}
+
+#[test]
+fn keeps_fill_colors() {
+    let output = render(&GraphOptions {
+        no_color: true,
+        color_by_class: true,
+        ..GraphOptions::default()
+    });
+
+    assert!(!output.contains(" color="));
+    assert!(output.contains(" style=filled fillcolor=lightblue ];\n"));
+}
+
+#[test]
+fn mermaid() {
+    let options = GraphOptions {
+        no_color: true,
+        ..GraphOptions::default()
+    };
+
+    let mut output = Vec::new();
+
+    common::fixture("dual-socket")
+        .write_mermaid(&mut output, &options)
+        .unwrap();
+
+    assert!(!String::from_utf8(output).unwrap().contains("stroke:"));
+}
+
+#[test]
+fn no_color_environment_variable() {
+    for var in ["NO_COLOR", "PCIGRAPH_NO_COLOR"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_pcigraph"))
+            .arg("tests/fixtures/dual-socket.txt")
+            .env_remove("NO_COLOR")
+            .env_remove("PCIGRAPH_NO_COLOR")
+            .env(var, "1")
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        assert!(
+            !String::from_utf8(output.stdout).unwrap().contains("color="),
+            "{}",
+            var
+        );
+    }
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use std::process::Command;
+
+use pcigraph::PciAddr;
+
+#[test]
+fn ports_without_lnk_cap() {
+    let machine = common::fixture("no-lnk-cap");
+
+    assert_eq!(
+        machine.ports_without_lnk_cap(),
+        [PciAddr::new(0, 0, 2, 0), PciAddr::new(0, 0, 3, 0)]
+    );
+}
+
+#[test]
+fn warning_only_when_drawing() {
+    let stderr = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_pcigraph"))
+            .args(args)
+            .arg("tests/fixtures/no-lnk-cap.txt")
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    assert!(stderr(&[]).contains("ports without LnkCap"));
+    assert!(!stderr(&["--summary"]).contains("ports without LnkCap"));
+    assert!(!stderr(&["--explain-clusters"]).contains("ports without LnkCap"));
+}
@@ -0,0 +1,25 @@
+use pcigraph::PciDevice;
+
+fn device(extra: &str) -> PciDevice {
+    PciDevice::parse(&format!(
+        concat!(
+            "0000:c1:00.0 Non-Volatile memory controller [0108]: Samsung Electronics Co Ltd NVMe SSD Controller PM173X [144d:a824]\n",
+            "{}",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+        ),
+        extra
+    ))
+    .unwrap()
+}
+
+#[test]
+fn decimal_numa_node() {
+    assert_eq!(device("\tNUMA node: 13\n").numa_node(), Some(13));
+    assert_eq!(device("\tNUMA node: 10\n").numa_node(), Some(10));
+    assert_eq!(device("\tNUMA node: 0\n").numa_node(), Some(0));
+}
+
+#[test]
+fn no_numa_node() {
+    assert_eq!(device("").numa_node(), None);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+//
+// An endpoint on a bus that no bridge leads to.
+//
+const ORPHAN: &str = concat!(
+    "\n",
+    "0000:05:00.0 Ethernet controller [0200]: Intel Corporation Ethernet Controller X550 [8086:1563] (rev 01)\n",
+    "\tCapabilities: [a0] Express (v2) Endpoint, MSI 00\n",
+    "\t\tLnkCap:\tPort #0, Speed 8GT/s, Width x4, ASPM L0s L1, Exit Latency L0s <2us, L1 <16us\n",
+    "\t\tLnkSta:\tSpeed 8GT/s (ok), Width x4 (ok)\n",
+);
+
+fn machine() -> Machine {
+    let mut machine = Machine::default();
+
+    let input = read_to_string("tests/fixtures/single-nvme.txt").unwrap() + ORPHAN;
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    machine
+}
+
+fn graph(show_orphans: bool) -> String {
+    common::graph(
+        &machine(),
+        &GraphOptions {
+            show_orphans,
+            ..GraphOptions::default()
+        },
+    )
+}
+
+#[test]
+fn unreachable_devices() {
+    assert_eq!(machine().unreachable_devices(), [PciAddr::new(0, 5, 0, 0)]);
+}
+
+#[test]
+fn hidden_by_default() {
+    assert!(!graph(false).contains("0000:05:00.0"));
+}
+
+#[test]
+fn show_orphans() {
+    let output = graph(true);
+
+    assert!(output.contains("\t\"0000:05:00.0\" [ label=\"Intel X550\\n0000:05:00.0\" ];\n"));
+    assert!(output.contains("\t\tlabel=\"unreachable\";\n\t\t\"0000:05:00.0\";\n"));
+}
@@ -0,0 +1,86 @@
+use pcigraph::{Machine, PciAddr};
+
+#[test]
+fn wide_domain() {
+    let addr = "10000:00:00.0".parse::<PciAddr>().unwrap();
+
+    assert_eq!(addr, PciAddr::new(0x10000, 0, 0, 0));
+    assert_eq!(addr.to_string(), "10000:00:00.0");
+
+    assert_eq!(
+        "0000:3a:00.1".parse::<PciAddr>().unwrap().to_string(),
+        "0000:3a:00.1"
+    );
+    assert!("100000000:00:00.0".parse::<PciAddr>().is_err());
+}
+
+#[test]
+fn wide_domain_device() {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(
+            &mut concat!(
+                "10000:01:00.0 Non-Volatile memory controller [0108]: ",
+                "Samsung Electronics Co Ltd NVMe SSD Controller PM173X [144d:a824]\n",
+                "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        machine
+            .endpoints()
+            .map(|dev| dev.addr())
+            .collect::<Vec<_>>(),
+        [PciAddr::new(0x10000, 1, 0, 0)]
+    );
+}
+
+#[test]
+fn four_part_address() {
+    let addr = "0000:3b:00.1".parse::<PciAddr>().unwrap();
+
+    assert_eq!(addr, PciAddr::new(0, 0x3b, 0, 1));
+    assert_eq!(addr.to_string().parse::<PciAddr>(), Ok(addr));
+
+    assert_eq!(
+        "0001:ff:1f.7".parse::<PciAddr>(),
+        Ok(PciAddr::new(1, 0xff, 0x1f, 7))
+    );
+}
+
+#[test]
+fn three_part_address() {
+    assert_eq!(
+        "3b:00.1".parse::<PciAddr>(),
+        Ok(PciAddr::new(0, 0x3b, 0, 1))
+    );
+    assert_eq!(
+        "3b:00.1".parse::<PciAddr>().unwrap().to_string(),
+        "0000:3b:00.1"
+    );
+}
+
+#[test]
+fn malformed_address() {
+    for addr in [
+        "",
+        "3b:00",
+        "3b:00.",
+        "0000:3b:00",
+        "0000:3g:00.1",
+        "0000:3b:zz.1",
+        "0000:3b:00.x",
+        "0000:3b:20.0",
+        "0000:3b:00.8",
+        "0000:3b:100.0",
+        "0000:0000:3b:00.1",
+        "3b.00.1",
+    ] {
+        let err = addr.parse::<PciAddr>().unwrap_err();
+
+        assert_eq!(err.to_string(), format!("invalid PCI address \"{}\"", addr));
+    }
+}
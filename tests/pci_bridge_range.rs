@@ -0,0 +1,32 @@
+mod common;
+
+use pcigraph::PciAddr;
+
+#[test]
+fn subordinate_bus() {
+    let machine = common::fixture("pci-bridge-range");
+
+    let bridge = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0x0f, 0, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(bridge.secondary_bus(), Some(0x10));
+    assert_eq!(bridge.subordinate_bus(), Some(0x12));
+
+    let serial = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0x10, 2, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(serial.subordinate_bus(), None);
+}
+
+#[test]
+fn devices_on_all_buses_are_reachable() {
+    assert!(
+        common::fixture("pci-bridge-range")
+            .unreachable_devices()
+            .is_empty()
+    );
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn device_physical_slot(machine: &Machine, addr: PciAddr) -> Option<String> {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+        .physical_slot()
+        .map(str::to_string)
+}
+
+#[test]
+fn physical_slot_extraction() {
+    let machine = common::fixture("pcie-switch");
+
+    assert_eq!(
+        device_physical_slot(&machine, PciAddr::new(0, 0x43, 0, 0)).as_deref(),
+        Some("8")
+    );
+    assert_eq!(
+        device_physical_slot(&machine, PciAddr::new(0, 0x41, 0, 0)),
+        None
+    );
+}
+
+#[test]
+fn physical_slot_fallback() {
+    let input = read_to_string("tests/fixtures/single-nvme.txt")
+        .unwrap()
+        .replace(
+            "\tSubsystem: Samsung Electronics Co Ltd SSD 980 PRO [144d:a801]\n",
+            "\tSubsystem: Samsung Electronics Co Ltd SSD 980 PRO [144d:a801]\n\tPhysical Slot: 3\n",
+        );
+
+    let machine = common::machine(&input);
+    let root_port = machine.root_ports().next().unwrap();
+
+    assert_eq!(machine.slot_name(root_port).as_deref(), Some("Slot 3"));
+    assert!(
+        common::graph(&machine, &GraphOptions::default())
+            .contains("\"0000:00:01.2_01\" [ label=\"Slot 3")
+    );
+}
+
+#[test]
+fn dmi_slot_takes_precedence() {
+    let machine = common::fixture("pcie-switch");
+
+    let root_port = machine.root_ports().next().unwrap();
+
+    assert_eq!(machine.slot_name(root_port).as_deref(), Some("PCIE1"));
+}
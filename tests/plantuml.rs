@@ -0,0 +1,106 @@
+use std::{collections::BTreeSet, fs};
+
+use pcigraph::{GraphOptions, Machine};
+
+fn plantuml(input: &str) -> String {
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    let mut output = Vec::new();
+
+    machine
+        .write_plantuml(&mut output, &GraphOptions::default())
+        .unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn structure() {
+    for entry in fs::read_dir("tests/fixtures").unwrap() {
+        let path = entry.unwrap().path();
+
+        let output = plantuml(&fs::read_to_string(&path).unwrap());
+
+        assert!(output.starts_with("@startuml\n"), "{}", path.display());
+        assert!(output.ends_with("\n@enduml\n"), "{}", path.display());
+
+        let mut depth = 0;
+        let mut aliases = BTreeSet::new();
+
+        for line in output.lines() {
+            if line.ends_with(" {") {
+                depth += 1;
+            } else if line.trim() == "}" {
+                assert!(depth > 0, "{}: unbalanced }}", path.display());
+                depth -= 1;
+            }
+
+            if let Some((_, alias)) = line.split_once("\" as ") {
+                aliases.insert(alias.split(' ').next().unwrap().to_string());
+            }
+        }
+
+        assert_eq!(depth, 0, "{}: unclosed package", path.display());
+
+        for line in output.lines() {
+            if let Some((from, rest)) = line.split_once(" -- ") {
+                let to = rest.split(' ').next().unwrap();
+
+                assert!(
+                    aliases.contains(from),
+                    "{}: undeclared {}",
+                    path.display(),
+                    from
+                );
+                assert!(
+                    aliases.contains(to),
+                    "{}: undeclared {}",
+                    path.display(),
+                    to
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn pcie_switch() {
+    let output = plantuml(&fs::read_to_string("tests/fixtures/pcie-switch.txt").unwrap());
+
+    assert!(output.contains(concat!(
+        "package \"PCIe switch\" as cluster571214201 {\n",
+        "  component \"0000:41:00.0\" as n0000_41_00_0\n",
+        "  component \"0000:42:00.0\" as n0000_42_00_0\n",
+        "  component \"0000:42:01.0\" as n0000_42_01_0\n",
+        "}\n",
+    )));
+    assert!(output.contains("component \"Samsung NVMe\\nnvme\\n0000:44:00.0\" as n0000_44_00_0\n"));
+    assert!(output.contains("n0000_42_01_0_44 -- n0000_44_00_0 : 16GT/s x4\n"));
+}
+
+#[test]
+fn escaping() {
+    let output = plantuml(concat!(
+        "Handle 0x0901, DMI type 9, 17 bytes\n",
+        "System Slot Information\n",
+        "\tDesignation: CPU1 SLOT2 \"PCIe 4.0 X16\" \\ riser\n",
+        "\tBus Address: 0000:00:01.0\n",
+        "\n",
+        "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+        "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+        "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+        "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+        "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+        "\n",
+        "01:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+        "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+        "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+        "\n",
+    ));
+
+    assert!(output.contains(
+        "rectangle \"CPU1 SLOT2 <U+0022>PCIe 4.0 X16<U+0022> \\\\ riser\" as n0000_00_01_0_01\n"
+    ));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn downstream_port(device: u8, port: u8) -> String {
+    format!(
+        concat!(
+            "02:{device:02x}.0 PCI bridge [0604]: Broadcom / LSI PEX88096 PCIe Gen 4 Switch [1000:c030]\n",
+            "\tBus: primary=02, secondary={bus:02x}, subordinate={bus:02x}, sec-latency=0\n",
+            "\tCapabilities: [68] Express (v2) Downstream Port (Slot-), MSI 00\n",
+            "\t\tLnkCap:\tPort #{port}, Speed 16GT/s, Width x4, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x4\n",
+            "\n",
+        ),
+        device = device,
+        bus = device + 3,
+        port = port
+    )
+}
+
+fn machine() -> Machine {
+    let input = [
+        concat!(
+            "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+            "\tBus: primary=00, secondary=01, subordinate=06, sec-latency=0\n",
+            "\n",
+            "01:00.0 PCI bridge [0604]: Broadcom / LSI PEX88096 PCIe Gen 4 Switch [1000:c030]\n",
+            "\tBus: primary=01, secondary=02, subordinate=06, sec-latency=0\n",
+            "\tCapabilities: [68] Express (v2) Upstream Port, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+            "\n",
+        )
+        .to_string(),
+        downstream_port(0, 12),
+        downstream_port(1, 4),
+        downstream_port(2, 8),
+    ]
+    .concat();
+
+    common::machine(&input)
+}
+
+fn port_number(machine: &Machine, device: u8) -> Option<u8> {
+    machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 2, device, 0))
+        .next()
+        .unwrap()
+        .lnk_cap()
+        .unwrap()
+        .port()
+}
+
+//
+// The order in which the upstream port's edges to its downstream ports
+// are drawn.
+//
+fn downstream_port_order(options: &GraphOptions) -> Vec<String> {
+    common::graph(&machine(), options)
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("\"0000:01:00.0\" -- "))
+        .map(|to| to.trim_end_matches(';').trim_matches('"').to_string())
+        .collect()
+}
+
+#[test]
+fn port_numbers() {
+    let machine = machine();
+
+    assert_eq!(port_number(&machine, 0), Some(12));
+    assert_eq!(port_number(&machine, 1), Some(4));
+    assert_eq!(port_number(&machine, 2), Some(8));
+}
+
+#[test]
+fn order_by_port() {
+    assert_eq!(
+        downstream_port_order(&GraphOptions::default()),
+        ["0000:02:00.0", "0000:02:01.0", "0000:02:02.0"]
+    );
+
+    assert_eq!(
+        downstream_port_order(&GraphOptions {
+            order_by_port: true,
+            ..GraphOptions::default()
+        }),
+        ["0000:02:01.0", "0000:02:02.0", "0000:02:00.0"]
+    );
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use pcigraph::PciDevice;
+
+fn addrs<'a>(devs: impl Iterator<Item = &'a PciDevice>) -> Vec<String> {
+    devs.map(|dev| dev.addr().to_string()).collect()
+}
+
+#[test]
+fn devices_where() {
+    let machine = common::fixture("dual-socket");
+
+    assert_eq!(
+        addrs(machine.devices_where(|dev| dev.vendor_id() == 0x15b3)),
+        ["0000:18:00.0", "0000:98:00.0"]
+    );
+    assert_eq!(
+        addrs(machine.devices_where(|dev| dev.addr().bus() == 0x97)),
+        ["0000:97:02.0", "0000:97:04.0"]
+    );
+    assert!(machine.devices_where(|_| false).next().is_none());
+}
+
+#[test]
+fn endpoints() {
+    assert_eq!(
+        addrs(common::fixture("dual-socket").endpoints()),
+        ["0000:18:00.0", "0000:98:00.0", "0000:99:00.0"]
+    );
+}
+
+#[test]
+fn root_ports() {
+    assert_eq!(
+        addrs(common::fixture("dual-socket").root_ports()),
+        [
+            "0000:00:1c.0",
+            "0000:17:02.0",
+            "0000:97:02.0",
+            "0000:97:04.0"
+        ]
+    );
+}
+
+#[test]
+fn on_numa_node() {
+    let machine = common::fixture("dual-socket");
+
+    assert_eq!(
+        addrs(machine.on_numa_node(1)),
+        [
+            "0000:97:02.0",
+            "0000:97:04.0",
+            "0000:98:00.0",
+            "0000:99:00.0"
+        ]
+    );
+    assert_eq!(
+        addrs(machine.on_numa_node(0).filter(|dev| dev.is_endpoint())),
+        ["0000:18:00.0"]
+    );
+    assert_eq!(machine.on_numa_node(2).count(), 0);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine};
+
+fn function(function: u8, name: &str, device_id: &str) -> String {
+    format!(
+        concat!(
+            "01:00.{} Ethernet controller [0200]: Intel Corporation {} [8086:{}]\n",
+            "\tCapabilities: [a0] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x8, ASPM L1, Exit Latency L1 <64us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x8\n",
+            "\n",
+        ),
+        function, name, device_id
+    )
+}
+
+fn render(record_functions: bool) -> String {
+    let input = [
+        concat!(
+            "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x8, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x8\n",
+            "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+            "\n",
+        )
+        .to_string(),
+        function(0, "Ethernet Controller <A|B>", "1592"),
+        function(1, "Ethernet Controller <A|B>", "1592"),
+        function(2, "Ethernet Controller IPU", "1593"),
+    ]
+    .concat();
+
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    let options = GraphOptions {
+        record_functions,
+        ..GraphOptions::default()
+    };
+
+    common::graph(&machine, &options)
+}
+
+#[test]
+fn three_functions() {
+    let output = render(true);
+
+    assert!(output.contains(concat!(
+        "\t\"0000:01:00.0\" [ label=\"0000:01:00",
+        "|0: Intel Corporation Ethernet Controller \\<A\\|B\\>",
+        "|1: Intel Corporation Ethernet Controller \\<A\\|B\\>",
+        "|2: Intel Corporation Ethernet Controller IPU\" shape=record ];",
+    )));
+    assert!(output.contains("\"0000:00:01.0\" -- \"0000:01:00.0\" [ label=\"16GT/s x8\" ];"));
+    assert!(!output.contains("\"0000:01:00.1\""));
+    assert!(!output.contains("\"0000:01:00.2\""));
+}
+
+#[test]
+fn functions_in_cluster() {
+    let output = render(false);
+
+    assert!(!output.contains("shape=record"));
+    assert!(output.contains("\"0000:01:00.1\""));
+    assert!(output.contains("\"0000:01:00.2\""));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn machine(retimers: Option<&str>) -> Machine {
+    let lnk_sta2 = match retimers {
+        Some(retimers) => format!(
+            concat!(
+                "\t\tLnkSta2: Current De-emphasis Level: -3.5dB, EqualizationComplete+ ",
+                "EqualizationPhase1+\n",
+                "\t\t\t EqualizationPhase2+ EqualizationPhase3+ LinkEqualizationRequest-\n",
+                "\t\t\t {} CrosslinkRes: unsupported\n",
+            ),
+            retimers
+        ),
+        None => String::new(),
+    };
+
+    let input = format!(
+        concat!(
+            "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+            "{}",
+            "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+            "\n",
+            "01:00.0 3D controller [0302]: NVIDIA Corporation GA100 [A100 SXM4 40GB] [10de:20b0]\n",
+            "\tCapabilities: [78] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x16, ASPM L0s L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x16\n",
+            "\n",
+        ),
+        lnk_sta2
+    );
+
+    common::machine(&input)
+}
+
+fn retimer_count(machine: &Machine) -> Option<u8> {
+    machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0, 1, 0))
+        .next()
+        .unwrap()
+        .retimer_count()
+}
+
+fn link_label(machine: &Machine) -> String {
+    common::graph(machine, &GraphOptions::default())
+        .lines()
+        .find(|line| line.contains("\"0000:00:01.0\" -- \"0000:01:00.0\""))
+        .and_then(|line| line.split("label=\"").nth(1))
+        .and_then(|label| label.split('"').next())
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn retimer_counts() {
+    assert_eq!(retimer_count(&machine(None)), None);
+    assert_eq!(
+        retimer_count(&machine(Some("Retimer- 2Retimers-"))),
+        Some(0)
+    );
+    assert_eq!(
+        retimer_count(&machine(Some("Retimer+ 2Retimers-"))),
+        Some(1)
+    );
+    assert_eq!(
+        retimer_count(&machine(Some("Retimer+ 2Retimers+"))),
+        Some(2)
+    );
+}
+
+#[test]
+fn retimer_labels() {
+    assert_eq!(link_label(&machine(None)), "16GT/s x16");
+    assert_eq!(
+        link_label(&machine(Some("Retimer- 2Retimers-"))),
+        "16GT/s x16"
+    );
+    assert_eq!(
+        link_label(&machine(Some("Retimer+ 2Retimers-"))),
+        "16GT/s x16\\n(retimer)"
+    );
+    assert_eq!(
+        link_label(&machine(Some("Retimer+ 2Retimers+"))),
+        "16GT/s x16\\n(2 retimers)"
+    );
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr, PciDevice};
+
+fn revision(machine: &Machine, bus: u8) -> Option<u8> {
+    machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, bus, 0, 0))
+        .next()
+        .unwrap()
+        .revision()
+}
+
+#[test]
+fn revisions() {
+    let machine = common::fixture("dual-socket");
+
+    assert_eq!(revision(&machine, 0x02), Some(0x41));
+    assert_eq!(revision(&machine, 0x99), Some(0x01));
+    assert_eq!(revision(&machine, 0x18), None);
+
+    //
+    // A device that no longer responds to configuration space reads.
+    //
+    let dev = PciDevice::parse(concat!(
+        "00:1f.6 Ethernet controller [0200]: Intel Corporation Ethernet Connection ",
+        "(7) I219-LM [8086:15bb] (rev ff) (prog-if ff)\n",
+        "\t!!! Unknown header type 7f\n",
+    ))
+    .unwrap();
+
+    assert_eq!(dev.revision(), Some(0xff));
+}
+
+#[test]
+fn show_rev() {
+    let render = |show_rev| {
+        let options = GraphOptions {
+            show_rev,
+            ..GraphOptions::default()
+        };
+
+        common::graph(&common::fixture("dual-socket"), &options)
+    };
+
+    let output = render(true);
+
+    assert!(
+        output.contains("[ label=\"ASPEED VGA\\nast\\n0000:02:00.0\\nrev 41\" shape=hexagon ];\n")
+    );
+    assert!(output.contains("[ label=\"Micron NVMe\\nnvme\\n0000:99:00.0\\nrev 01\" ];\n"));
+    assert!(output.contains("[ label=\"MT2910 ConnectX-7 IB\\nmlx5_core\\n0000:18:00.0\" ];\n"));
+
+    assert!(!render(false).contains("\\nrev "));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use pcigraph::{GraphOptions, PciAddr};
+
+fn graph(root: PciAddr) -> String {
+    let options = GraphOptions {
+        root: Some(root),
+        ..GraphOptions::default()
+    };
+
+    common::graph(&common::fixture("pcie-switch"), &options)
+}
+
+#[test]
+fn switch() {
+    let output = graph(PciAddr::new(0, 0x41, 0, 0));
+
+    assert!(output.contains("\t\tlabel=\"PCIe switch\";\n\t\t\"0000:41:00.0\";\n"));
+    assert!(
+        output.contains("\t\"0000:42:00.0_43\" -- \"0000:43:00.0\" [ label=\"16GT/s x16\" ];\n")
+    );
+    assert!(
+        output.contains("\t\"0000:44:00.0\" [ label=\"Samsung NVMe\\nnvme\\n0000:44:00.0\" ];\n")
+    );
+
+    //
+    // The root port and the link to it are left out.
+    //
+    assert!(!output.contains("0000:40:01.1"));
+}
+
+#[test]
+fn downstream_port() {
+    let output = graph(PciAddr::new(0, 0x42, 1, 0));
+
+    assert!(output.contains("0000:44:00.0"));
+    assert!(!output.contains("0000:41:00.0"));
+    assert!(!output.contains("0000:43:00.0"));
+}
+
+#[test]
+fn endpoint() {
+    let machine = common::fixture("pcie-switch");
+
+    assert!(machine.subtree_root(PciAddr::new(0, 0x43, 0, 0)).is_none());
+    assert!(
+        machine
+            .write_graph(
+                &mut Vec::new(),
+                &GraphOptions {
+                    root: Some(PciAddr::new(0, 0x43, 0, 0)),
+                    ..GraphOptions::default()
+                },
+            )
+            .is_err()
+    );
+}
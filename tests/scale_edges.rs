@@ -0,0 +1,59 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine};
+
+fn link(port: u8, width: u8) -> String {
+    format!(
+        concat!(
+            "00:{port:02x}.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 8GT/s, Width x{width}, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x{width}\n",
+            "\tBus: primary=00, secondary={port:02x}, subordinate={port:02x}, sec-latency=0\n",
+            "\n",
+            "{port:02x}:00.0 Ethernet controller [0200]: Intel Corporation Device [8086:1593]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 8GT/s, Width x{width}, ASPM L1, Exit Latency L1 <64us\n",
+            "\t\tLnkSta:\tSpeed 8GT/s, Width x{width}\n",
+            "\n",
+        ),
+        port = port,
+        width = width
+    )
+}
+
+fn render(scale_edges: bool) -> String {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(&mut [link(1, 1), link(2, 4), link(3, 16)].concat().as_bytes())
+        .unwrap();
+
+    let options = GraphOptions {
+        scale_edges,
+        ..GraphOptions::default()
+    };
+
+    common::graph(&machine, &options)
+}
+
+#[test]
+fn penwidth_by_lanes() {
+    let output = render(true);
+
+    assert!(
+        output.contains("\"0000:00:01.0\" -- \"0000:01:00.0\" [ label=\"8GT/s x1\" penwidth=1 ];")
+    );
+    assert!(
+        output
+            .contains("\"0000:00:02.0\" -- \"0000:02:00.0\" [ label=\"8GT/s x4\" penwidth=2.5 ];")
+    );
+    assert!(
+        output.contains("\"0000:00:03.0\" -- \"0000:03:00.0\" [ label=\"8GT/s x16\" penwidth=4 ];")
+    );
+}
+
+#[test]
+fn no_penwidth_by_default() {
+    assert!(!render(false).contains("penwidth"));
+}
@@ -0,0 +1,62 @@
+use pcigraph::{Machine, PciAddr, Section};
+
+const INPUT: &str = concat!(
+    "# dmidecode 3.5\n",
+    "Getting SMBIOS data from sysfs.\n",
+    "SMBIOS 3.3.0 present.\n",
+    "\n",
+    "Handle 0x0000, DMI type 0, 26 bytes\n",
+    "BIOS Information\n",
+    "\tVendor: American Megatrends Inc.\n",
+    "\n",
+    "Handle 0x0900, DMI type 9, 24 bytes\n",
+    "System Slot Information\n",
+    "\tDesignation: PCIE1\n",
+    "\tType: x16 PCI Express 4\n",
+    "\tBus Address: 0000:00:01.2\n",
+    "\n",
+    "0000:01:00.0 Non-Volatile memory controller [0108]: Samsung Electronics Co Ltd ",
+    "NVMe SSD Controller PM9A1/PM9A3/980PRO [144d:a80a] (prog-if 02 [NVM Express])\n",
+    "\tKernel driver in use: nvme\n",
+    "\n",
+);
+
+#[test]
+fn classify() {
+    let sections = INPUT.split("\n\n").map(Section::parse).collect::<Vec<_>>();
+
+    assert!(matches!(sections[0], Section::Unknown));
+    assert!(matches!(sections[1], Section::Unknown));
+    assert!(matches!(
+        &sections[2],
+        Section::DmiSlot(addr, name) if *addr == PciAddr::new(0, 0, 1, 2) && name == "PCIE1"
+    ));
+    assert!(matches!(
+        &sections[3],
+        Section::PciDevice(dev) if dev.addr() == PciAddr::new(0, 1, 0, 0)
+    ));
+}
+
+#[test]
+fn unknown_sections() {
+    let mut machine = Machine::default();
+
+    machine.parse(&mut INPUT.as_bytes()).unwrap();
+
+    assert_eq!(machine.sections_seen(), 4);
+    assert_eq!(machine.unknown_sections(), 2);
+
+    //
+    // An unknown section that is cut off at the end of a piece of input is
+    // only counted once.
+    //
+    let at = INPUT.find("Vendor").unwrap();
+
+    let mut machine = Machine::default();
+
+    machine.parse(&mut &INPUT.as_bytes()[..at]).unwrap();
+    machine.parse(&mut &INPUT.as_bytes()[at..]).unwrap();
+
+    assert_eq!(machine.sections_seen(), 4);
+    assert_eq!(machine.unknown_sections(), 2);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use pcigraph::GraphOptions;
+
+//
+// Returns the members of each "PCIe switch" cluster in `output`.
+//
+fn switch_clusters(output: &str) -> Vec<Vec<&str>> {
+    output
+        .split("\t\tlabel=\"PCIe switch\";\n")
+        .skip(1)
+        .map(|cluster| {
+            cluster
+                .lines()
+                .take_while(|line| *line != "\t}")
+                .map(|line| line.trim().trim_end_matches(';'))
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn switches_stay_separate() {
+    let input = read_to_string("tests/fixtures/pcie-switch.txt").unwrap();
+
+    //
+    // The same switch in two PCI domains, both reporting the same Device
+    // Serial Number.
+    //
+    let machine = common::machine(&format!("{}\n{}", input, input.replace("0000:", "0001:")));
+
+    let output = common::graph(&machine, &GraphOptions::default());
+
+    assert_eq!(
+        switch_clusters(&output),
+        [
+            ["\"0000:41:00.0\"", "\"0000:42:00.0\"", "\"0000:42:01.0\""],
+            ["\"0001:41:00.0\"", "\"0001:42:00.0\"", "\"0001:42:01.0\""],
+        ]
+    );
+}
+
+#[test]
+fn single_switch_uses_serial_number() {
+    let machine = common::fixture("pcie-switch");
+
+    let mut output = Vec::new();
+
+    machine
+        .explain_clusters(&mut output, &GraphOptions::default())
+        .unwrap();
+
+    assert!(
+        String::from_utf8(output)
+            .unwrap()
+            .contains("  reason: Device Serial Number\n")
+    );
+}
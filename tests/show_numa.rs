@@ -0,0 +1,37 @@
+mod common;
+
+use pcigraph::GraphOptions;
+
+fn render(name: &str, show_numa: bool) -> String {
+    let options = GraphOptions {
+        show_numa,
+        ..GraphOptions::default()
+    };
+
+    common::graph(&common::fixture(name), &options)
+}
+
+#[test]
+fn show_numa() {
+    let output = render("dual-socket", true);
+
+    assert!(output.contains(
+        "\t\"0000:97:02.0\" [ label=\"Root port\\n0000:97:02.0\\nNUMA 1\" shape=rectangle ];\n"
+    ));
+    assert!(output.contains(
+        "\t\"0000:18:00.0\" [ label=\"MT2910 ConnectX-7 IB\\nmlx5_core\\n0000:18:00.0\\nNUMA 0\" ];\n"
+    ));
+    assert!(output.contains(
+        "\t\"0000:99:00.0\" [ label=\"Micron NVMe\\nnvme\\n0000:99:00.0\\nNUMA 1\" ];\n"
+    ));
+
+    assert!(!render("dual-socket", false).contains("\\nNUMA "));
+}
+
+#[test]
+fn without_numa_nodes() {
+    //
+    // Devices without a NUMA node don't get a NUMA line.
+    //
+    assert!(!render("single-nvme", true).contains("\\nNUMA "));
+}
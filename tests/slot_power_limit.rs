@@ -0,0 +1,94 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+fn root_port(bus: u8, power_limit: &str) -> String {
+    format!(
+        concat!(
+            "00:{bus:02x}.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+            "\tBus: primary=00, secondary={bus:02x}, subordinate={bus:02x}, sec-latency=0\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+            "\t\tDevCap:\tMaxPayload 256 bytes, PhantFunc 0\n",
+            "\t\t\tExtTag+ RBE+\n",
+            "\t\tLnkCap:\tPort #{bus}, Speed 16GT/s, Width x16, ASPM L1, Exit Latency L1 <4us\n",
+            "\t\tLnkSta:\tSpeed 16GT/s, Width x0\n",
+            "\t\tSltCap:\tAttnBtn- PwrCtrl+ MRL- AttnInd- PwrInd- HotPlug- Surprise-\n",
+            "\t\t\tSlot #{bus}, {power_limit}; Interlock- NoCompl-\n",
+            "\n",
+        ),
+        bus = bus,
+        power_limit = power_limit
+    )
+}
+
+fn machine() -> Machine {
+    let input = [
+        concat!(
+            "Handle 0x0901, DMI type 9, 17 bytes\n",
+            "System Slot Information\n",
+            "\tDesignation: SLOT1\n",
+            "\tBus Address: 0000:00:01.0\n",
+            "\n",
+        )
+        .to_string(),
+        root_port(1, "PowerLimit 75.000W"),
+        root_port(2, "PowerLimit 25W"),
+        root_port(3, "PowerLimit 0.250W"),
+        root_port(4, "PowerLimit 0W"),
+        concat!(
+            "05:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tDevCap:\tMaxPayload 256 bytes, PhantFunc 0, Latency L0s unlimited, L1 unlimited\n",
+            "\t\t\tExtTag+ AttnBtn- AttnInd- PwrInd- RBE+ FLReset+ SlotPowerLimit 75W\n",
+            "\n",
+        )
+        .to_string(),
+    ]
+    .concat();
+
+    common::machine(&input)
+}
+
+fn slot_power_limit(machine: &Machine, addr: PciAddr) -> Option<f32> {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+        .slot_power_limit()
+}
+
+#[test]
+fn power_limits() {
+    let machine = machine();
+
+    assert_eq!(
+        slot_power_limit(&machine, PciAddr::new(0, 0, 1, 0)),
+        Some(75.0)
+    );
+    assert_eq!(
+        slot_power_limit(&machine, PciAddr::new(0, 0, 2, 0)),
+        Some(25.0)
+    );
+    assert_eq!(
+        slot_power_limit(&machine, PciAddr::new(0, 0, 3, 0)),
+        Some(0.25)
+    );
+    assert_eq!(
+        slot_power_limit(&machine, PciAddr::new(0, 0, 4, 0)),
+        Some(0.0)
+    );
+
+    //
+    // The slot power limit in an endpoint's DevCap is the limit that its
+    // slot imposes on it, not that of a slot of its own.
+    //
+    assert_eq!(slot_power_limit(&machine, PciAddr::new(0, 5, 0, 0)), None);
+}
+
+#[test]
+fn slot_label() {
+    assert!(
+        common::graph(&machine(), &GraphOptions::default())
+            .contains("\"0000:00:01.0_01\" [ label=\"SLOT1\\n75W\" shape=rectangle ];")
+    );
+}
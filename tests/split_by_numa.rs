@@ -0,0 +1,44 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use pcigraph::{GraphOptions, Machine};
+
+fn render(machine: &Machine, numa_node: Option<usize>) -> String {
+    let options = GraphOptions {
+        numa_node: Some(numa_node),
+        show_orphans: true,
+        ..GraphOptions::default()
+    };
+
+    common::graph(machine, &options)
+}
+
+#[test]
+fn numa_nodes() {
+    assert_eq!(
+        common::fixture("dual-socket").numa_nodes(),
+        BTreeSet::from([Some(0), Some(1)])
+    );
+}
+
+#[test]
+fn partition_root_ports() {
+    let machine = common::fixture("dual-socket");
+
+    let numa0 = render(&machine, Some(0));
+
+    assert!(numa0.contains("# root port 0000:00:1c.0"));
+    assert!(numa0.contains("# root port 0000:17:02.0"));
+    assert!(!numa0.contains("0000:97:"));
+    assert!(!numa0.contains("unreachable"));
+
+    let numa1 = render(&machine, Some(1));
+
+    assert!(numa1.contains("# root port 0000:97:02.0"));
+    assert!(numa1.contains("# root port 0000:97:04.0"));
+    assert!(!numa1.contains("0000:17:"));
+    assert!(!numa1.contains("unreachable"));
+
+    assert!(!render(&machine, None).contains("# root port"));
+}
@@ -0,0 +1,39 @@
+use pcigraph::PciDevice;
+
+fn nvme(subsystem: &str) -> PciDevice {
+    PciDevice::parse(&format!(
+        concat!(
+            "0000:01:00.0 Non-Volatile memory controller [0108]: Samsung Electronics Co Ltd NVMe SSD Controller PM9A1/PM9A3/980PRO [144d:a80a] (prog-if 02 [NVM Express])\n",
+            "{}",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+        ),
+        subsystem
+    ))
+    .unwrap()
+}
+
+#[test]
+fn subsystem_id_extraction() {
+    assert_eq!(
+        nvme("\tSubsystem: Samsung Electronics Co Ltd SSD 980 PRO [144d:a801]\n").subsystem_id(),
+        Some((0x144d, 0xa801))
+    );
+    assert_eq!(nvme("").subsystem_id(), None);
+}
+
+#[test]
+fn subsystem_name_takes_precedence() {
+    assert_eq!(
+        nvme("\tSubsystem: Samsung Electronics Co Ltd SSD 980 PRO [144d:a801]\n").short_name(),
+        Some("Samsung 980 PRO")
+    );
+}
+
+#[test]
+fn falls_back_to_device_name() {
+    assert_eq!(
+        nvme("\tSubsystem: Dell Device [1028:2113]\n").short_name(),
+        Some("Samsung NVMe")
+    );
+    assert_eq!(nvme("").short_name(), Some("Samsung NVMe"));
+}
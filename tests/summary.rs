@@ -0,0 +1,33 @@
+mod common;
+
+use pcigraph::{Machine, PciAddr};
+
+fn summary(machine: &Machine) -> String {
+    let mut output = Vec::new();
+
+    machine.write_summary(&mut output).unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn degraded_link() {
+    let machine = common::fixture("dual-socket");
+
+    assert_eq!(machine.degraded_links(), [PciAddr::new(0, 0x99, 0, 0)]);
+    assert_eq!(
+        summary(&machine),
+        "4 roots, 0 switches, 3 endpoints, 1 degraded link\n"
+    );
+}
+
+#[test]
+fn switch() {
+    let machine = common::fixture("pcie-switch");
+
+    assert!(machine.degraded_links().is_empty());
+    assert_eq!(
+        summary(&machine),
+        "1 root, 1 switch, 2 endpoints, 0 degraded links\n"
+    );
+}
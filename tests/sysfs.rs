@@ -0,0 +1,184 @@
+mod common;
+
+use std::{
+    env, fs,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+};
+
+use pcigraph::{AspmState, GraphOptions, Machine, PciAddr, PciDevice};
+
+fn write_attrs(dir: &Path, attrs: &[(&str, &str)]) {
+    fs::create_dir_all(dir).unwrap();
+
+    for (attr, value) in attrs {
+        fs::write(dir.join(attr), format!("{}\n", value)).unwrap();
+    }
+}
+
+//
+// A configuration space with a PCI Express capability at 0x40 and room
+// for extended capabilities.
+//
+fn config(header_type: u8, flags: u16, lnk_cap: u32, lnk_ctl: u16) -> Vec<u8> {
+    let mut config = vec![0; 0x110];
+
+    config[0x06] = 0x10;
+    config[0x0e] = header_type;
+    config[0x34] = 0x40;
+    config[0x40] = 0x10;
+    config[0x42..0x44].copy_from_slice(&flags.to_le_bytes());
+    config[0x4c..0x50].copy_from_slice(&lnk_cap.to_le_bytes());
+    config[0x50..0x52].copy_from_slice(&lnk_ctl.to_le_bytes());
+
+    config
+}
+
+fn sysfs_tree(name: &str) -> PathBuf {
+    let root = env::temp_dir().join(format!("pcigraph-sysfs-{}-{}", name, std::process::id()));
+
+    let _ = fs::remove_dir_all(&root);
+
+    let devices = root.join("bus/pci/devices");
+
+    //
+    // A root port in slot 3, with ASPM L0s and L1 supported and L1
+    // enabled.
+    //
+    let port = devices.join("0000:00:01.0");
+
+    write_attrs(
+        &port,
+        &[
+            ("vendor", "0x8086"),
+            ("device", "0x1901"),
+            ("class", "0x060400"),
+            ("numa_node", "1"),
+            ("max_link_speed", "16.0 GT/s PCIe"),
+            ("max_link_width", "4"),
+            ("current_link_speed", "8.0 GT/s PCIe"),
+            ("current_link_width", "4"),
+        ],
+    );
+
+    let mut port_config = config(0x01, 0x0142, (1 << 24) | (3 << 10), 0x0002);
+
+    port_config[0x18..0x1c].copy_from_slice(&[0x00, 0x01, 0x01, 0x00]);
+
+    fs::write(port.join("config"), port_config).unwrap();
+
+    symlink("../../../kernel/iommu_groups/7", port.join("iommu_group")).unwrap();
+    symlink("../../../bus/pci/drivers/pcieport", port.join("driver")).unwrap();
+
+    write_attrs(&root.join("bus/pci/slots/3"), &[("address", "0000:00:01")]);
+
+    //
+    // An NVMe drive with a serial number, without ASPM enabled.
+    //
+    let nvme = devices.join("0000:01:00.0");
+
+    write_attrs(
+        &nvme,
+        &[
+            ("vendor", "0x144d"),
+            ("device", "0xa80a"),
+            ("class", "0x010802"),
+            ("subsystem_vendor", "0x144d"),
+            ("subsystem_device", "0xa801"),
+            ("numa_node", "-1"),
+            ("max_link_speed", "16.0 GT/s PCIe"),
+            ("max_link_width", "4"),
+            ("current_link_speed", "8.0 GT/s PCIe"),
+            ("current_link_width", "4"),
+        ],
+    );
+
+    let mut nvme_config = config(0x00, 0x0002, 3 << 10, 0x0000);
+
+    nvme_config[0x100..0x104].copy_from_slice(&0x0001_0003u32.to_le_bytes());
+    nvme_config[0x104..0x10c].copy_from_slice(&0x0011_2233_4455_6677u64.to_le_bytes());
+
+    fs::write(nvme.join("config"), nvme_config).unwrap();
+
+    symlink("../../../bus/pci/drivers/nvme", nvme.join("driver")).unwrap();
+
+    root
+}
+
+fn sysfs_machine(name: &str) -> Machine {
+    let dir = sysfs_tree(name);
+
+    let mut machine = Machine::default();
+
+    machine.parse_sysfs(dir.join("bus/pci")).unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    machine
+}
+
+fn device(machine: &Machine, addr: PciAddr) -> &PciDevice {
+    machine
+        .devices_where(|dev| dev.addr() == addr)
+        .next()
+        .unwrap()
+}
+
+#[test]
+fn root_port() {
+    let machine = sysfs_machine("root_port");
+
+    let port = device(&machine, PciAddr::new(0, 0, 1, 0));
+
+    assert_eq!((port.vendor_id(), port.device_id()), (0x8086, 0x1901));
+    assert_eq!(port.class_code(), Some(0x0604));
+    assert!(port.is_root_port());
+    assert_eq!(port.secondary_bus(), Some(1));
+    assert_eq!(port.subordinate_bus(), Some(1));
+    assert_eq!(port.numa_node(), Some(1));
+    assert_eq!(port.iommu_group(), Some(7));
+    assert_eq!(port.physical_slot(), Some("3"));
+    assert_eq!(port.kernel_driver(), Some("pcieport"));
+    assert_eq!(port.aspm_capable(), Some(AspmState::L0sL1));
+    assert_eq!(port.aspm_enabled(), Some(AspmState::L1));
+
+    let lnk_cap = port.lnk_cap().unwrap();
+
+    assert_eq!(
+        (lnk_cap.port(), lnk_cap.speed(), lnk_cap.width()),
+        (Some(1), 16.0, 4)
+    );
+
+    let lnk_sta = port.lnk_sta().unwrap();
+
+    assert_eq!((lnk_sta.speed(), lnk_sta.width()), (8.0, 4));
+    assert!(lnk_sta.is_downgraded());
+}
+
+#[test]
+fn endpoint() {
+    let machine = sysfs_machine("endpoint");
+
+    let nvme = device(&machine, PciAddr::new(0, 1, 0, 0));
+
+    assert!(nvme.is_endpoint());
+    assert_eq!(nvme.class_code(), Some(0x0108));
+    assert_eq!(nvme.subsystem_id(), Some((0x144d, 0xa801)));
+    assert_eq!(nvme.numa_node(), None);
+    assert_eq!(nvme.iommu_group(), None);
+    assert_eq!(nvme.physical_slot(), None);
+    assert_eq!(nvme.serial_number(), Some(0x0011_2233_4455_6677));
+    assert_eq!(nvme.kernel_driver(), Some("nvme"));
+    assert_eq!(nvme.aspm_enabled(), Some(AspmState::None));
+}
+
+#[test]
+fn render() {
+    let output = common::graph(&sysfs_machine("render"), &GraphOptions::default());
+
+    assert!(output.contains(concat!(
+        "\t\"0000:00:01.0\" -- \"0000:01:00.0\" ",
+        "[ label=\"8GT/s x4\\n(downgraded) (cap 16GT/s x4)\" color=red ];\n"
+    )));
+    assert!(output.contains("label=\"Samsung 980 PRO\\nnvme\\n0000:01:00.0\""));
+}
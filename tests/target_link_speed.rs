@@ -0,0 +1,100 @@
+mod common;
+
+use pcigraph::{GraphOptions, LnkCap, LnkSta, Machine, PciDevice};
+
+fn lnk_ctl2(target_speed: Option<&str>) -> String {
+    match target_speed {
+        Some(target_speed) => format!(
+            "\t\tLnkCtl2:\tTarget Link Speed: {}GT/s, EnterCompliance- SpeedDis-\n",
+            target_speed
+        ),
+        None => String::new(),
+    }
+}
+
+fn machine(port_target: Option<&str>, dev_target: Option<&str>, width: u8) -> Machine {
+    let input = format!(
+        concat!(
+            "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:1901]\n",
+            "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+            "\tCapabilities: [40] Express (v2) Root Port (Slot-), MSI 00\n",
+            "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x4, ASPM not supported\n",
+            "\t\tLnkSta:\tSpeed 8GT/s (downgraded), Width x{width}\n",
+            "{port_lnk_ctl2}",
+            "\n",
+            "01:00.0 Non-Volatile memory controller [0108]: Acme NVMe SSD [1234:5678]\n",
+            "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x4, ASPM not supported\n",
+            "\t\tLnkSta:\tSpeed 8GT/s (downgraded), Width x{width}\n",
+            "{dev_lnk_ctl2}",
+            "\n",
+        ),
+        width = width,
+        port_lnk_ctl2 = lnk_ctl2(port_target),
+        dev_lnk_ctl2 = lnk_ctl2(dev_target)
+    );
+
+    common::machine(&input)
+}
+
+fn edge(machine: &Machine) -> String {
+    common::graph(machine, &GraphOptions::default())
+        .lines()
+        .find(|line| line.starts_with("\t\"0000:00:01.0\" -- \"0000:01:00.0\""))
+        .unwrap()
+        .to_string()
+}
+
+fn endpoint(machine: &Machine) -> &PciDevice {
+    machine.endpoints().next().unwrap()
+}
+
+#[test]
+fn target_link_speed() {
+    assert_eq!(
+        endpoint(&machine(None, Some("8"), 4)).target_link_speed(),
+        Some(8.0)
+    );
+    assert_eq!(endpoint(&machine(None, None, 4)).target_link_speed(), None);
+}
+
+#[test]
+fn is_capped_against() {
+    let lnk_cap = LnkCap::new(16.0, 4);
+
+    assert!(LnkSta::new(8.0, 4, true).is_capped_against(&lnk_cap, 8.0));
+    assert!(!LnkSta::new(8.0, 4, true).is_capped_against(&lnk_cap, 16.0));
+    assert!(!LnkSta::new(8.0, 2, true).is_capped_against(&lnk_cap, 8.0));
+    assert!(!LnkSta::new(16.0, 4, false).is_capped_against(&lnk_cap, 16.0));
+}
+
+#[test]
+fn capped() {
+    //
+    // Either end of the link can have its Target Link Speed lowered.
+    //
+    for machine in [machine(None, Some("8"), 4), machine(Some("8"), None, 4)] {
+        assert_eq!(
+            edge(&machine),
+            "\t\"0000:00:01.0\" -- \"0000:01:00.0\" [ label=\"8GT/s x4\\n(capped)\" ];"
+        );
+    }
+}
+
+#[test]
+fn downgraded() {
+    //
+    // A link that runs below its Target Link Speed, or that is also
+    // narrower than it should be, is still downgraded.
+    //
+    for machine in [
+        machine(None, None, 4),
+        machine(None, Some("16"), 4),
+        machine(None, Some("8"), 2),
+    ] {
+        let edge = edge(&machine);
+
+        assert!(edge.contains("(downgraded)"), "{}", edge);
+        assert!(edge.ends_with(" color=red ];"), "{}", edge);
+    }
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use pcigraph::{GraphOptions, Machine};
+
+#[test]
+fn endpoint_tooltip() {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(
+            &mut concat!(
+                "00:01.0 PCI bridge [0604]: Intel Corporation Device [8086:09ab]\n",
+                "\tCapabilities: [40] Express (v2) Root Port (Slot+), MSI 00\n",
+                "\t\tLnkCap:\tPort #1, Speed 16GT/s, Width x4, ASPM L1, Exit Latency L1 <4us\n",
+                "\t\tLnkSta:\tSpeed 16GT/s, Width x4\n",
+                "\tBus: primary=00, secondary=01, subordinate=01, sec-latency=0\n",
+                "\n",
+                "01:00.0 Non-Volatile memory controller [0108]: ",
+                "Vendor \"Quoted\" NVMe \\ SSD [144d:a824]\n",
+                "\tNUMA node: 1\n",
+                "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+                "\t\tLnkCap:\tPort #0, Speed 16GT/s, Width x4, ASPM L1, Exit Latency L1 <64us\n",
+                "\t\tLnkSta:\tSpeed 8GT/s (downgraded), Width x4\n",
+                "\tCapabilities: [100 v1] Device Serial Number 00-11-22-33-44-55-66-77\n",
+                "\tKernel driver in use: nvme\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+    let options = GraphOptions {
+        tooltips: true,
+        ..GraphOptions::default()
+    };
+
+    let output = common::graph(&machine, &options);
+
+    assert!(output.contains(concat!(
+        "tooltip=\"0000:01:00.0 Vendor \\\"Quoted\\\" NVMe \\\\ SSD\\n",
+        "LnkCap: 16GT/s (Gen4) x4\\n",
+        "LnkSta: 8GT/s (Gen3) x4 (downgraded)\\n",
+        "Driver: nvme\\n",
+        "NUMA node: 1\\n",
+        "Serial: 0011223344556677\"",
+    )));
+}
+
+#[test]
+fn no_tooltips_by_default() {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(
+            &mut concat!(
+                "01:00.0 Non-Volatile memory controller [0108]: ",
+                "Samsung Electronics Co Ltd NVMe SSD Controller PM173X [144d:a824]\n",
+                "\tCapabilities: [70] Express (v2) Endpoint, MSI 00\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+    assert!(!common::graph(&machine, &GraphOptions::default()).contains("tooltip="));
+}
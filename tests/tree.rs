@@ -0,0 +1,84 @@
+use std::fs;
+
+use pcigraph::{LnkCap, LnkSta, Machine, PciAddr, PciDevice, PortType};
+
+fn tree(machine: &Machine) -> String {
+    let mut output = Vec::new();
+
+    machine.write_tree(&mut output).unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+fn bridge(addr: PciAddr, secondary: u8, subordinate: u8) -> PciDevice {
+    PciDevice::builder(addr, 0x1a03, 0x1150)
+        .port_type(PortType::PciBridge)
+        .buses(secondary, subordinate)
+        .build()
+}
+
+#[test]
+fn pcie_switch() {
+    let input = fs::read_to_string("tests/fixtures/pcie-switch.txt").unwrap();
+
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    assert_eq!(
+        tree(&machine),
+        concat!(
+            "0000:40:01.1 Advanced Micro Devices, Inc. [AMD] Starship/Matisse GPP Bridge (pcieport)\n",
+            "`-- 0000:41:00.0 Broadcom / LSI PEX88096 PCIe Gen 4 Switch (pcieport) 16GT/s x16\n",
+            "    |-- 0000:42:00.0 Broadcom / LSI PEX88096 PCIe Gen 4 Switch (pcieport) 16GT/s x16\n",
+            "    |   `-- 0000:43:00.0 A100 SXM4 40GB (nvidia) 16GT/s x16\n",
+            "    `-- 0000:42:01.0 Broadcom / LSI PEX88096 PCIe Gen 4 Switch (pcieport) 16GT/s x4\n",
+            "        `-- 0000:44:00.0 Samsung NVMe (nvme) 16GT/s x4\n",
+        )
+    );
+}
+
+#[test]
+fn bad_bus_ranges() {
+    let mut machine = Machine::default();
+
+    machine.add_device(
+        PciDevice::builder(PciAddr::new(0, 0, 1, 0), 0x8086, 0x1901)
+            .port_type(PortType::RootPort)
+            .buses(1, 2)
+            .lnk_cap(LnkCap::new(8.0, 4))
+            .lnk_sta(LnkSta::new(8.0, 4, false))
+            .build(),
+    );
+
+    //
+    // A bridge that claims its own bus as its secondary bus, and one that
+    // loops back to the bus it is on.
+    //
+    machine.add_device(bridge(PciAddr::new(0, 1, 0, 0), 1, 1));
+    machine.add_device(bridge(PciAddr::new(0, 1, 1, 0), 2, 2));
+    machine.add_device(bridge(PciAddr::new(0, 2, 0, 0), 1, 2));
+
+    assert_eq!(
+        tree(&machine),
+        concat!(
+            "0000:00:01.0 unknown 8086:1901\n",
+            "|-- 0000:01:00.0 unknown 1a03:1150\n",
+            "`-- 0000:01:01.0 unknown 1a03:1150\n",
+            "    `-- 0000:02:00.0 unknown 1a03:1150\n",
+        )
+    );
+}
+
+#[test]
+fn root_port_without_buses() {
+    let mut machine = Machine::default();
+
+    machine.add_device(
+        PciDevice::builder(PciAddr::new(0, 0, 1, 0), 0x8086, 0x1901)
+            .port_type(PortType::RootPort)
+            .build(),
+    );
+
+    assert_eq!(tree(&machine), "");
+}
@@ -0,0 +1,81 @@
+use std::fs::read_to_string;
+
+use pcigraph::{GraphOptions, Machine, PciAddr};
+
+//
+// The pcie-switch fixture, with a Vendor-Specific Extended Capability with
+// the given ID added to the upstream port, and moved to the given domain.
+//
+fn switch(domain: &str, vsec_id: &str) -> String {
+    let input = read_to_string("tests/fixtures/pcie-switch.txt").unwrap();
+
+    let at = input.find("0000:41:00.0 ").unwrap();
+    let at = at + input[at..].find("\tKernel driver in use").unwrap();
+
+    let input = format!(
+        "{}\tCapabilities: [b70 v1] Vendor Specific Information: ID={} Rev=0 Len=010 <?>\n{}",
+        &input[..at],
+        vsec_id,
+        &input[at..]
+    );
+
+    input.replace("0000:", &format!("{}:", domain))
+}
+
+fn explain(input: &str) -> String {
+    let mut machine = Machine::default();
+
+    machine.parse(&mut input.as_bytes()).unwrap();
+
+    let mut output = Vec::new();
+
+    machine
+        .explain_clusters(&mut output, &GraphOptions::default())
+        .unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn vsec_ids() {
+    let mut machine = Machine::default();
+
+    machine
+        .parse(&mut switch("0000", "0001").as_bytes())
+        .unwrap();
+
+    let upstream_port = machine
+        .devices_where(|dev| dev.addr() == PciAddr::new(0, 0x41, 0, 0))
+        .next()
+        .unwrap();
+
+    assert_eq!(upstream_port.vsec_ids(), [(0x0001, 0x010)]);
+}
+
+#[test]
+fn switches_with_same_serial_number() {
+    let output = explain(&format!(
+        "{}\n{}",
+        switch("0000", "0001"),
+        switch("0001", "0001")
+    ));
+
+    assert!(output.contains(concat!(
+        "  identifier: 0000:41:00.0\n",
+        "  reason: address, as another switch has the same serial number\n",
+    )));
+
+    let output = explain(&format!(
+        "{}\n{}",
+        switch("0000", "0001"),
+        switch("0001", "0002")
+    ));
+
+    assert!(output.contains(concat!(
+        "  identifier: 00805e1000000001-0001:010\n",
+        "  reason: Device Serial Number and Vendor-Specific capabilities, as another switch \
+         has the same serial number\n",
+        "  member: 0000:41:00.0\n",
+    )));
+    assert!(output.contains("  identifier: 00805e1000000001-0002:010\n"));
+}